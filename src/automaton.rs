@@ -0,0 +1,184 @@
+//! Glushkov automata of content models. Their states are the positions of
+//! the element and wildcard particles, and an edge from one position to
+//! another means the second may follow the first.
+
+use std::collections::BTreeSet;
+
+use crate::particle::{Particle, Term};
+
+/// How many times a particle with occurrence bounds is repeated when it is
+/// unrolled into positions. Larger bounds are approximated by this many
+/// required and this many optional copies.
+const UNROLL: usize = 4;
+
+#[derive(Debug)]
+pub struct Glushkov<'p, 'a> {
+    /// The element or wildcard particle of each position. A particle that
+    /// may occur more than once has a position for each occurrence.
+    pub positions: Vec<&'p Particle<'a>>,
+    pub first: BTreeSet<usize>,
+    pub follow: Vec<BTreeSet<usize>>,
+    pub last: BTreeSet<usize>,
+    pub nullable: bool,
+}
+
+#[derive(Clone, Default)]
+struct Fragment {
+    first: BTreeSet<usize>,
+    last: BTreeSet<usize>,
+    nullable: bool,
+}
+
+impl<'p, 'a> Glushkov<'p, 'a> {
+    pub fn new(particle: &'p Particle<'a>) -> Self {
+        let mut automaton = Glushkov {
+            positions: Vec::new(),
+            first: BTreeSet::new(),
+            follow: Vec::new(),
+            last: BTreeSet::new(),
+            nullable: true,
+        };
+
+        let fragment = automaton.particle(particle);
+
+        automaton.first = fragment.first;
+        automaton.last = fragment.last;
+        automaton.nullable = fragment.nullable;
+        automaton
+    }
+
+    fn particle(&mut self, particle: &'p Particle<'a>) -> Fragment {
+        let required = particle.min.min(UNROLL);
+        let optional = particle
+            .max
+            .map(|max| max.saturating_sub(particle.min).min(UNROLL));
+
+        let mut fragment = Fragment {
+            nullable: true,
+            ..Fragment::default()
+        };
+
+        for _ in 0..required {
+            let copy = self.term(particle);
+            fragment = self.concat(fragment, copy);
+        }
+
+        match optional {
+            Some(optional) => {
+                for _ in 0..optional {
+                    let mut copy = self.term(particle);
+                    copy.nullable = true;
+                    fragment = self.concat(fragment, copy);
+                }
+            }
+            None => {
+                let mut copy = self.term(particle);
+
+                for &last in &copy.last {
+                    let first = copy.first.clone();
+                    self.follow[last].extend(first);
+                }
+
+                copy.nullable = true;
+                fragment = self.concat(fragment, copy);
+            }
+        }
+
+        fragment
+    }
+
+    fn term(&mut self, particle: &'p Particle<'a>) -> Fragment {
+        match particle.term {
+            Term::Element(_) | Term::Any(_) => {
+                let position = self.positions.len();
+
+                self.positions.push(particle);
+                self.follow.push(BTreeSet::new());
+
+                Fragment {
+                    first: BTreeSet::from([position]),
+                    last: BTreeSet::from([position]),
+                    nullable: false,
+                }
+            }
+            Term::Sequence(ref particles) => particles.iter().fold(
+                Fragment {
+                    nullable: true,
+                    ..Fragment::default()
+                },
+                |fragment, particle| {
+                    let next = self.particle(particle);
+                    self.concat(fragment, next)
+                },
+            ),
+            Term::Choice(ref particles) => {
+                let mut fragment = Fragment {
+                    nullable: particles.is_empty(),
+                    ..Fragment::default()
+                };
+
+                for particle in particles {
+                    let next = self.particle(particle);
+
+                    fragment.first.extend(next.first);
+                    fragment.last.extend(next.last);
+                    fragment.nullable |= next.nullable;
+                }
+
+                fragment
+            }
+            Term::All(ref particles) => {
+                // Each child may be followed by any of the others.
+                let children: Vec<Fragment> = particles.iter().map(|p| self.particle(p)).collect();
+                let mut fragment = Fragment {
+                    nullable: true,
+                    ..Fragment::default()
+                };
+
+                for (i, child) in children.iter().enumerate() {
+                    for (j, other) in children.iter().enumerate() {
+                        if i != j {
+                            for &last in &child.last {
+                                self.follow[last].extend(other.first.iter().cloned());
+                            }
+                        }
+                    }
+
+                    fragment.first.extend(child.first.iter().cloned());
+                    fragment.last.extend(child.last.iter().cloned());
+                    fragment.nullable &= child.nullable;
+                }
+
+                fragment
+            }
+        }
+    }
+
+    fn concat(&mut self, a: Fragment, b: Fragment) -> Fragment {
+        for &last in &a.last {
+            self.follow[last].extend(b.first.iter().cloned());
+        }
+
+        let mut first = a.first;
+        if a.nullable {
+            first.extend(b.first.iter().cloned());
+        }
+
+        let mut last = b.last;
+        if b.nullable {
+            last.extend(a.last);
+        }
+
+        Fragment {
+            first,
+            last,
+            nullable: a.nullable && b.nullable,
+        }
+    }
+
+    /// The sets of positions that may match the same element: the first
+    /// positions, and the positions that may follow each position.
+    pub fn candidates(&self) -> impl Iterator<Item = &BTreeSet<usize>> {
+        std::iter::once(&self.first).chain(self.follow.iter())
+    }
+}
@@ -0,0 +1,544 @@
+//! The simple types built into the schema namespace.
+
+/// How a built-in type is derived.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Derivation {
+    /// A restriction of the named type.
+    Restriction(&'static str),
+    /// A list of the named type.
+    List(&'static str),
+}
+
+const TYPES: &[(&str, Derivation)] = &[
+    ("anySimpleType", Derivation::Restriction("anyType")),
+    ("string", Derivation::Restriction("anySimpleType")),
+    ("boolean", Derivation::Restriction("anySimpleType")),
+    ("decimal", Derivation::Restriction("anySimpleType")),
+    ("float", Derivation::Restriction("anySimpleType")),
+    ("double", Derivation::Restriction("anySimpleType")),
+    ("duration", Derivation::Restriction("anySimpleType")),
+    ("dateTime", Derivation::Restriction("anySimpleType")),
+    ("time", Derivation::Restriction("anySimpleType")),
+    ("date", Derivation::Restriction("anySimpleType")),
+    ("gYearMonth", Derivation::Restriction("anySimpleType")),
+    ("gYear", Derivation::Restriction("anySimpleType")),
+    ("gMonthDay", Derivation::Restriction("anySimpleType")),
+    ("gDay", Derivation::Restriction("anySimpleType")),
+    ("gMonth", Derivation::Restriction("anySimpleType")),
+    ("hexBinary", Derivation::Restriction("anySimpleType")),
+    ("base64Binary", Derivation::Restriction("anySimpleType")),
+    ("anyURI", Derivation::Restriction("anySimpleType")),
+    ("QName", Derivation::Restriction("anySimpleType")),
+    ("NOTATION", Derivation::Restriction("anySimpleType")),
+    ("normalizedString", Derivation::Restriction("string")),
+    ("token", Derivation::Restriction("normalizedString")),
+    ("language", Derivation::Restriction("token")),
+    ("NMTOKEN", Derivation::Restriction("token")),
+    ("NMTOKENS", Derivation::List("NMTOKEN")),
+    ("Name", Derivation::Restriction("token")),
+    ("NCName", Derivation::Restriction("Name")),
+    ("ID", Derivation::Restriction("NCName")),
+    ("IDREF", Derivation::Restriction("NCName")),
+    ("IDREFS", Derivation::List("IDREF")),
+    ("ENTITY", Derivation::Restriction("NCName")),
+    ("ENTITIES", Derivation::List("ENTITY")),
+    ("integer", Derivation::Restriction("decimal")),
+    ("nonPositiveInteger", Derivation::Restriction("integer")),
+    (
+        "negativeInteger",
+        Derivation::Restriction("nonPositiveInteger"),
+    ),
+    ("long", Derivation::Restriction("integer")),
+    ("int", Derivation::Restriction("long")),
+    ("short", Derivation::Restriction("int")),
+    ("byte", Derivation::Restriction("short")),
+    ("nonNegativeInteger", Derivation::Restriction("integer")),
+    (
+        "unsignedLong",
+        Derivation::Restriction("nonNegativeInteger"),
+    ),
+    ("unsignedInt", Derivation::Restriction("unsignedLong")),
+    ("unsignedShort", Derivation::Restriction("unsignedInt")),
+    ("unsignedByte", Derivation::Restriction("unsignedShort")),
+    (
+        "positiveInteger",
+        Derivation::Restriction("nonNegativeInteger"),
+    ),
+    ("dayTimeDuration", Derivation::Restriction("duration")),
+    ("yearMonthDuration", Derivation::Restriction("duration")),
+];
+
+/// The derivation of a built-in simple type, or `None` if `name` is not
+/// one. `anyType` is the only built-in complex type.
+pub fn derivation(name: &str) -> Option<Derivation> {
+    TYPES.iter().find(|(n, _)| *n == name).map(|&(_, d)| d)
+}
+
+/// The name of a built-in simple type, as a static string.
+pub fn simple_type(name: &str) -> Option<&'static str> {
+    TYPES.iter().find(|(n, _)| *n == name).map(|&(n, _)| n)
+}
+
+pub fn is_builtin(name: &str) -> bool {
+    name == "anyType" || derivation(name).is_some()
+}
+
+/// The primitive type a built-in atomic type is derived from, `None` for
+/// the list types and the ur-types.
+pub fn primitive(name: &str) -> Option<&'static str> {
+    let mut name = TYPES.iter().find(|(n, _)| *n == name)?.0;
+
+    loop {
+        match derivation(name)? {
+            Derivation::Restriction("anySimpleType") => return Some(name),
+            Derivation::Restriction(base) => name = base,
+            Derivation::List(_) => return None,
+        }
+    }
+}
+
+/// The facets that apply to a primitive type, or to a list when
+/// `primitive` is `None`.
+pub fn applicable_facets(primitive: Option<&str>) -> &'static [&'static str] {
+    const LENGTHS: &[&str] = &[
+        "length",
+        "minLength",
+        "maxLength",
+        "pattern",
+        "enumeration",
+        "whiteSpace",
+    ];
+    const BOUNDS: &[&str] = &[
+        "pattern",
+        "enumeration",
+        "whiteSpace",
+        "maxInclusive",
+        "maxExclusive",
+        "minInclusive",
+        "minExclusive",
+    ];
+    const DIGITS: &[&str] = &[
+        "totalDigits",
+        "fractionDigits",
+        "pattern",
+        "whiteSpace",
+        "enumeration",
+        "maxInclusive",
+        "maxExclusive",
+        "minInclusive",
+        "minExclusive",
+    ];
+
+    match primitive {
+        Some("boolean") => &["pattern", "whiteSpace"],
+        Some("decimal") => DIGITS,
+        Some("float") | Some("double") | Some("duration") | Some("dateTime") | Some("time")
+        | Some("date") | Some("gYearMonth") | Some("gYear") | Some("gMonthDay") | Some("gDay")
+        | Some("gMonth") => BOUNDS,
+        _ => LENGTHS,
+    }
+}
+
+/// How whitespace in a value is normalised before it is checked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WhiteSpace {
+    Preserve,
+    /// Tabs and line breaks become spaces.
+    Replace,
+    /// As `Replace`, then runs of spaces become one and leading and
+    /// trailing spaces are removed.
+    Collapse,
+}
+
+impl WhiteSpace {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "preserve" => Some(WhiteSpace::Preserve),
+            "replace" => Some(WhiteSpace::Replace),
+            "collapse" => Some(WhiteSpace::Collapse),
+            _ => None,
+        }
+    }
+
+    pub fn normalize(self, value: &str) -> String {
+        match self {
+            WhiteSpace::Preserve => value.to_owned(),
+            WhiteSpace::Replace => value
+                .chars()
+                .map(|c| match c {
+                    '\t' | '\n' | '\r' => ' ',
+                    c => c,
+                })
+                .collect(),
+            WhiteSpace::Collapse => value.split_whitespace().collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+/// The whitespace handling of a built-in type. Only `string` and
+/// `normalizedString` keep any.
+pub fn white_space(name: &str) -> WhiteSpace {
+    match name {
+        "string" | "anySimpleType" => WhiteSpace::Preserve,
+        "normalizedString" => WhiteSpace::Replace,
+        _ => WhiteSpace::Collapse,
+    }
+}
+
+/// Whether `value`, already normalised, is in the lexical space of the
+/// built-in atomic type `name`.
+pub fn is_valid(name: &str, value: &str) -> bool {
+    match name {
+        "anySimpleType" | "string" | "anyURI" => true,
+        "normalizedString" => !value.contains(['\t', '\n', '\r']),
+        "token" => {
+            !value.contains(['\t', '\n', '\r'])
+                && !value.starts_with(' ')
+                && !value.ends_with(' ')
+                && !value.contains("  ")
+        }
+        "language" => is_language(value),
+        "NMTOKEN" => !value.is_empty() && value.chars().all(is_name_char),
+        "Name" => {
+            let mut chars = value.chars();
+            matches!(chars.next(), Some(c) if is_name_start(c) || c == ':')
+                && chars.all(|c| is_name_char(c) || c == ':')
+        }
+        "NCName" | "ID" | "IDREF" | "ENTITY" => is_ncname(value),
+        "QName" | "NOTATION" => is_qname(value),
+        "boolean" => ["true", "false", "1", "0"].contains(&value),
+        "decimal" => is_decimal(value),
+        "float" | "double" => is_float(value),
+        "hexBinary" => {
+            value.len().is_multiple_of(2) && value.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        "base64Binary" => is_base64(value),
+        "duration" => is_duration(value),
+        // The M of minutes may follow the T.
+        "dayTimeDuration" => {
+            is_duration(value) && !value.split('T').next().unwrap_or("").contains(['Y', 'M'])
+        }
+        "yearMonthDuration" => is_duration(value) && !value.contains(['D', 'T']),
+        "dateTime" => match value.find('T') {
+            Some(idx) => is_date(&value[..idx]) && is_time(&value[idx + 1..]),
+            None => false,
+        },
+        "date" => is_date(value),
+        "time" => is_time(value),
+        "gYearMonth" => {
+            is_date(&format!("{}-01", strip_timezone(value).0))
+                && is_timezone(strip_timezone(value).1)
+        }
+        "gYear" => {
+            let (year, timezone) = strip_timezone(value);
+            is_year(year) && is_timezone(timezone)
+        }
+        "gMonthDay" => {
+            let (month_day, timezone) = strip_timezone(value);
+            month_day.starts_with("--")
+                && is_date(&format!("2000{}", &month_day[1..]))
+                && is_timezone(timezone)
+        }
+        "gDay" => {
+            let (day, timezone) = strip_timezone(value);
+            day.starts_with("---")
+                && is_date(&format!("2000-01-{}", &day[3..]))
+                && is_timezone(timezone)
+        }
+        "gMonth" => {
+            let (month, timezone) = strip_timezone(value);
+            month.starts_with("--")
+                && is_date(&format!("2000-{}-01", &month[2..]))
+                && is_timezone(timezone)
+        }
+        name => match integer_range(name) {
+            Some((min, max)) => is_integer_within(value, min, max),
+            None => false,
+        },
+    }
+}
+
+/// The bounds of the built-in integer types, `None` for no bound.
+fn integer_range(name: &str) -> Option<(Option<i128>, Option<i128>)> {
+    let range = match name {
+        "integer" => (None, None),
+        "nonPositiveInteger" => (None, Some(0)),
+        "negativeInteger" => (None, Some(-1)),
+        "nonNegativeInteger" => (Some(0), None),
+        "positiveInteger" => (Some(1), None),
+        "long" => (Some(i64::MIN.into()), Some(i64::MAX.into())),
+        "int" => (Some(i32::MIN.into()), Some(i32::MAX.into())),
+        "short" => (Some(i16::MIN.into()), Some(i16::MAX.into())),
+        "byte" => (Some(i8::MIN.into()), Some(i8::MAX.into())),
+        "unsignedLong" => (Some(0), Some(u64::MAX.into())),
+        "unsignedInt" => (Some(0), Some(u32::MAX.into())),
+        "unsignedShort" => (Some(0), Some(u16::MAX.into())),
+        "unsignedByte" => (Some(0), Some(u8::MAX.into())),
+        _ => return None,
+    };
+
+    Some(range)
+}
+
+fn is_integer_within(value: &str, min: Option<i128>, max: Option<i128>) -> bool {
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+
+    let negative = value.starts_with('-') && digits.bytes().any(|b| b != b'0');
+
+    match value.parse::<i128>() {
+        Ok(n) => min.is_none_or(|min| n >= min) && max.is_none_or(|max| n <= max),
+        // Beyond i128, only the unbounded side of a range can be met.
+        Err(_) if negative => min.is_none(),
+        Err(_) => max.is_none(),
+    }
+}
+
+fn is_decimal(value: &str) -> bool {
+    let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+    let (whole, fraction) = match unsigned.find('.') {
+        Some(idx) => (&unsigned[..idx], &unsigned[idx + 1..]),
+        None => (unsigned, ""),
+    };
+
+    !(whole.is_empty() && fraction.is_empty())
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_float(value: &str) -> bool {
+    if ["INF", "-INF", "+INF", "NaN"].contains(&value) {
+        return true;
+    }
+
+    let (mantissa, exponent) = match value.find(['e', 'E']) {
+        Some(idx) => (&value[..idx], Some(&value[idx + 1..])),
+        None => (value, None),
+    };
+
+    is_decimal(mantissa)
+        && exponent.is_none_or(|exponent| {
+            let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+fn is_base64(value: &str) -> bool {
+    let data: Vec<u8> = value.bytes().filter(|&b| b != b' ').collect();
+    let padding = data.iter().rev().take_while(|&&b| b == b'=').count();
+
+    data.len().is_multiple_of(4)
+        && padding <= 2
+        && data[..data.len() - padding]
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+}
+
+fn is_language(value: &str) -> bool {
+    value.split('-').enumerate().all(|(idx, part)| {
+        (1..=8).contains(&part.len())
+            && if idx == 0 {
+                part.bytes().all(|b| b.is_ascii_alphabetic())
+            } else {
+                part.bytes().all(|b| b.is_ascii_alphanumeric())
+            }
+    })
+}
+
+fn is_duration(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let rest = match value.strip_prefix('P') {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let (date, time) = match rest.find('T') {
+        Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+        None => (rest, None),
+    };
+
+    let components = |mut part: &str, designators: &[char], seconds: bool| {
+        let mut count = 0;
+        let mut allowed = designators;
+
+        while !part.is_empty() {
+            let idx = match part.find(|c: char| !c.is_ascii_digit() && c != '.') {
+                Some(idx) => idx,
+                None => return None,
+            };
+            let (number, designator) = (&part[..idx], part[idx..].chars().next()?);
+            let position = allowed.iter().position(|&d| d == designator)?;
+            let fractional = seconds && designator == 'S';
+
+            if number.is_empty()
+                || (!fractional && number.contains('.'))
+                || (fractional && !is_decimal(number))
+            {
+                return None;
+            }
+
+            allowed = &allowed[position + 1..];
+            part = &part[idx + 1..];
+            count += 1;
+        }
+
+        Some(count)
+    };
+
+    let date = components(date, &['Y', 'M', 'D'], false);
+    let time = match time {
+        Some(time) => components(time, &['H', 'M', 'S'], true).filter(|&count| count > 0),
+        None => Some(0),
+    };
+
+    matches!((date, time), (Some(date), Some(time)) if date + time > 0)
+}
+
+/// Splits a trailing timezone off a date or time.
+pub fn strip_timezone(value: &str) -> (&str, &str) {
+    if let Some(value) = value.strip_suffix('Z') {
+        return (value, "Z");
+    }
+
+    match value.len().checked_sub(6) {
+        Some(idx)
+            if value.is_char_boundary(idx)
+                && (value[idx..].starts_with('+') || value[idx..].starts_with('-'))
+                && value.as_bytes()[idx + 3] == b':' =>
+        {
+            (&value[..idx], &value[idx..])
+        }
+        _ => (value, ""),
+    }
+}
+
+fn is_timezone(timezone: &str) -> bool {
+    match timezone {
+        "" | "Z" => true,
+        timezone => {
+            let hours = timezone.get(1..3).and_then(number);
+            let minutes = timezone.get(4..).and_then(number);
+
+            matches!((hours, minutes), (Some(h), Some(m)) if h < 14 && m < 60 || h == 14 && m == 0)
+        }
+    }
+}
+
+fn number(digits: &str) -> Option<u32> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    digits.parse().ok()
+}
+
+fn is_year(year: &str) -> bool {
+    let digits = year.strip_prefix('-').unwrap_or(year);
+
+    digits.len() >= 4
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && (digits.len() == 4 || !digits.starts_with('0'))
+}
+
+/// A date with an optional timezone, as `xsd:date` has it.
+fn is_date(value: &str) -> bool {
+    let (date, timezone) = strip_timezone(value);
+    let mut parts = date.rsplitn(3, '-');
+    let (day, month, year) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(day), Some(month), Some(year)) => (day, month, year),
+        _ => return false,
+    };
+
+    if day.len() != 2 || month.len() != 2 || !is_year(year) || !is_timezone(timezone) {
+        return false;
+    }
+
+    let (day, month) = match (number(day), number(month)) {
+        (Some(day), Some(month)) => (day, month),
+        _ => return false,
+    };
+    let leap = year
+        .trim_start_matches('-')
+        .parse::<u64>()
+        .is_ok_and(|y| y % 4 == 0 && (y % 100 != 0 || y % 400 == 0));
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+
+    (1..=days).contains(&day)
+}
+
+/// A time of day with an optional timezone, as `xsd:time` has it.
+fn is_time(value: &str) -> bool {
+    let (time, timezone) = strip_timezone(value);
+
+    if time.len() < 8 || !time.is_char_boundary(8) || !is_timezone(timezone) {
+        return false;
+    }
+
+    let (hms, fraction) = time.split_at(8);
+    let bytes = hms.as_bytes();
+
+    if bytes[2] != b':' || bytes[5] != b':' {
+        return false;
+    }
+
+    let fraction_ok = fraction.is_empty()
+        || fraction.len() > 1
+            && fraction.starts_with('.')
+            && fraction[1..].bytes().all(|b| b.is_ascii_digit());
+
+    match (number(&hms[..2]), number(&hms[3..5]), number(&hms[6..])) {
+        (Some(24), Some(0), Some(0)) => fraction.bytes().skip(1).all(|b| b == b'0') && fraction_ok,
+        (Some(h), Some(m), Some(s)) => h < 24 && m < 60 && s < 60 && fraction_ok,
+        _ => false,
+    }
+}
+
+pub fn is_ncname(value: &str) -> bool {
+    let mut chars = value.chars();
+
+    match chars.next() {
+        Some(c) if is_name_start(c) => chars.all(is_name_char),
+        _ => false,
+    }
+}
+
+pub fn is_name_start(c: char) -> bool {
+    matches!(c,
+        'A'..='Z' | '_' | 'a'..='z'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+pub fn is_name_char(c: char) -> bool {
+    match c {
+        '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}' => true,
+        _ => is_name_start(c),
+    }
+}
+
+pub fn is_qname(value: &str) -> bool {
+    let value = value.trim();
+
+    match value.find(':') {
+        Some(idx) => is_ncname(&value[..idx]) && is_ncname(&value[idx + 1..]),
+        None => is_ncname(value),
+    }
+}
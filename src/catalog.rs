@@ -0,0 +1,362 @@
+//! OASIS XML Catalogs 1.1, used to map the locations and namespaces of
+//! imported schemas to local copies.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::rc::Rc;
+
+use crate::errors::Error;
+use crate::resolver::{ResolvedSchema, SchemaResolver};
+use crate::span::Span;
+use crate::uri;
+
+use roxmltree::{Document, Node};
+
+const CATALOG_NAMESPACE: &str = "urn:oasis:names:tc:entity:xmlns:xml:catalog";
+
+const CATALOG: &str = "catalog";
+const GROUP: &str = "group";
+const SYSTEM: &str = "system";
+const REWRITE_SYSTEM: &str = "rewriteSystem";
+const SYSTEM_SUFFIX: &str = "systemSuffix";
+const DELEGATE_SYSTEM: &str = "delegateSystem";
+const URI: &str = "uri";
+const REWRITE_URI: &str = "rewriteURI";
+const URI_SUFFIX: &str = "uriSuffix";
+const DELEGATE_URI: &str = "delegateURI";
+const NEXT_CATALOG: &str = "nextCatalog";
+
+/// The entries for one kind of identifier (system identifiers or URIs).
+/// Every target is absolute, resolved against the entry's base URI.
+#[derive(Debug, Default, PartialEq)]
+pub struct Entries {
+    /// Exact matches: identifier and replacement.
+    pub exact: Vec<(String, String)>,
+    /// Start string and replacement prefix.
+    pub rewrite: Vec<(String, String)>,
+    /// Suffix and replacement.
+    pub suffix: Vec<(String, String)>,
+    /// Start string and the catalog to delegate to.
+    pub delegate: Vec<(String, String)>,
+}
+
+enum Lookup {
+    Found(String),
+    Delegate(Vec<String>),
+    NotFound,
+}
+
+impl Entries {
+    fn lookup(&self, id: &str) -> Lookup {
+        if let Some((_, target)) = self.exact.iter().find(|(exact, _)| exact == id) {
+            return Lookup::Found(target.clone());
+        }
+
+        if let Some((start, prefix)) = self
+            .rewrite
+            .iter()
+            .filter(|(start, _)| id.starts_with(start.as_str()))
+            .max_by_key(|(start, _)| start.len())
+        {
+            return Lookup::Found(format!("{}{}", prefix, &id[start.len()..]));
+        }
+
+        if let Some((_, target)) = self
+            .suffix
+            .iter()
+            .filter(|(suffix, _)| id.ends_with(suffix.as_str()))
+            .max_by_key(|(suffix, _)| suffix.len())
+        {
+            return Lookup::Found(target.clone());
+        }
+
+        let mut delegates: Vec<&(String, String)> = self
+            .delegate
+            .iter()
+            .filter(|(start, _)| id.starts_with(start.as_str()))
+            .collect();
+
+        if delegates.is_empty() {
+            return Lookup::NotFound;
+        }
+
+        delegates.sort_by_key(|(start, _)| std::cmp::Reverse(start.len()));
+
+        Lookup::Delegate(delegates.into_iter().map(|(_, c)| c.clone()).collect())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    System,
+    Uri,
+}
+
+/// A single catalog entry file.
+#[derive(Debug, Default, PartialEq)]
+pub struct Catalog {
+    pub location: String,
+    pub system: Entries,
+    pub uri: Entries,
+    pub next_catalogs: Vec<String>,
+}
+
+impl Catalog {
+    /// Reads the catalog at `location`, a path or `file:` URI.
+    pub fn load(location: &str) -> Result<Self, Error> {
+        let path = uri::to_file_path(location).ok_or_else(|| Error::SchemaNotFound {
+            location: location.to_owned(),
+            span: None,
+        })?;
+        let contents = fs::read_to_string(path)?;
+
+        Catalog::parse(location, &contents)
+    }
+
+    /// Parses a catalog. Relative URIs in it are resolved against
+    /// `location` or the `xml:base` in scope.
+    pub fn parse(location: &str, contents: &str) -> Result<Self, Error> {
+        let doc = Document::parse(contents).map_err(|error| Error::ParseXml {
+            span: Span::text_pos(location, contents, error.pos()),
+            error,
+        })?;
+        let root = doc.root_element();
+
+        let mut catalog = Catalog {
+            location: location.to_owned(),
+            ..Default::default()
+        };
+
+        if root.tag_name().namespace() != Some(CATALOG_NAMESPACE)
+            || root.tag_name().name() != CATALOG
+        {
+            return Err(Error::InvalidCatalog {
+                span: Span::node(location, contents, root),
+            });
+        }
+
+        catalog.parse_entries(root, location);
+
+        Ok(catalog)
+    }
+
+    fn parse_entries(&mut self, node: Node, base: &str) {
+        let base = match node.attribute((roxmltree::NS_XML_URI, "base")) {
+            Some(xml_base) => uri::resolve(base, xml_base),
+            None => base.to_owned(),
+        };
+
+        // Entries from other namespaces, and entries missing a required
+        // attribute, are ignored as the specification requires.
+        for child in node
+            .children()
+            .filter(|n| n.tag_name().namespace() == Some(CATALOG_NAMESPACE))
+        {
+            let child_base = match child.attribute((roxmltree::NS_XML_URI, "base")) {
+                Some(xml_base) => uri::resolve(&base, xml_base),
+                None => base.clone(),
+            };
+            let target = |name: &str| child.attribute(name).map(|t| uri::resolve(&child_base, t));
+            let pair = |key: &str, value: &str| match (child.attribute(key), target(value)) {
+                (Some(key), Some(value)) => Some((key.to_owned(), value)),
+                _ => None,
+            };
+
+            match child.tag_name().name() {
+                GROUP => self.parse_entries(child, &base),
+                SYSTEM => self.system.exact.extend(pair("systemId", "uri")),
+                REWRITE_SYSTEM => self
+                    .system
+                    .rewrite
+                    .extend(pair("systemIdStartString", "rewritePrefix")),
+                SYSTEM_SUFFIX => self.system.suffix.extend(pair("systemIdSuffix", "uri")),
+                DELEGATE_SYSTEM => self
+                    .system
+                    .delegate
+                    .extend(pair("systemIdStartString", "catalog")),
+                URI => self.uri.exact.extend(pair("name", "uri")),
+                REWRITE_URI => self
+                    .uri
+                    .rewrite
+                    .extend(pair("uriStartString", "rewritePrefix")),
+                URI_SUFFIX => self.uri.suffix.extend(pair("uriSuffix", "uri")),
+                DELEGATE_URI => self.uri.delegate.extend(pair("uriStartString", "catalog")),
+                NEXT_CATALOG => self.next_catalogs.extend(target("catalog")),
+                _ => {}
+            }
+        }
+    }
+
+    fn entries(&self, kind: Kind) -> &Entries {
+        match kind {
+            Kind::System => &self.system,
+            Kind::Uri => &self.uri,
+        }
+    }
+}
+
+/// Resolves schema locations through a list of catalogs. Catalogs referred
+/// to by `nextCatalog` and `delegate*` entries are loaded on first use;
+/// ones that do not exist are skipped.
+#[derive(Debug, Default)]
+pub struct CatalogResolver {
+    catalogs: Vec<Rc<Catalog>>,
+    loaded: RefCell<HashMap<String, Option<Rc<Catalog>>>>,
+}
+
+impl CatalogResolver {
+    pub fn new() -> Self {
+        CatalogResolver::default()
+    }
+
+    /// Adds the catalog at `location` to the end of the catalog list.
+    pub fn load(&mut self, location: &str) -> Result<(), Error> {
+        self.catalogs.push(Rc::new(Catalog::load(location)?));
+
+        Ok(())
+    }
+
+    /// Adds an already parsed catalog to the end of the catalog list.
+    pub fn push(&mut self, catalog: Catalog) {
+        self.catalogs.push(Rc::new(catalog));
+    }
+
+    /// Maps a system identifier, returning the URI it resolves to.
+    pub fn resolve_system(&self, system_id: &str) -> Result<Option<String>, Error> {
+        self.resolve_in(&self.catalogs, Kind::System, system_id, &mut HashSet::new())
+    }
+
+    /// Maps a URI reference, such as a namespace name or a schema location.
+    pub fn resolve_uri(&self, uri: &str) -> Result<Option<String>, Error> {
+        self.resolve_in(&self.catalogs, Kind::Uri, uri, &mut HashSet::new())
+    }
+
+    fn resolve_in(
+        &self,
+        catalogs: &[Rc<Catalog>],
+        kind: Kind,
+        id: &str,
+        visited: &mut HashSet<String>,
+    ) -> Result<Option<String>, Error> {
+        for catalog in catalogs {
+            if let Some(resolved) = self.lookup(catalog, kind, id, visited)? {
+                return Ok(Some(resolved));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn lookup(
+        &self,
+        catalog: &Catalog,
+        kind: Kind,
+        id: &str,
+        visited: &mut HashSet<String>,
+    ) -> Result<Option<String>, Error> {
+        if !visited.insert(catalog.location.clone()) {
+            return Ok(None);
+        }
+
+        match catalog.entries(kind).lookup(id) {
+            Lookup::Found(resolved) => return Ok(Some(resolved)),
+            // Delegation replaces the remaining catalogs entirely.
+            Lookup::Delegate(locations) => {
+                let mut delegates = Vec::new();
+
+                for location in locations {
+                    delegates.extend(self.catalog(&location)?);
+                }
+
+                return self.resolve_in(&delegates, kind, id, visited);
+            }
+            Lookup::NotFound => {}
+        }
+
+        for location in &catalog.next_catalogs {
+            if let Some(next) = self.catalog(location)? {
+                if let Some(resolved) = self.lookup(&next, kind, id, visited)? {
+                    return Ok(Some(resolved));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn catalog(&self, location: &str) -> Result<Option<Rc<Catalog>>, Error> {
+        if let Some(catalog) = self.loaded.borrow().get(location) {
+            return Ok(catalog.clone());
+        }
+
+        let catalog = match Catalog::load(location) {
+            Ok(catalog) => Some(Rc::new(catalog)),
+            Err(Error::IO { ref error, .. }) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        self.loaded
+            .borrow_mut()
+            .insert(location.to_owned(), catalog.clone());
+
+        Ok(catalog)
+    }
+}
+
+impl SchemaResolver for CatalogResolver {
+    /// Looks up the location, resolved against `base` and then as written,
+    /// as a system identifier and as a URI, and finally the import's
+    /// namespace as a URI. Mapped documents are read from the filesystem.
+    fn resolve(
+        &self,
+        base: Option<&str>,
+        schema_location: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<ResolvedSchema>, Error> {
+        let mut candidates = Vec::new();
+
+        if let Some(base) = base {
+            candidates.push(uri::resolve(base, schema_location));
+        }
+
+        if !candidates.iter().any(|c| c == schema_location) {
+            candidates.push(schema_location.to_owned());
+        }
+
+        let mut mapped = None;
+
+        for candidate in &candidates {
+            mapped = match self.resolve_system(candidate)? {
+                Some(mapped) => Some(mapped),
+                None => self.resolve_uri(candidate)?,
+            };
+
+            if mapped.is_some() {
+                break;
+            }
+        }
+
+        if mapped.is_none() {
+            if let Some(namespace) = namespace {
+                mapped = self.resolve_uri(namespace)?;
+            }
+        }
+
+        let location = match mapped {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+
+        let path = uri::to_file_path(&location).ok_or_else(|| Error::SchemaNotFound {
+            location: location.clone(),
+            span: None,
+        })?;
+
+        Ok(Some(ResolvedSchema {
+            contents: fs::read(path)?,
+            location,
+        }))
+    }
+}
@@ -1,12 +1,14 @@
-use std::convert::From;
+use std::collections::HashMap;
 use std::default::Default;
 use std::str::FromStr;
 
-use crate::element::Element;
+use crate::element::{DefaultFixed, Element};
 use crate::errors::Error;
 use crate::restriction::Restriction;
-use crate::shared::Occurrence;
-use crate::traits::TryFrom;
+use crate::shared::{BlockDefault, Final, FormDefault, Occurrence, QName};
+use crate::simple_type::SimpleType;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
 
 use roxmltree::Node;
 
@@ -19,47 +21,106 @@ const ANY: &str = "any";
 const CHOICE: &str = "choice";
 const RESTRICTION: &str = "restriction";
 const EXTENSION: &str = "extension";
+const SIMPLE_TYPE: &str = "simpleType";
+const ALL: &str = "all";
+const GROUP: &str = "group";
+const ATTRIBUTE_GROUP: &str = "attributeGroup";
+const ANY_ATTRIBUTE: &str = "anyAttribute";
+const ANNOTATION: &str = "annotation";
 
 #[derive(Debug, Default, PartialEq)]
 pub struct ComplexType {
     pub name: Option<String>,
+    pub r#final: Option<Final>,
+    /// The derivations that `xsi:type` may not substitute for this type.
+    pub block: Option<BlockDefault>,
+    /// Whether character data may appear between the child elements.
+    pub mixed: bool,
     pub sequence: Option<Sequence>,
-    pub attribute: Option<Attribute>,
+    pub attributes: Vec<Attribute>,
+    /// References to named attribute groups.
+    pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Box<AnyAttribute>>,
     pub simple_content: Option<SimpleContent>,
     pub choice: Option<Choice>,
+    pub all: Option<All>,
+    /// A reference to a named model group.
+    pub group: Option<Group>,
     pub complex_content: Option<ComplexContent>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for ComplexType {
-    type Error = crate::errors::Error;
+impl ComplexType {
+    /// The base of a complex type derived by extension or restriction.
+    pub fn base(&self) -> Option<&String> {
+        let content = match (&self.complex_content, &self.simple_content) {
+            (Some(complex_content), _) => &complex_content.content,
+            (None, Some(simple_content)) => &simple_content.content,
+            (None, None) => return None,
+        };
+
+        match *content {
+            Content::Extension(ref extension) => Some(&extension.base),
+            Content::Restriction(ref restriction) => restriction.base.as_ref(),
+        }
+    }
+
+    /// Whether a complex type is derived by extension, rather than by
+    /// restriction.
+    pub fn is_extension(&self) -> bool {
+        let content = self
+            .complex_content
+            .as_ref()
+            .map(|c| &c.content)
+            .or_else(|| self.simple_content.as_ref().map(|c| &c.content));
+
+        matches!(content, Some(Content::Extension(_)))
+    }
+}
 
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let mut complex_type = ComplexType::default();
+impl FromNode for ComplexType {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut complex_type = ComplexType {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
 
         if let Some(name) = node.attribute("name") {
             complex_type.name = Some(name.to_owned());
         }
 
-        for child in node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-        {
+        complex_type.r#final = context.attribute(node, "final")?;
+        complex_type.block = context.attribute(node, "block")?;
+
+        if let Some(mixed) = node.attribute("mixed") {
+            complex_type.mixed = mixed == "true" || mixed == "1";
+        }
+
+        for child in context.children(node) {
+            let child = child?;
+
             match child.tag_name().name() {
-                SEQUENCE => complex_type.sequence = Some(Sequence::try_from(child)?),
-                ATTRIBUTE => complex_type.attribute = Some(Attribute::try_from(child)?),
-                SIMPLE_CONTENT => {
-                    complex_type.simple_content = Some(SimpleContent::try_from(child)?)
-                }
-                CHOICE => complex_type.choice = Some(Choice::try_from(child)?),
-                COMPLEX_CONTENT => {
-                    complex_type.complex_content = Some(ComplexContent::try_from(child)?)
-                }
-                unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
-                        parent: node.tag_name().name().to_owned(),
-                        tag: unknown.to_owned(),
-                    })
-                }
+                SEQUENCE => complex_type.sequence = context.parse(child)?,
+                ATTRIBUTE => complex_type
+                    .attributes
+                    .extend(context.parse::<Attribute>(child)?),
+                ATTRIBUTE_GROUP => complex_type
+                    .attribute_groups
+                    .extend(context.parse::<AttributeGroup>(child)?),
+                ANY_ATTRIBUTE => complex_type.any_attribute = context.parse(child)?.map(Box::new),
+                ANNOTATION => {}
+                SIMPLE_CONTENT => complex_type.simple_content = context.parse(child)?,
+                CHOICE => complex_type.choice = context.parse(child)?,
+                ALL => complex_type.all = context.parse(child)?,
+                GROUP => complex_type.group = context.parse(child)?,
+                COMPLEX_CONTENT => complex_type.complex_content = context.parse(child)?,
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
             }
         }
 
@@ -71,41 +132,51 @@ impl<'a, 'd> TryFrom<Node<'a, 'd>> for ComplexType {
 pub struct Sequence {
     pub min_occurrences: Option<Occurrence>,
     pub max_occurrences: Option<Occurrence>,
-    pub elements: Vec<Element>,
-    pub anys: Vec<Any>,
-    pub choice: Option<Choice>,
-    pub sequences: Vec<Sequence>,
+    pub particles: Vec<NestedParticle>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Sequence {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let mut sequence = Sequence::default();
+impl FromNode for Sequence {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut sequence = Sequence {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
 
-        if let Some(min_occurrences) = node.attribute("minOccurs") {
-            sequence.min_occurrences = Some(Occurrence::from_str(min_occurrences).unwrap());
-        }
+        sequence.min_occurrences = context.attribute(node, "minOccurs")?;
+        sequence.max_occurrences = context.attribute(node, "maxOccurs")?;
 
-        if let Some(max_occurrences) = node.attribute("maxOccurs") {
-            sequence.max_occurrences = Some(Occurrence::from_str(max_occurrences).unwrap());
-        }
+        for child in context.children(node) {
+            let child = child?;
 
-        for child in node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-        {
             match child.tag_name().name() {
-                ELEMENT => sequence.elements.push(Element::try_from(child)?),
-                ANY => sequence.anys.push(Any::from(child)),
-                CHOICE => sequence.choice = Some(Choice::try_from(child)?),
-                SEQUENCE => sequence.sequences.push(Sequence::try_from(child)?),
-                unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
-                        parent: node.tag_name().name().to_owned(),
-                        tag: unknown.to_owned(),
-                    })
-                }
+                ELEMENT => sequence.particles.extend(
+                    context
+                        .parse::<Element>(child)?
+                        .map(|element| NestedParticle::Element(Box::new(element))),
+                ),
+                ANY => sequence
+                    .particles
+                    .extend(context.parse(child)?.map(NestedParticle::Any)),
+                CHOICE => sequence
+                    .particles
+                    .extend(context.parse(child)?.map(NestedParticle::Choice)),
+                SEQUENCE => sequence
+                    .particles
+                    .extend(context.parse(child)?.map(NestedParticle::Sequence)),
+                GROUP => sequence.particles.extend(
+                    context
+                        .parse::<Group>(child)?
+                        .map(|group| NestedParticle::Group(Box::new(group))),
+                ),
+                ANNOTATION => {}
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
             }
         }
 
@@ -113,120 +184,356 @@ impl<'a, 'd> TryFrom<Node<'a, 'd>> for Sequence {
     }
 }
 
+/// A particle of a sequence or choice. They are kept in document order.
+#[derive(Debug, PartialEq)]
+pub enum NestedParticle {
+    Element(Box<Element>),
+    Any(Any),
+    Choice(Choice),
+    Sequence(Sequence),
+    Group(Box<Group>),
+}
+
 #[derive(Debug, Default, PartialEq)]
 pub struct Any {
     pub min_occurrences: Option<Occurrence>,
     pub max_occurrences: Option<Occurrence>,
     pub namespace: Option<String>,
-    // TODO: processContents: Enum {strict, lax, skip}
+    pub process_contents: ProcessContents,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> From<Node<'a, 'd>> for Any {
-    fn from(node: Node) -> Self {
-        let mut any = Any::default();
+impl FromNode for Any {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut any = Any {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        any.min_occurrences = context.attribute(node, "minOccurs")?;
+        any.max_occurrences = context.attribute(node, "maxOccurs")?;
 
-        if let Some(min_occurrences) = node.attribute("minOccurs") {
-            any.min_occurrences = Some(Occurrence::from_str(min_occurrences).unwrap());
+        if let Some(namespace) = node.attribute("namespace") {
+            any.namespace = Some(namespace.to_owned());
         }
 
-        if let Some(max_occurrences) = node.attribute("maxOccurs") {
-            any.max_occurrences = Some(Occurrence::from_str(max_occurrences).unwrap());
+        if let Some(process_contents) = context.attribute(node, "processContents")? {
+            any.process_contents = process_contents;
         }
 
-        if let Some(namespace) = node.attribute("namespace") {
-            any.namespace = Some(namespace.to_owned());
+        Ok(any)
+    }
+}
+
+/// How the elements a wildcard matches are validated.
+#[derive(Debug, Default, PartialEq)]
+pub enum ProcessContents {
+    /// Against their global declaration, which must exist.
+    #[default]
+    Strict,
+    /// Against their global declaration, if there is one.
+    Lax,
+    /// Not at all.
+    Skip,
+}
+
+impl FromStr for ProcessContents {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(ProcessContents::Strict),
+            "lax" => Ok(ProcessContents::Lax),
+            "skip" => Ok(ProcessContents::Skip),
+            _ => Err(Error::InvalidAttributeValue {
+                attribute: "processContents".to_owned(),
+                value: s.to_owned(),
+                span: None,
+            }),
+        }
+    }
+}
+
+/// An attribute wildcard, which allows attributes from the namespaces it
+/// names besides the declared ones.
+#[derive(Debug, Default, PartialEq)]
+pub struct AnyAttribute {
+    pub namespace: Option<String>,
+    pub process_contents: ProcessContents,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for AnyAttribute {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut any_attribute = AnyAttribute {
+            namespace: node.attribute("namespace").map(|n| n.to_owned()),
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        if let Some(process_contents) = context.attribute(node, "processContents")? {
+            any_attribute.process_contents = process_contents;
         }
 
-        any
+        Ok(any_attribute)
     }
 }
 
 #[derive(Debug, Default, PartialEq)]
 pub struct Choice {
+    pub min_occurrences: Option<Occurrence>,
+    pub max_occurrences: Option<Occurrence>,
+    pub particles: Vec<NestedParticle>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for Choice {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut choice = Choice {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        choice.min_occurrences = context.attribute(node, "minOccurs")?;
+        choice.max_occurrences = context.attribute(node, "maxOccurs")?;
+
+        for child in context.children(node) {
+            let child = child?;
+
+            match child.tag_name().name() {
+                ELEMENT => choice.particles.extend(
+                    context
+                        .parse::<Element>(child)?
+                        .map(|element| NestedParticle::Element(Box::new(element))),
+                ),
+                ANY => choice
+                    .particles
+                    .extend(context.parse(child)?.map(NestedParticle::Any)),
+                CHOICE => choice
+                    .particles
+                    .extend(context.parse(child)?.map(NestedParticle::Choice)),
+                SEQUENCE => choice
+                    .particles
+                    .extend(context.parse(child)?.map(NestedParticle::Sequence)),
+                GROUP => choice.particles.extend(
+                    context
+                        .parse::<Group>(child)?
+                        .map(|group| NestedParticle::Group(Box::new(group))),
+                ),
+                ANNOTATION => {}
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
+            }
+        }
+
+        Ok(choice)
+    }
+}
+
+/// An `all` group: each of its elements may appear once, in any order.
+#[derive(Debug, Default, PartialEq)]
+pub struct All {
     pub min_occurrences: Option<Occurrence>,
     pub max_occurrences: Option<Occurrence>,
     pub elements: Vec<Element>,
-    pub any: Option<Any>,
-    pub sequences: Vec<Sequence>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Choice {
-    type Error = crate::errors::Error;
+impl FromNode for All {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut all = All {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        all.min_occurrences = context.attribute(node, "minOccurs")?;
+        all.max_occurrences = context.attribute(node, "maxOccurs")?;
 
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let mut choice = Choice::default();
+        for child in context.children(node) {
+            let child = child?;
 
-        if let Some(min_occurrences) = node.attribute("minOccurs") {
-            choice.min_occurrences = Some(Occurrence::from_str(min_occurrences).unwrap());
+            match child.tag_name().name() {
+                ELEMENT => all.elements.extend(context.parse::<Element>(child)?),
+                ANNOTATION => {}
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
+            }
         }
 
-        if let Some(max_occurrences) = node.attribute("maxOccurs") {
-            choice.max_occurrences = Some(Occurrence::from_str(max_occurrences).unwrap());
+        Ok(all)
+    }
+}
+
+/// A named model group at the top level of a schema, or a reference to one
+/// from a content model.
+#[derive(Debug, Default, PartialEq)]
+pub struct Group {
+    pub name: Option<String>,
+    pub r#ref: Option<String>,
+    pub min_occurrences: Option<Occurrence>,
+    pub max_occurrences: Option<Occurrence>,
+    pub sequence: Option<Sequence>,
+    pub choice: Option<Choice>,
+    pub all: Option<All>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for Group {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut group = Group {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        group.name = node.attribute("name").map(|n| n.to_owned());
+        group.r#ref = node.attribute("ref").map(|r| r.to_owned());
+        group.min_occurrences = context.attribute(node, "minOccurs")?;
+        group.max_occurrences = context.attribute(node, "maxOccurs")?;
+
+        for child in context.children(node) {
+            let child = child?;
+
+            match child.tag_name().name() {
+                SEQUENCE => group.sequence = context.parse(child)?,
+                CHOICE => group.choice = context.parse(child)?,
+                ALL => group.all = context.parse(child)?,
+                ANNOTATION => {}
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
+            }
         }
 
-        for child in node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-        {
+        Ok(group)
+    }
+}
+
+/// A named attribute group at the top level of a schema, or a reference to
+/// one from a complex type or another attribute group.
+#[derive(Debug, Default, PartialEq)]
+pub struct AttributeGroup {
+    pub name: Option<String>,
+    pub r#ref: Option<String>,
+    pub attributes: Vec<Attribute>,
+    pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Box<AnyAttribute>>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for AttributeGroup {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut attribute_group = AttributeGroup {
+            name: node.attribute("name").map(|n| n.to_owned()),
+            r#ref: node.attribute("ref").map(|r| r.to_owned()),
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        for child in context.children(node) {
+            let child = child?;
+
             match child.tag_name().name() {
-                ELEMENT => choice.elements.push(Element::try_from(child)?),
-                ANY => choice.any = Some(Any::from(child)),
-                SEQUENCE => choice.sequences.push(Sequence::try_from(child)?),
-                unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
-                        parent: node.tag_name().name().to_owned(),
-                        tag: unknown.to_owned(),
-                    })
+                ATTRIBUTE => attribute_group
+                    .attributes
+                    .extend(context.parse::<Attribute>(child)?),
+                ATTRIBUTE_GROUP => attribute_group
+                    .attribute_groups
+                    .extend(context.parse::<AttributeGroup>(child)?),
+                ANY_ATTRIBUTE => {
+                    attribute_group.any_attribute = context.parse(child)?.map(Box::new)
                 }
+                ANNOTATION => {}
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
             }
         }
 
-        Ok(choice)
+        Ok(attribute_group)
     }
 }
 
 #[derive(Debug, Default, PartialEq)]
 pub struct Attribute {
     pub name: Option<String>,
+    pub r#ref: Option<String>,
     pub usage: Usage,
     pub r#type: Option<String>,
+    /// An anonymous type declared inside the attribute.
+    pub simple_type: Option<SimpleType>,
+    pub default_fixed: Option<DefaultFixed>,
+    pub form: Option<FormDefault>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Attribute {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let mut attribute = Attribute::default();
+impl FromNode for Attribute {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut attribute = Attribute {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
 
         if let Some(name) = node.attribute("name") {
             attribute.name = Some(name.to_owned());
         }
 
+        attribute.r#ref = node.attribute("ref").map(|r| r.to_owned());
+
         if let Some(type_) = node.attribute("type") {
             attribute.r#type = Some(type_.to_owned());
         }
 
-        if let Some(usage) = node.attribute("use") {
-            attribute.usage = Usage::from_str(usage)?;
+        if let Some(usage) = context.attribute(node, "use")? {
+            attribute.usage = usage;
+        }
+
+        attribute.default_fixed = DefaultFixed::from_node(node);
+        attribute.form = context.attribute(node, "form")?;
+
+        for child in context.children(node) {
+            let child = child?;
+
+            if child.tag_name().name() == SIMPLE_TYPE {
+                attribute.simple_type = context.parse(child)?;
+            }
         }
 
         Ok(attribute)
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub enum Usage {
+    #[default]
     Optional,
     Prohibited,
     Required,
 }
 
-impl Default for Usage {
-    fn default() -> Self {
-        Usage::Optional
-    }
-}
-
 impl FromStr for Usage {
     type Err = Error;
 
@@ -235,7 +542,10 @@ impl FromStr for Usage {
             "optional" => Ok(Usage::Optional),
             "prohibited" => Ok(Usage::Prohibited),
             "required" => Ok(Usage::Required),
-            _ => Err(Error::InvalidUse),
+            _ => Err(Error::InvalidUse {
+                value: s.to_owned(),
+                span: None,
+            }),
         }
     }
 }
@@ -243,36 +553,53 @@ impl FromStr for Usage {
 #[derive(Debug, PartialEq)]
 pub struct Extension {
     pub base: String,
-    pub attribute: Option<Attribute>,
+    pub attributes: Vec<Attribute>,
+    pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Box<AnyAttribute>>,
     pub sequence: Option<Sequence>,
     pub choice: Option<Choice>,
+    pub all: Option<All>,
+    pub group: Option<Group>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Extension {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
+impl FromNode for Extension {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
         let mut extension = Extension {
-            base: node.attribute("base").unwrap().to_owned(),
-            attribute: None,
+            base: context.required_attribute(node, "base")?.to_owned(),
+            attributes: Vec::new(),
+            attribute_groups: Vec::new(),
+            any_attribute: None,
             sequence: None,
             choice: None,
+            all: None,
+            group: None,
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
         };
 
-        for child in node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-        {
+        for child in context.children(node) {
+            let child = child?;
+
             match child.tag_name().name() {
-                ATTRIBUTE => extension.attribute = Some(Attribute::try_from(child)?),
-                SEQUENCE => extension.sequence = Some(Sequence::try_from(child)?),
-                CHOICE => extension.choice = Some(Choice::try_from(child)?),
-                unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
-                        parent: node.tag_name().name().to_owned(),
-                        tag: unknown.to_owned(),
-                    })
-                }
+                ATTRIBUTE => extension
+                    .attributes
+                    .extend(context.parse::<Attribute>(child)?),
+                ATTRIBUTE_GROUP => extension
+                    .attribute_groups
+                    .extend(context.parse::<AttributeGroup>(child)?),
+                ANY_ATTRIBUTE => extension.any_attribute = context.parse(child)?.map(Box::new),
+                ANNOTATION => {}
+                SEQUENCE => extension.sequence = context.parse(child)?,
+                CHOICE => extension.choice = context.parse(child)?,
+                ALL => extension.all = context.parse(child)?,
+                GROUP => extension.group = context.parse(child)?,
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
             }
         }
 
@@ -283,34 +610,35 @@ impl<'a, 'd> TryFrom<Node<'a, 'd>> for Extension {
 #[derive(Debug, PartialEq)]
 pub struct SimpleContent {
     pub content: Content,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Content {
-    Restriction(Restriction),
+    Restriction(Box<Restriction>),
     Extension(Box<Extension>),
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for SimpleContent {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let child = node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-            .nth(0)
-            .unwrap();
+impl FromNode for SimpleContent {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let child = context.first_child(node)?;
 
         match child.tag_name().name() {
             RESTRICTION => Ok(SimpleContent {
-                content: Content::Restriction(Restriction::try_from(child)?),
+                content: Content::Restriction(Box::new(Restriction::from_node(child, context)?)),
+                extra_attributes: context.extra_attributes(node),
+                span: context.span(node),
             }),
             EXTENSION => Ok(SimpleContent {
-                content: Content::Extension(Box::new(Extension::try_from(child)?)),
+                content: Content::Extension(Box::new(Extension::from_node(child, context)?)),
+                extra_attributes: context.extra_attributes(node),
+                span: context.span(node),
             }),
             unknown => Err(crate::errors::Error::UnhandledTag {
                 parent: node.tag_name().name().to_owned(),
                 tag: unknown.to_owned(),
+                span: context.span(child),
             }),
         }
     }
@@ -319,28 +647,29 @@ impl<'a, 'd> TryFrom<Node<'a, 'd>> for SimpleContent {
 #[derive(Debug, PartialEq)]
 pub struct ComplexContent {
     pub content: Content,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for ComplexContent {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let child = node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-            .nth(0)
-            .unwrap();
+impl FromNode for ComplexContent {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let child = context.first_child(node)?;
 
         match child.tag_name().name() {
             RESTRICTION => Ok(ComplexContent {
-                content: Content::Restriction(Restriction::try_from(child)?),
+                content: Content::Restriction(Box::new(Restriction::from_node(child, context)?)),
+                extra_attributes: context.extra_attributes(node),
+                span: context.span(node),
             }),
             EXTENSION => Ok(ComplexContent {
-                content: Content::Extension(Box::new(Extension::try_from(child)?)),
+                content: Content::Extension(Box::new(Extension::from_node(child, context)?)),
+                extra_attributes: context.extra_attributes(node),
+                span: context.span(node),
             }),
             unknown => Err(crate::errors::Error::UnhandledTag {
                 parent: node.tag_name().name().to_owned(),
                 tag: unknown.to_owned(),
+                span: context.span(child),
             }),
         }
     }
@@ -0,0 +1,1970 @@
+//! Checks the constraints the spec puts on schema components once their
+//! references are resolved, reporting each violation with the id of the
+//! rule it breaks.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::automaton::Glushkov;
+use crate::builtins::{self, Derivation};
+use crate::complex_type::{
+    Attribute, AttributeGroup, ComplexType, Content, Group, NestedParticle, Usage,
+};
+use crate::decimal::Decimal;
+use crate::diagnostics::Diagnostics;
+use crate::element::{DefaultFixed, Element};
+use crate::errors::Error;
+use crate::identity_constraint::IdentityConstraint;
+use crate::parser::SchemaDocument;
+use crate::particle::{content_model, Groups, Particle, Term};
+use crate::pattern::Pattern;
+use crate::restriction::{Restriction, Restrictions};
+use crate::schema_set::{Global, SchemaSet};
+use crate::shared::{Final, FinalDefault, QName};
+use crate::simple_type::SimpleType;
+use crate::span::Span;
+use crate::substitution_group::SubstitutionGroups;
+use crate::temporal::{DateTime, Duration};
+use crate::traits::XSD_NAMESPACE;
+use crate::validator::MAX_DEPTH;
+use crate::xpath::XPath;
+
+/// Checks every component of `schema_set`.
+pub fn check(schema_set: &SchemaSet) -> Diagnostics {
+    let mut checker = Checker {
+        schema_set,
+        substitution_groups: SubstitutionGroups::new(schema_set),
+        keys: HashMap::new(),
+        keyrefs: Vec::new(),
+        diagnostics: Diagnostics::default(),
+    };
+
+    checker.unique_names();
+
+    for simple_type in schema_set.simple_types() {
+        checker.simple_type(simple_type);
+    }
+
+    for complex_type in schema_set.complex_types() {
+        checker.complex_type(complex_type);
+    }
+
+    for element in schema_set.elements() {
+        checker.global_element(element);
+    }
+
+    for attribute in schema_set.attributes() {
+        checker.attribute(attribute.component, attribute.document);
+    }
+
+    for attribute_group in schema_set.attribute_groups() {
+        checker.attribute_group(attribute_group);
+    }
+
+    for group in schema_set.groups() {
+        checker.model_group(group);
+    }
+
+    checker.keyrefs();
+    checker.diagnostics
+}
+
+enum Type<'s, 'a> {
+    Builtin(String),
+    Simple(&'s Global<'a, SimpleType>),
+    Complex(&'s Global<'a, ComplexType>),
+}
+
+fn xsd(name: &str) -> QName {
+    QName::new(Some(XSD_NAMESPACE), name)
+}
+
+struct Checker<'s, 'a> {
+    schema_set: &'s SchemaSet<'a>,
+    substitution_groups: SubstitutionGroups<'s, 'a>,
+    /// The key and unique constraints found, with how many fields each has.
+    keys: HashMap<QName, usize>,
+    /// The keyrefs found, with the name they refer to, checked once all the
+    /// keys are known.
+    keyrefs: Vec<(&'a IdentityConstraint, Option<QName>)>,
+    diagnostics: Diagnostics,
+}
+
+impl<'s, 'a> Checker<'s, 'a> {
+    fn report(&mut self, rule: &'static str, span: &Span, message: String) {
+        self.diagnostics.push(Error::Constraint {
+            rule,
+            message,
+            span: span.clone(),
+        });
+    }
+
+    fn lookup(&self, name: &QName) -> Option<Type<'s, 'a>> {
+        if name.namespace.as_deref() == Some(XSD_NAMESPACE) && builtins::is_builtin(&name.name) {
+            return Some(Type::Builtin(name.name.clone()));
+        }
+
+        match self.schema_set.simple_type(name) {
+            Some(simple_type) => Some(Type::Simple(simple_type)),
+            None => self.schema_set.complex_type(name).map(Type::Complex),
+        }
+    }
+
+    /// The name of the type `ty` is derived from, `None` for `anyType` and
+    /// for bases that cannot be resolved.
+    fn base(&self, ty: &Type) -> Option<QName> {
+        match *ty {
+            Type::Builtin(ref name) => match builtins::derivation(name)? {
+                Derivation::Restriction(base) => Some(xsd(base)),
+                Derivation::List(_) => Some(xsd("anySimpleType")),
+            },
+            Type::Simple(simple_type) => {
+                // An inline base has no name, so the chain continues at the
+                // first named type it is derived from.
+                let mut current: &SimpleType = simple_type.component;
+
+                loop {
+                    let restriction = match current.restriction {
+                        Some(ref restriction) => restriction,
+                        // Lists and unions are derived from anySimpleType.
+                        None => return Some(xsd("anySimpleType")),
+                    };
+
+                    match (&restriction.base, &restriction.simple_type) {
+                        (Some(base), _) => return simple_type.resolve(base),
+                        (None, Some(inline)) => current = inline,
+                        (None, None) => return None,
+                    }
+                }
+            }
+            Type::Complex(complex_type) => match complex_type.component.base() {
+                Some(base) => complex_type.resolve(base),
+                None => Some(xsd("anyType")),
+            },
+        }
+    }
+
+    /// Whether the type named `derived` is `base` or derived from it.
+    fn derives_from(&self, derived: &QName, base: &QName) -> bool {
+        let mut seen = HashSet::new();
+        let mut current = derived.clone();
+
+        loop {
+            if current == *base {
+                return true;
+            }
+
+            if !seen.insert(current.clone()) {
+                return false;
+            }
+
+            current = match self.lookup(&current).and_then(|ty| self.base(&ty)) {
+                Some(next) => next,
+                None => return false,
+            };
+        }
+    }
+
+    /// Whether following the bases of the type named `name` leads back to
+    /// it.
+    fn is_circular(&self, name: &QName) -> bool {
+        let mut seen = HashSet::new();
+        let mut current = match self.lookup(name).and_then(|ty| self.base(&ty)) {
+            Some(base) => base,
+            None => return false,
+        };
+
+        while seen.insert(current.clone()) {
+            if current == *name {
+                return true;
+            }
+
+            current = match self.lookup(&current).and_then(|ty| self.base(&ty)) {
+                Some(next) => next,
+                None => return false,
+            };
+        }
+
+        false
+    }
+
+    /// Resolves a type reference, reporting it if it cannot be.
+    fn resolve_type(
+        &mut self,
+        document: &SchemaDocument,
+        value: &str,
+        span: &Span,
+    ) -> Option<Type<'s, 'a>> {
+        let name = match document.resolve(value) {
+            Some(name) => name,
+            None => {
+                self.report(
+                    "src-resolve.4",
+                    span,
+                    format!("The prefix of {} is not declared", value),
+                );
+                return None;
+            }
+        };
+
+        let ty = self.lookup(&name);
+
+        if ty.is_none() {
+            self.report(
+                "src-resolve",
+                span,
+                format!("Cannot resolve {} to a type definition", name),
+            );
+        }
+
+        ty
+    }
+
+    /// Global names must be unique within each symbol space, and simple
+    /// and complex types share one.
+    fn unique_names(&mut self) {
+        let mut elements = HashSet::new();
+        let mut types = HashSet::new();
+
+        let duplicate_elements: Vec<_> = self
+            .schema_set
+            .elements()
+            .iter()
+            .filter(|e| !elements.insert(&e.name))
+            .map(|e| (e.name.clone(), e.component.span.clone()))
+            .collect();
+
+        let mut type_spans: Vec<(&QName, &Span)> = self
+            .schema_set
+            .simple_types()
+            .iter()
+            .map(|t| (&t.name, &t.component.span))
+            .chain(
+                self.schema_set
+                    .complex_types()
+                    .iter()
+                    .map(|t| (&t.name, &t.component.span)),
+            )
+            .collect();
+        type_spans.sort_by_key(|(_, span)| (span.document.clone(), span.start));
+
+        let duplicate_types: Vec<_> = type_spans
+            .into_iter()
+            .filter(|(name, _)| !types.insert(*name))
+            .map(|(name, span)| (name.clone(), span.clone()))
+            .collect();
+
+        let others = [
+            (
+                "Attribute",
+                duplicates(
+                    self.schema_set
+                        .attributes()
+                        .iter()
+                        .map(|a| (&a.name, &a.component.span)),
+                ),
+            ),
+            (
+                "Attribute group",
+                duplicates(
+                    self.schema_set
+                        .attribute_groups()
+                        .iter()
+                        .map(|g| (&g.name, &g.component.span)),
+                ),
+            ),
+            (
+                "Model group",
+                duplicates(
+                    self.schema_set
+                        .groups()
+                        .iter()
+                        .map(|g| (&g.name, &g.component.span)),
+                ),
+            ),
+            (
+                "Notation",
+                duplicates(
+                    self.schema_set
+                        .notations()
+                        .iter()
+                        .map(|n| (&n.name, &n.component.span)),
+                ),
+            ),
+        ];
+
+        for (kind, duplicates) in others {
+            for (name, span) in duplicates {
+                self.report(
+                    "sch-props-correct.2",
+                    &span,
+                    format!("{} {} is declared more than once", kind, name),
+                );
+            }
+        }
+
+        for (name, span) in duplicate_elements {
+            self.report(
+                "sch-props-correct.2",
+                &span,
+                format!("Element {} is declared more than once", name),
+            );
+        }
+
+        for (name, span) in duplicate_types {
+            self.report(
+                "sch-props-correct.2",
+                &span,
+                format!("Type {} is defined more than once", name),
+            );
+        }
+    }
+
+    fn simple_type(&mut self, simple_type: &'s Global<'a, SimpleType>) {
+        let component = simple_type.component;
+        let restriction = match component.restriction {
+            Some(ref restriction) => restriction,
+            None => return,
+        };
+
+        if self.is_circular(&simple_type.name) {
+            self.report(
+                "st-props-correct.2",
+                &component.span,
+                format!("Type {} is derived from itself", simple_type.name),
+            );
+            return;
+        }
+
+        let base = match restriction.base {
+            Some(ref base) => self.resolve_type(simple_type.document, base, &restriction.span),
+            // An inline base has no name, so the facets are checked against
+            // the nearest named type it is derived from.
+            None => self
+                .base(&Type::Simple(simple_type))
+                .and_then(|base| self.lookup(&base)),
+        };
+        let base = match base {
+            Some(base) => base,
+            None => return,
+        };
+
+        if let Type::Complex(base) = base {
+            self.report(
+                "src-resolve",
+                &restriction.span,
+                format!(
+                    "The base of a simple type, {}, is a complex type",
+                    base.name
+                ),
+            );
+            return;
+        }
+
+        self.facets(&base, restriction);
+    }
+
+    /// The primitive type a simple type is derived from, `Some(None)` for
+    /// lists and `None` when it cannot be told.
+    fn primitive(&self, ty: &Type) -> Option<Option<&'static str>> {
+        let mut seen = HashSet::new();
+        let mut name = match *ty {
+            Type::Builtin(ref name) => xsd(name),
+            Type::Simple(simple_type) => simple_type.name.clone(),
+            Type::Complex(_) => return None,
+        };
+
+        loop {
+            let ty = self.lookup(&name)?;
+
+            if let Type::Builtin(ref builtin) = ty {
+                return match builtin.as_str() {
+                    "anyType" | "anySimpleType" => None,
+                    builtin => Some(builtins::primitive(builtin)),
+                };
+            }
+
+            if !seen.insert(name) {
+                return None;
+            }
+
+            name = self.base(&ty)?;
+        }
+    }
+
+    /// The facets in effect for a simple type, the nearest in its derivation
+    /// of each kind.
+    fn effective_facets(&self, ty: &Type<'s, 'a>) -> Vec<(&'static str, &'a str)> {
+        let mut facets: Vec<(&'static str, &'a str)> = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = match *ty {
+            Type::Simple(simple_type) => simple_type,
+            _ => return facets,
+        };
+
+        while seen.insert(&current.name) {
+            let restriction = match current.component.restriction {
+                Some(ref restriction) => restriction,
+                None => break,
+            };
+
+            for (name, value) in restriction.restrictions.iter().map(Restrictions::facet) {
+                if !facets.iter().any(|&(n, _)| n == name) {
+                    facets.push((name, value));
+                }
+            }
+
+            current = match self
+                .base(&Type::Simple(current))
+                .and_then(|base| self.schema_set.simple_type(&base))
+            {
+                Some(base) => base,
+                None => break,
+            };
+        }
+
+        facets
+    }
+
+    fn facets(&mut self, base: &Type<'s, 'a>, restriction: &'a Restriction) {
+        let span = &restriction.span;
+        let primitive = self.primitive(base);
+
+        let own: Vec<(&'static str, &str)> = restriction
+            .restrictions
+            .iter()
+            .map(Restrictions::facet)
+            .collect();
+
+        for restriction in &restriction.restrictions {
+            if let Restrictions::Pattern(ref pattern) = *restriction {
+                if let Err(err) = Pattern::new(pattern) {
+                    self.report(
+                        "s4s-att-invalid-value",
+                        span,
+                        format!(
+                            "The pattern {:?} is not a regular expression: {}",
+                            pattern, err
+                        ),
+                    );
+                }
+            }
+        }
+
+        if let Some(primitive) = primitive {
+            let applicable = builtins::applicable_facets(primitive);
+
+            for &(name, _) in own.iter().filter(|(name, _)| !applicable.contains(name)) {
+                self.report(
+                    "cos-applicable-facets",
+                    span,
+                    format!(
+                        "Facet {} does not apply to {}",
+                        name,
+                        primitive.unwrap_or("list types")
+                    ),
+                );
+            }
+        }
+
+        let inherited = self.effective_facets(base);
+        let get = |facets: &[(&'static str, &'a str)], name: &str| {
+            facets.iter().find(|&&(n, _)| n == name).map(|&(_, v)| v)
+        };
+        let effective = |name: &str| get(&own, name).or_else(|| get(&inherited, name));
+        let ordered = |rule, smaller: &'static str, larger: &'static str, numeric: bool| {
+            if get(&own, smaller).is_none() && get(&own, larger).is_none() {
+                return None;
+            }
+
+            let (low, high) = (effective(smaller)?, effective(larger)?);
+            let order = if numeric {
+                compare_numbers(low, high)?
+            } else {
+                compare_values(primitive.flatten(), low, high)?
+            };
+
+            match order {
+                Ordering::Greater => Some((
+                    rule,
+                    format!(
+                        "The value of {} ({}) is greater than that of {} ({})",
+                        smaller, low, larger, high
+                    ),
+                )),
+                _ => None,
+            }
+        };
+
+        let mut violations = vec![
+            ordered(
+                "minLength-less-than-equal-to-maxLength",
+                "minLength",
+                "maxLength",
+                true,
+            ),
+            ordered(
+                "length-minLength-maxLength.1.1",
+                "minLength",
+                "length",
+                true,
+            ),
+            ordered(
+                "length-minLength-maxLength.2.1",
+                "length",
+                "maxLength",
+                true,
+            ),
+            ordered(
+                "fractionDigits-totalDigits",
+                "fractionDigits",
+                "totalDigits",
+                true,
+            ),
+            ordered(
+                "minInclusive-less-than-equal-to-maxInclusive",
+                "minInclusive",
+                "maxInclusive",
+                false,
+            ),
+        ];
+
+        // A restriction may only narrow the facets of its base.
+        let narrows = |rule, name: &'static str, wider: Ordering, numeric: bool| {
+            let (value, base_value) = (get(&own, name)?, get(&inherited, name)?);
+            let order = if numeric {
+                compare_numbers(value, base_value)?
+            } else {
+                compare_values(primitive.flatten(), value, base_value)?
+            };
+
+            if order == wider || (name == "length" && order != Ordering::Equal) {
+                return Some((
+                    rule,
+                    format!(
+                        "The value of {} ({}) is not a restriction of the base's ({})",
+                        name, value, base_value
+                    ),
+                ));
+            }
+
+            None
+        };
+
+        violations.extend(vec![
+            narrows("length-valid-restriction", "length", Ordering::Equal, true),
+            narrows(
+                "maxLength-valid-restriction",
+                "maxLength",
+                Ordering::Greater,
+                true,
+            ),
+            narrows(
+                "minLength-valid-restriction",
+                "minLength",
+                Ordering::Less,
+                true,
+            ),
+            narrows(
+                "totalDigits-valid-restriction",
+                "totalDigits",
+                Ordering::Greater,
+                true,
+            ),
+            narrows(
+                "fractionDigits-valid-restriction",
+                "fractionDigits",
+                Ordering::Greater,
+                true,
+            ),
+            narrows(
+                "maxInclusive-valid-restriction.2",
+                "maxInclusive",
+                Ordering::Greater,
+                false,
+            ),
+            narrows(
+                "minInclusive-valid-restriction.2",
+                "minInclusive",
+                Ordering::Less,
+                false,
+            ),
+        ]);
+
+        for (rule, message) in violations.into_iter().flatten() {
+            self.report(rule, span, message);
+        }
+    }
+
+    fn complex_type(&mut self, complex_type: &'s Global<'a, ComplexType>) {
+        let component = complex_type.component;
+
+        if self.is_circular(&complex_type.name) {
+            self.report(
+                "ct-props-correct.3",
+                &component.span,
+                format!("Type {} is derived from itself", complex_type.name),
+            );
+            return;
+        }
+
+        if let Some(ref complex_content) = component.complex_content {
+            self.complex_content(complex_type, &complex_content.content);
+        }
+
+        if let Some(ref simple_content) = component.simple_content {
+            self.simple_content(complex_type, &simple_content.content);
+        }
+
+        self.complex_type_attributes(component, complex_type.document);
+        self.group_references(own_groups(component), complex_type.document);
+        self.single_id(component, complex_type.document, &complex_type.name);
+
+        if let Some(particle) = content_model(
+            self.schema_set,
+            complex_type.component,
+            complex_type.document,
+        ) {
+            self.local_elements(complex_type, &particle);
+            self.elements_consistent(&particle);
+            self.unique_particle_attribution(&particle);
+        }
+    }
+
+    /// Unique Particle Attribution: no element may match two particles at
+    /// the same point of a content model. Reports each pair of competing
+    /// particles once.
+    fn unique_particle_attribution(&mut self, particle: &Particle<'a>) {
+        let automaton = Glushkov::new(particle);
+        let mut reported = HashSet::new();
+        let mut ambiguities = Vec::new();
+
+        for candidates in automaton.candidates() {
+            let positions: Vec<&Particle> = candidates
+                .iter()
+                .map(|&position| automaton.positions[position])
+                .collect();
+
+            for (i, &a) in positions.iter().enumerate() {
+                for &b in &positions[i + 1..] {
+                    if std::ptr::eq(a.span, b.span) {
+                        continue;
+                    }
+
+                    let (first, second) = if a.span.start <= b.span.start {
+                        (a, b)
+                    } else {
+                        (b, a)
+                    };
+                    let key = (first.span as *const Span, second.span as *const Span);
+
+                    if reported.contains(&key) {
+                        continue;
+                    }
+
+                    if let Some(term) = self.competing(first, second) {
+                        reported.insert(key);
+                        ambiguities.push((term, Box::new(first.span.clone()), second.span.clone()));
+                    }
+                }
+            }
+        }
+
+        for (term, first, span) in ambiguities {
+            self.diagnostics
+                .push(Error::AmbiguousContentModel { term, first, span });
+        }
+    }
+
+    /// The names an element particle matches: its own, or for a reference
+    /// to a global element, those of the elements that may substitute it.
+    fn matched_names(&self, particle: &Particle<'a>) -> Vec<QName> {
+        let name = match particle.element_name() {
+            Some(name) => name,
+            None => return Vec::new(),
+        };
+
+        let is_reference = match particle.term {
+            Term::Element(element) => element.r#ref.is_some(),
+            _ => false,
+        };
+
+        if !is_reference || self.schema_set.element(&name).is_none() {
+            return vec![name];
+        }
+
+        self.substitution_groups
+            .allowed(&name)
+            .into_iter()
+            .map(|element| element.name.clone())
+            .collect()
+    }
+
+    /// What both particles may match, if anything.
+    fn competing(&self, a: &Particle<'a>, b: &Particle<'a>) -> Option<String> {
+        match (&a.term, &b.term) {
+            (Term::Element(_), Term::Element(_)) => {
+                let names = self.matched_names(b);
+
+                self.matched_names(a)
+                    .into_iter()
+                    .find(|name| names.contains(name))
+                    .map(|name| format!("Element {}", name))
+            }
+            (Term::Element(_), Term::Any(_)) | (Term::Any(_), Term::Element(_)) => {
+                let (element, any) = match a.term {
+                    Term::Element(_) => (a, b),
+                    _ => (b, a),
+                };
+                let namespaces = any.namespaces();
+
+                self.matched_names(element)
+                    .into_iter()
+                    .find(|name| namespaces.allows(name.namespace.as_deref()))
+                    .map(|name| format!("Element {}", name))
+            }
+            (Term::Any(_), Term::Any(_)) if a.namespaces().intersects(&b.namespaces()) => {
+                Some("An element matched by a wildcard".to_owned())
+            }
+            _ => None,
+        }
+    }
+
+    fn complex_content(&mut self, complex_type: &'s Global<'a, ComplexType>, content: &'a Content) {
+        let (is_extension, base_name, span) = match *content {
+            Content::Extension(ref extension) => (true, &extension.base, &extension.span),
+            Content::Restriction(ref restriction) => match restriction.base {
+                Some(ref base) => (false, base, &restriction.span),
+                // The structure checks report a restriction without a base.
+                None => return,
+            },
+        };
+
+        let base = match self.resolve_type(complex_type.document, base_name, span) {
+            Some(base) => base,
+            None => return,
+        };
+
+        let base = match base {
+            Type::Complex(base) => base,
+            Type::Builtin(ref name) if name == "anyType" => return,
+            _ => {
+                self.report(
+                    "src-ct.1",
+                    span,
+                    format!(
+                        "The base of complex content, {}, is a simple type",
+                        base_name
+                    ),
+                );
+                return;
+            }
+        };
+
+        if !self.check_final(base, is_extension, span) || is_extension {
+            return;
+        }
+
+        let derived = match *content {
+            Content::Restriction(ref restriction) => Particle::group(
+                self.schema_set,
+                Groups {
+                    sequence: restriction.sequence.as_deref(),
+                    choice: restriction.choice.as_deref(),
+                    all: restriction.all.as_deref(),
+                    group: restriction.group.as_deref(),
+                },
+                complex_type.document,
+            ),
+            Content::Extension(_) => None,
+        };
+
+        match (derived, content_model(self.schema_set, base.component, base.document)) {
+            (Some(derived), Some(base)) => {
+                let (derived, base) = (derived.normalized(), base.normalized());
+
+                if let Err((rule, span, message)) = self.restricts(&derived, &base) {
+                    self.report(rule, span, message);
+                }
+            }
+            (Some(derived), None) => self.report(
+                "derivation-ok-restriction.5",
+                derived.span,
+                format!("Type {} has no content to restrict", base.name),
+            ),
+            (None, Some(base_particle)) if !base_particle.is_emptiable() => self.report(
+                "derivation-ok-restriction.5.2",
+                span,
+                format!(
+                    "Empty content is not a restriction of the content of {}, which is not emptiable",
+                    base.name
+                ),
+            ),
+            _ => {}
+        }
+    }
+
+    fn simple_content(&mut self, complex_type: &'s Global<'a, ComplexType>, content: &'a Content) {
+        let (is_extension, base_name, span) = match *content {
+            Content::Extension(ref extension) => (true, &extension.base, &extension.span),
+            Content::Restriction(ref restriction) => match restriction.base {
+                Some(ref base) => (false, base, &restriction.span),
+                // The structure checks report a restriction without a base.
+                None => return,
+            },
+        };
+
+        match self.resolve_type(complex_type.document, base_name, span) {
+            Some(Type::Complex(base)) => {
+                if base.component.simple_content.is_none() {
+                    self.report(
+                        "src-ct.2",
+                        span,
+                        format!(
+                            "The base of simple content, {}, does not have simple content",
+                            base.name
+                        ),
+                    );
+                    return;
+                }
+
+                self.check_final(base, is_extension, span);
+            }
+            Some(Type::Builtin(ref name)) if name == "anyType" => self.report(
+                "src-ct.2",
+                span,
+                "The base of simple content, anyType, does not have simple content".to_owned(),
+            ),
+            Some(_) if !is_extension => self.report(
+                "src-ct.2",
+                span,
+                format!(
+                    "Simple content restricts a simple type, {}, rather than a complex type",
+                    base_name
+                ),
+            ),
+            _ => {}
+        }
+    }
+
+    /// Reports a derivation that the base's `final` does not allow.
+    fn check_final(
+        &mut self,
+        base: &Global<'a, ComplexType>,
+        is_extension: bool,
+        span: &Span,
+    ) -> bool {
+        let blocked = match base.component.r#final {
+            Some(Final::All) => true,
+            Some(Final::Extension) => is_extension,
+            Some(Final::Restriction) => !is_extension,
+            None => match base.document.schema.final_default {
+                Some(FinalDefault::All) => true,
+                Some(FinalDefault::Extension) => is_extension,
+                Some(FinalDefault::Restriction) => !is_extension,
+                _ => false,
+            },
+        };
+
+        if blocked {
+            let (rule, kind) = if is_extension {
+                ("cos-ct-extends.1.1", "extension")
+            } else {
+                ("derivation-ok-restriction.1", "restriction")
+            };
+
+            self.report(
+                rule,
+                span,
+                format!("Type {} does not allow derivation by {}", base.name, kind),
+            );
+        }
+
+        !blocked
+    }
+
+    /// Checks that the types and references of the local element
+    /// declarations of a complex type resolve.
+    fn local_elements(
+        &mut self,
+        complex_type: &'s Global<'a, ComplexType>,
+        particle: &Particle<'a>,
+    ) {
+        if !std::ptr::eq(particle.document, complex_type.document) {
+            return;
+        }
+
+        match particle.term {
+            Term::Element(element) => {
+                if let Some(ref r) = element.r#ref {
+                    match complex_type.resolve(r) {
+                        Some(name) if self.schema_set.element(&name).is_none() => self.report(
+                            "src-resolve",
+                            &element.span,
+                            format!("Cannot resolve {} to an element declaration", name),
+                        ),
+                        Some(_) => {}
+                        None => self.report(
+                            "src-resolve.4",
+                            &element.span,
+                            format!("The prefix of {} is not declared", r),
+                        ),
+                    }
+                } else {
+                    if let Some(ref ty) = element.r#type {
+                        self.resolve_type(complex_type.document, ty, &element.span);
+                    }
+
+                    if let Some(ref local) = element.complex_type {
+                        self.complex_type_attributes(local, complex_type.document);
+                    }
+
+                    self.identity_constraints(element, complex_type.document);
+                }
+            }
+            Term::Any(_) => {}
+            Term::Sequence(ref particles)
+            | Term::Choice(ref particles)
+            | Term::All(ref particles) => {
+                for particle in particles {
+                    self.local_elements(complex_type, particle);
+                }
+            }
+        }
+    }
+
+    /// Checks the attribute declarations and references of a complex type,
+    /// and that the attribute groups it refers to resolve.
+    fn complex_type_attributes(
+        &mut self,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+    ) {
+        let content = complex_type
+            .complex_content
+            .as_ref()
+            .map(|c| &c.content)
+            .or_else(|| complex_type.simple_content.as_ref().map(|c| &c.content));
+
+        let (attributes, attribute_groups) = match content {
+            Some(Content::Extension(extension)) => {
+                (&extension.attributes, &extension.attribute_groups)
+            }
+            Some(Content::Restriction(restriction)) => {
+                (&restriction.attributes, &restriction.attribute_groups)
+            }
+            None => (&complex_type.attributes, &complex_type.attribute_groups),
+        };
+
+        self.attribute_uses(attributes, attribute_groups, document);
+    }
+
+    fn attribute_uses(
+        &mut self,
+        attributes: &'a [Attribute],
+        attribute_groups: &'a [AttributeGroup],
+        document: &'a SchemaDocument,
+    ) {
+        for attribute in attributes {
+            match attribute.r#ref {
+                Some(ref r) => {
+                    let found = self.resolve_reference(document, r, &attribute.span);
+
+                    if let Some(name) = found.filter(|n| self.schema_set.attribute(n).is_none()) {
+                        self.report(
+                            "src-resolve",
+                            &attribute.span,
+                            format!("Cannot resolve {} to an attribute declaration", name),
+                        );
+                    }
+                }
+                None => self.attribute(attribute, document),
+            }
+        }
+
+        for attribute_group in attribute_groups {
+            let found = match attribute_group.r#ref {
+                Some(ref r) => self.resolve_reference(document, r, &attribute_group.span),
+                None => continue,
+            };
+
+            if let Some(name) = found.filter(|n| self.schema_set.attribute_group(n).is_none()) {
+                self.report(
+                    "src-resolve",
+                    &attribute_group.span,
+                    format!("Cannot resolve {} to an attribute group definition", name),
+                );
+            }
+        }
+    }
+
+    /// The type of an attribute declaration has to be a simple type.
+    fn attribute(&mut self, attribute: &'a Attribute, document: &'a SchemaDocument) {
+        let ty = match attribute.r#type {
+            Some(ref ty) => ty,
+            None => return,
+        };
+
+        match self.resolve_type(document, ty, &attribute.span) {
+            Some(Type::Complex(complex_type)) => self.report(
+                "a-props-correct.1",
+                &attribute.span,
+                format!(
+                    "The type of attribute {}, {}, is a complex type",
+                    attribute.name.as_deref().unwrap_or_default(),
+                    complex_type.name
+                ),
+            ),
+            Some(Type::Builtin(ref name)) if name == "anyType" => self.report(
+                "a-props-correct.1",
+                &attribute.span,
+                format!(
+                    "The type of attribute {}, anyType, is a complex type",
+                    attribute.name.as_deref().unwrap_or_default()
+                ),
+            ),
+            _ => {}
+        }
+    }
+
+    fn model_group(&mut self, group: &'s Global<'a, Group>) {
+        let component = group.component;
+
+        if self.is_circular_model_group(group) {
+            self.report(
+                "mg-props-correct.2",
+                &component.span,
+                format!("Model group {} refers to itself", group.name),
+            );
+        }
+
+        self.group_references(
+            Groups {
+                sequence: component.sequence.as_ref(),
+                choice: component.choice.as_ref(),
+                all: component.all.as_ref(),
+                group: None,
+            },
+            group.document,
+        );
+    }
+
+    /// Whether a model group definition refers to itself, directly or
+    /// through the groups it refers to. References from the types of its
+    /// elements do not count.
+    fn is_circular_model_group(&self, group: &Global<'a, Group>) -> bool {
+        let mut seen = HashSet::new();
+        let mut pending = vec![(group.component, group.document)];
+
+        while let Some((definition, document)) = pending.pop() {
+            let mut references = Vec::new();
+
+            if let Some(ref sequence) = definition.sequence {
+                collect_group_references(&sequence.particles, &mut references);
+            }
+
+            if let Some(ref choice) = definition.choice {
+                collect_group_references(&choice.particles, &mut references);
+            }
+
+            for reference in references {
+                let name = match reference.r#ref.as_ref().and_then(|r| document.resolve(r)) {
+                    Some(name) => name,
+                    None => continue,
+                };
+
+                if name == group.name {
+                    return true;
+                }
+
+                if let Some(next) = self.schema_set.group(&name) {
+                    if seen.insert(name) {
+                        pending.push((next.component, next.document));
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Checks that the model group references in `groups` resolve, and
+    /// those in the anonymous types of the local elements there.
+    fn group_references(&mut self, groups: Groups<'a>, document: &'a SchemaDocument) {
+        if let Some(sequence) = groups.sequence {
+            self.nested_group_references(&sequence.particles, document);
+        }
+
+        if let Some(choice) = groups.choice {
+            self.nested_group_references(&choice.particles, document);
+        }
+
+        if let Some(all) = groups.all {
+            for element in &all.elements {
+                if let Some(ref complex_type) = element.complex_type {
+                    self.group_references(own_groups(complex_type), document);
+                }
+            }
+        }
+
+        if let Some(group) = groups.group {
+            self.group_reference(group, document);
+        }
+    }
+
+    fn nested_group_references(
+        &mut self,
+        particles: &'a [NestedParticle],
+        document: &'a SchemaDocument,
+    ) {
+        for particle in particles {
+            match *particle {
+                NestedParticle::Element(ref element) => {
+                    if let Some(ref complex_type) = element.complex_type {
+                        self.group_references(own_groups(complex_type), document);
+                    }
+                }
+                NestedParticle::Any(_) => {}
+                NestedParticle::Choice(ref choice) => {
+                    self.nested_group_references(&choice.particles, document)
+                }
+                NestedParticle::Sequence(ref sequence) => {
+                    self.nested_group_references(&sequence.particles, document)
+                }
+                NestedParticle::Group(ref group) => self.group_reference(group, document),
+            }
+        }
+    }
+
+    fn group_reference(&mut self, group: &'a Group, document: &'a SchemaDocument) {
+        let found = match group.r#ref {
+            Some(ref r) => self.resolve_reference(document, r, &group.span),
+            None => return,
+        };
+
+        if let Some(name) = found.filter(|n| self.schema_set.group(n).is_none()) {
+            self.report(
+                "src-resolve",
+                &group.span,
+                format!("Cannot resolve {} to a model group definition", name),
+            );
+        }
+    }
+
+    fn attribute_group(&mut self, attribute_group: &'s Global<'a, AttributeGroup>) {
+        let component = attribute_group.component;
+
+        if self.is_circular_group(attribute_group) {
+            self.report(
+                "src-attribute_group.3",
+                &component.span,
+                format!("Attribute group {} refers to itself", attribute_group.name),
+            );
+        }
+
+        self.attribute_uses(
+            &component.attributes,
+            &component.attribute_groups,
+            attribute_group.document,
+        );
+
+        let mut uses = HashMap::new();
+        self.id_uses(
+            &component.attributes,
+            &component.attribute_groups,
+            attribute_group.document,
+            &mut uses,
+            &mut HashSet::new(),
+        );
+
+        if uses.values().filter(|&&is_id| is_id).count() > 1 {
+            self.report(
+                "ag-props-correct.3",
+                &component.span,
+                format!(
+                    "Attribute group {} has more than one attribute of type ID",
+                    attribute_group.name
+                ),
+            );
+        }
+    }
+
+    /// A complex type may have only one attribute use of type ID, counting
+    /// those it inherits. `name` names the type, or the element it is the
+    /// anonymous type of.
+    fn single_id(
+        &mut self,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+        name: &QName,
+    ) {
+        let mut uses = HashMap::new();
+        self.complex_type_id_uses(complex_type, document, &mut uses, 0);
+
+        if uses.values().filter(|&&is_id| is_id).count() > 1 {
+            self.report(
+                "ct-props-correct.5",
+                &complex_type.span,
+                format!("Type of {} has more than one attribute of type ID", name),
+            );
+        }
+    }
+
+    /// The attribute uses of a complex type by name, with whether each is of
+    /// type ID. Those of the base come first and are replaced by those of
+    /// the same name.
+    fn complex_type_id_uses(
+        &self,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+        uses: &mut HashMap<QName, bool>,
+        depth: usize,
+    ) {
+        let content = complex_type
+            .complex_content
+            .as_ref()
+            .map(|c| &c.content)
+            .or_else(|| complex_type.simple_content.as_ref().map(|c| &c.content));
+
+        let (base, attributes, attribute_groups) = match content {
+            Some(Content::Extension(extension)) => (
+                Some(&extension.base),
+                &extension.attributes,
+                &extension.attribute_groups,
+            ),
+            Some(Content::Restriction(restriction)) => (
+                restriction.base.as_ref(),
+                &restriction.attributes,
+                &restriction.attribute_groups,
+            ),
+            None => (
+                None,
+                &complex_type.attributes,
+                &complex_type.attribute_groups,
+            ),
+        };
+
+        let base = base
+            .and_then(|base| document.resolve(base))
+            .and_then(|base| self.schema_set.complex_type(&base));
+
+        if let Some(base) = base.filter(|_| depth < MAX_DEPTH) {
+            self.complex_type_id_uses(base.component, base.document, uses, depth + 1);
+        }
+
+        self.id_uses(
+            attributes,
+            attribute_groups,
+            document,
+            uses,
+            &mut HashSet::new(),
+        );
+    }
+
+    fn id_uses(
+        &self,
+        attributes: &'a [Attribute],
+        attribute_groups: &'a [AttributeGroup],
+        document: &'a SchemaDocument,
+        uses: &mut HashMap<QName, bool>,
+        seen: &mut HashSet<QName>,
+    ) {
+        for attribute in attributes {
+            let name = match (&attribute.r#ref, &attribute.name) {
+                (Some(r), _) => document.resolve(r),
+                (None, Some(name)) => Some(QName::new(None, name)),
+                (None, None) => None,
+            };
+            let name = match name {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if attribute.usage == Usage::Prohibited {
+                uses.remove(&name);
+            } else {
+                uses.insert(name, self.is_id(attribute, document));
+            }
+        }
+
+        for reference in attribute_groups {
+            let group = reference
+                .r#ref
+                .as_ref()
+                .and_then(|r| document.resolve(r))
+                .filter(|name| seen.insert(name.clone()))
+                .and_then(|name| self.schema_set.attribute_group(&name));
+
+            if let Some(group) = group {
+                let component = group.component;
+                self.id_uses(
+                    &component.attributes,
+                    &component.attribute_groups,
+                    group.document,
+                    uses,
+                    seen,
+                );
+            }
+        }
+    }
+
+    /// Whether an attribute declaration, or the one a reference names, has
+    /// a type derived from ID.
+    fn is_id(&self, attribute: &'a Attribute, document: &'a SchemaDocument) -> bool {
+        if let Some(ref r) = attribute.r#ref {
+            return document
+                .resolve(r)
+                .and_then(|name| self.schema_set.attribute(&name))
+                .is_some_and(|global| {
+                    global.component.r#ref.is_none()
+                        && self.is_id(global.component, global.document)
+                });
+        }
+
+        let id = xsd("ID");
+
+        if let Some(ref ty) = attribute.r#type {
+            return document
+                .resolve(ty)
+                .is_some_and(|ty| self.derives_from(&ty, &id));
+        }
+
+        // An anonymous type is derived from ID through its nearest named base.
+        let mut current = attribute.simple_type.as_ref();
+
+        while let Some(simple_type) = current {
+            let restriction = match simple_type.restriction {
+                Some(ref restriction) => restriction,
+                None => return false,
+            };
+
+            match (&restriction.base, &restriction.simple_type) {
+                (Some(base), _) => {
+                    return document
+                        .resolve(base)
+                        .is_some_and(|base| self.derives_from(&base, &id))
+                }
+                (None, inline) => current = inline.as_deref(),
+            }
+        }
+
+        false
+    }
+
+    /// Whether following the references of an attribute group leads back to
+    /// it.
+    fn is_circular_group(&self, attribute_group: &Global<'a, AttributeGroup>) -> bool {
+        let mut seen = HashSet::new();
+        let mut pending = vec![(attribute_group.component, attribute_group.document)];
+
+        while let Some((group, document)) = pending.pop() {
+            for reference in &group.attribute_groups {
+                let name = match reference.r#ref.as_ref().and_then(|r| document.resolve(r)) {
+                    Some(name) => name,
+                    None => continue,
+                };
+
+                if name == attribute_group.name {
+                    return true;
+                }
+
+                if let Some(next) = self.schema_set.attribute_group(&name) {
+                    if seen.insert(name) {
+                        pending.push((next.component, next.document));
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Resolves the QName of a reference, reporting an undeclared prefix.
+    fn resolve_reference(
+        &mut self,
+        document: &SchemaDocument,
+        value: &str,
+        span: &Span,
+    ) -> Option<QName> {
+        let name = document.resolve(value);
+
+        if name.is_none() {
+            self.report(
+                "src-resolve.4",
+                span,
+                format!("The prefix of {} is not declared", value),
+            );
+        }
+
+        name
+    }
+
+    /// Element Declarations Consistent: elements of the same name in a
+    /// content model must have the same type.
+    fn elements_consistent(&mut self, particle: &Particle<'a>) {
+        let mut types: HashMap<QName, QName> = HashMap::new();
+        let mut elements = Vec::new();
+        collect_elements(particle, &mut elements);
+
+        for element in elements {
+            let (name, ty) = match (
+                element.element_name(),
+                element.element_type(self.schema_set),
+            ) {
+                (Some(name), Some(ty)) => (name, ty),
+                _ => continue,
+            };
+
+            match types.get(&name) {
+                Some(other) if *other != ty => self.report(
+                    "cos-element-consistent",
+                    element.span,
+                    format!(
+                        "Element {} is declared with both type {} and type {}",
+                        name, other, ty
+                    ),
+                ),
+                Some(_) => {}
+                None => {
+                    types.insert(name, ty);
+                }
+            }
+        }
+    }
+
+    fn global_element(&mut self, element: &'s Global<'a, Element>) {
+        let component = element.component;
+        self.identity_constraints(component, element.document);
+
+        if let Some(ref complex_type) = component.complex_type {
+            self.complex_type_attributes(complex_type, element.document);
+            self.group_references(own_groups(complex_type), element.document);
+            self.single_id(complex_type, element.document, &element.name);
+        }
+
+        let ty = match component.r#type {
+            Some(ref ty) => self
+                .resolve_type(element.document, ty, &component.span)
+                .and_then(|_| element.resolve(ty)),
+            None => None,
+        };
+
+        for head in &component.substitution_group {
+            let head_element = match element.resolve(head) {
+                Some(name) => self.schema_set.element(&name),
+                None => None,
+            };
+
+            let head_element = match head_element {
+                Some(head_element) => head_element,
+                None => {
+                    self.report(
+                        "src-resolve",
+                        &component.span,
+                        format!("Cannot resolve {} to an element declaration", head),
+                    );
+                    continue;
+                }
+            };
+
+            let head_type = head_element
+                .component
+                .r#type
+                .as_ref()
+                .and_then(|ty| head_element.resolve(ty));
+
+            if let (Some(ty), Some(head_type)) = (&ty, head_type) {
+                if !self.derives_from(ty, &head_type) {
+                    self.report(
+                        "e-props-correct.4",
+                        &component.span,
+                        format!(
+                            "The type of {}, {}, is not derived from the type of its substitution group head {}, {}",
+                            element.name, ty, head_element.name, head_type
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// The selector and fields of identity constraints have to be in the
+    /// XPath subset the spec allows.
+    fn identity_constraints(&mut self, element: &'a Element, document: &'a SchemaDocument) {
+        let namespaces = &document.schema.namespaces;
+
+        for constraint in &element.identity_constraints {
+            if let Err(message) = XPath::selector(&constraint.selector, namespaces) {
+                self.report(
+                    "c-selector-xpath",
+                    &constraint.span,
+                    format!(
+                        "The selector {:?} of {} is not valid: {}",
+                        constraint.selector, constraint.name, message
+                    ),
+                );
+            }
+
+            for field in &constraint.fields {
+                if let Err(message) = XPath::field(field, namespaces) {
+                    self.report(
+                        "c-fields-xpaths",
+                        &constraint.span,
+                        format!(
+                            "The field {:?} of {} is not valid: {}",
+                            field, constraint.name, message
+                        ),
+                    );
+                }
+            }
+
+            match constraint.refer {
+                Some(ref refer) => self.keyrefs.push((constraint, document.resolve(refer))),
+                None => {
+                    let name = QName::new(document.target_namespace.as_deref(), &constraint.name);
+                    self.keys.insert(name, constraint.fields.len());
+                }
+            }
+        }
+    }
+
+    /// A keyref has to refer to a key or unique constraint with as many
+    /// fields.
+    fn keyrefs(&mut self) {
+        for (constraint, refer) in std::mem::take(&mut self.keyrefs) {
+            let name = constraint.refer.as_deref().unwrap_or_default();
+
+            match refer.as_ref().and_then(|refer| self.keys.get(refer)) {
+                None => self.report(
+                    "src-resolve",
+                    &constraint.span,
+                    format!("Cannot resolve {} to a key or unique constraint", name),
+                ),
+                Some(&fields) if fields != constraint.fields.len() => self.report(
+                    "c-props-correct.2",
+                    &constraint.span,
+                    format!(
+                        "Keyref {} has {} fields, but {} has {}",
+                        constraint.name,
+                        constraint.fields.len(),
+                        name,
+                        fields
+                    ),
+                ),
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Whether `derived` is a valid restriction of `base`, as Particle Valid
+    /// (Restriction) has it. Both are normalized.
+    fn restricts<'p>(
+        &self,
+        derived: &'p Particle<'a>,
+        base: &Particle<'a>,
+    ) -> Result<(), (&'static str, &'p Span, String)> {
+        let fail = |rule, message: String| Err((rule, derived.span, message));
+
+        match (&derived.term, &base.term) {
+            (Term::Element(_), Term::Element(base_element)) => {
+                let (name, base_name) = (derived.element_name(), base.element_name());
+
+                if name != base_name {
+                    return fail(
+                        "rcase-NameAndTypeOK.1",
+                        format!(
+                            "Element {} does not restrict element {}",
+                            display(&name),
+                            display(&base_name)
+                        ),
+                    );
+                }
+
+                if !range_ok(derived, base) {
+                    return fail(
+                        "rcase-NameAndTypeOK.3",
+                        format!(
+                            "The occurrence range of {} is wider than the base's",
+                            display(&name)
+                        ),
+                    );
+                }
+
+                let element = match derived.term {
+                    Term::Element(element) => element,
+                    _ => unreachable!(),
+                };
+
+                if element.nillable && !base_element.nillable {
+                    return fail(
+                        "rcase-NameAndTypeOK.2",
+                        format!(
+                            "Element {} is nillable where the base's is not",
+                            display(&name)
+                        ),
+                    );
+                }
+
+                if let Some(DefaultFixed::Fixed(ref base_fixed)) = base_element.default_fixed {
+                    match element.default_fixed {
+                        Some(DefaultFixed::Fixed(ref fixed)) if fixed == base_fixed => {}
+                        _ => {
+                            return fail(
+                                "rcase-NameAndTypeOK.4",
+                                format!(
+                                    "Element {} must have the base's fixed value {}",
+                                    display(&name),
+                                    base_fixed
+                                ),
+                            )
+                        }
+                    }
+                }
+
+                let types = (
+                    derived.element_type(self.schema_set),
+                    base.element_type(self.schema_set),
+                );
+
+                if let (Some(ty), Some(base_ty)) = types {
+                    if !self.derives_from(&ty, &base_ty) {
+                        return fail(
+                            "rcase-NameAndTypeOK.7",
+                            format!(
+                                "The type of {}, {}, is not derived from {}",
+                                display(&name),
+                                ty,
+                                base_ty
+                            ),
+                        );
+                    }
+                }
+
+                Ok(())
+            }
+            (Term::Element(_), Term::Any(_)) => {
+                let name = derived.element_name();
+
+                if !base
+                    .namespaces()
+                    .allows(name.as_ref().and_then(|n| n.namespace.as_deref()))
+                {
+                    return fail(
+                        "rcase-NSCompat.1",
+                        format!(
+                            "Element {} is not allowed by the base's wildcard",
+                            display(&name)
+                        ),
+                    );
+                }
+
+                if !range_ok(derived, base) {
+                    return fail(
+                        "rcase-NSCompat.2",
+                        format!(
+                            "The occurrence range of {} is wider than the base's",
+                            display(&name)
+                        ),
+                    );
+                }
+
+                Ok(())
+            }
+            (Term::Element(_), Term::Sequence(_))
+            | (Term::Element(_), Term::Choice(_))
+            | (Term::Element(_), Term::All(_)) => {
+                // RecurseAsIfGroup: the element as a group of the base's kind.
+                let group = Particle {
+                    min: 1,
+                    max: Some(1),
+                    term: match base.term {
+                        Term::Sequence(_) => Term::Sequence(vec![derived.clone()]),
+                        Term::All(_) => Term::All(vec![derived.clone()]),
+                        _ => Term::Choice(vec![derived.clone()]),
+                    },
+                    document: derived.document,
+                    span: derived.span,
+                };
+
+                self.restricts(&group, base)
+                    .map_err(|(rule, _, message)| (rule, derived.span, message))
+            }
+            (Term::Any(_), Term::Any(_)) => {
+                if !range_ok(derived, base) {
+                    return fail(
+                        "rcase-NSSubset.1",
+                        "The occurrence range of the wildcard is wider than the base's".to_owned(),
+                    );
+                }
+
+                if !derived.namespaces().is_subset(&base.namespaces()) {
+                    return fail(
+                        "rcase-NSSubset.2",
+                        "The wildcard allows namespaces that the base's does not".to_owned(),
+                    );
+                }
+
+                Ok(())
+            }
+            (Term::Sequence(particles), Term::Any(_)) | (Term::Choice(particles), Term::Any(_)) => {
+                // NSRecurseCheckCardinality.
+                let unbounded = Particle {
+                    min: 0,
+                    max: None,
+                    ..base.clone()
+                };
+
+                for particle in particles {
+                    if let Err((_, span, message)) = self.restricts(particle, &unbounded) {
+                        return Err(("rcase-NSRecurseCheckCardinality.1", span, message));
+                    }
+                }
+
+                let (min, max) = derived.total_range();
+
+                if !range_within(min, max, base.min, base.max) {
+                    return fail(
+                        "rcase-NSRecurseCheckCardinality.2",
+                        "The group may match more or fewer elements than the base's wildcard allows".to_owned(),
+                    );
+                }
+
+                Ok(())
+            }
+            (Term::Sequence(particles), Term::Sequence(base_particles)) => self.recurse(
+                derived,
+                particles,
+                base,
+                base_particles,
+                ("rcase-Recurse.1", "rcase-Recurse.2"),
+                true,
+            ),
+            (Term::All(particles), Term::All(base_particles)) => self.recurse(
+                derived,
+                particles,
+                base,
+                base_particles,
+                ("rcase-Recurse.1", "rcase-Recurse.2"),
+                true,
+            ),
+            (Term::Choice(particles), Term::Choice(base_particles)) => self.recurse(
+                derived,
+                particles,
+                base,
+                base_particles,
+                ("rcase-RecurseLax.1", "rcase-RecurseLax.2"),
+                false,
+            ),
+            (Term::Sequence(particles), Term::Choice(base_particles)) => {
+                // MapAndSum.
+                for particle in particles {
+                    if !base_particles
+                        .iter()
+                        .any(|b| self.restricts(particle, b).is_ok())
+                    {
+                        return Err((
+                            "rcase-MapAndSum.1",
+                            particle.span,
+                            "The particle does not restrict any particle of the base's choice"
+                                .to_owned(),
+                        ));
+                    }
+                }
+
+                let count = particles.len();
+                let min = derived.min * count;
+                let max = derived.max.map(|max| max * count);
+
+                if !range_within(min, max, base.min, base.max) {
+                    return fail(
+                        "rcase-MapAndSum.2",
+                        "The occurrence range of the sequence is wider than the base's choice"
+                            .to_owned(),
+                    );
+                }
+
+                Ok(())
+            }
+            _ => fail(
+                "cos-particle-restrict.2",
+                format!("A {} cannot restrict a {}", kind(derived), kind(base)),
+            ),
+        }
+    }
+
+    /// Recurse and RecurseLax: an order-preserving mapping from the
+    /// particles of `derived` to those of `base`. Recurse also requires
+    /// the base particles left out to be emptiable.
+    fn recurse<'p>(
+        &self,
+        derived: &'p Particle<'a>,
+        particles: &'p [Particle<'a>],
+        base: &Particle<'a>,
+        base_particles: &[Particle<'a>],
+        (range_rule, rule): (&'static str, &'static str),
+        check_skipped: bool,
+    ) -> Result<(), (&'static str, &'p Span, String)> {
+        if !range_ok(derived, base) {
+            return Err((
+                range_rule,
+                derived.span,
+                format!(
+                    "The occurrence range of the {} is wider than the base's",
+                    kind(derived)
+                ),
+            ));
+        }
+
+        let mut remaining = base_particles.iter();
+
+        for particle in particles {
+            loop {
+                let base_particle = match remaining.next() {
+                    Some(base_particle) => base_particle,
+                    None => {
+                        return Err((
+                            rule,
+                            particle.span,
+                            format!("The {} has no counterpart in the base", kind(particle)),
+                        ))
+                    }
+                };
+
+                match self.restricts(particle, base_particle) {
+                    Ok(()) => break,
+                    Err((_, _, message)) if check_skipped && !base_particle.is_emptiable() => {
+                        return Err((rule, particle.span, message))
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if check_skipped {
+            if let Some(skipped) = remaining.find(|p| !p.is_emptiable()) {
+                return Err((
+                    "rcase-Recurse.2",
+                    derived.span,
+                    format!(
+                        "The {} of the base at {} is left out but is not emptiable",
+                        kind(skipped),
+                        skipped.span
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The model groups a complex type has itself, in its complex content when
+/// it has that.
+fn own_groups(complex_type: &ComplexType) -> Groups<'_> {
+    match complex_type.complex_content.as_ref().map(|c| &c.content) {
+        Some(Content::Restriction(restriction)) => Groups {
+            sequence: restriction.sequence.as_deref(),
+            choice: restriction.choice.as_deref(),
+            all: restriction.all.as_deref(),
+            group: restriction.group.as_deref(),
+        },
+        Some(Content::Extension(extension)) => Groups {
+            sequence: extension.sequence.as_ref(),
+            choice: extension.choice.as_ref(),
+            all: extension.all.as_ref(),
+            group: extension.group.as_ref(),
+        },
+        None => Groups {
+            sequence: complex_type.sequence.as_ref(),
+            choice: complex_type.choice.as_ref(),
+            all: complex_type.all.as_ref(),
+            group: complex_type.group.as_ref(),
+        },
+    }
+}
+
+/// The model group references among `particles` and the groups nested in
+/// them.
+fn collect_group_references<'g>(particles: &'g [NestedParticle], references: &mut Vec<&'g Group>) {
+    for particle in particles {
+        match *particle {
+            NestedParticle::Choice(ref choice) => {
+                collect_group_references(&choice.particles, references)
+            }
+            NestedParticle::Sequence(ref sequence) => {
+                collect_group_references(&sequence.particles, references)
+            }
+            NestedParticle::Group(ref group) => references.push(group),
+            NestedParticle::Element(_) | NestedParticle::Any(_) => {}
+        }
+    }
+}
+
+/// The names that occur more than once, with the span of each repeat.
+fn duplicates<'g>(names: impl Iterator<Item = (&'g QName, &'g Span)>) -> Vec<(QName, Span)> {
+    let mut seen = HashSet::new();
+
+    names
+        .filter(|(name, _)| !seen.insert(*name))
+        .map(|(name, span)| (name.clone(), span.clone()))
+        .collect()
+}
+
+fn compare_numbers(a: &str, b: &str) -> Option<Ordering> {
+    let (a, b) = (a.trim().parse::<u64>().ok()?, b.trim().parse::<u64>().ok()?);
+    Some(a.cmp(&b))
+}
+
+/// Compares two values of a primitive type, where that is supported.
+fn compare_values(primitive: Option<&str>, a: &str, b: &str) -> Option<Ordering> {
+    match primitive? {
+        "decimal" => Decimal::parse(a.trim())?.partial_cmp(&Decimal::parse(b.trim())?),
+        "float" | "double" => {
+            let (a, b) = (a.trim().parse::<f64>().ok()?, b.trim().parse::<f64>().ok()?);
+            a.partial_cmp(&b)
+        }
+        "duration" => Duration::parse(a.trim())?.partial_cmp(&Duration::parse(b.trim())?),
+        primitive => DateTime::parse(primitive, a.trim())?
+            .partial_cmp(&DateTime::parse(primitive, b.trim())?),
+    }
+}
+
+fn range_within(min: usize, max: Option<usize>, base_min: usize, base_max: Option<usize>) -> bool {
+    min >= base_min
+        && match (max, base_max) {
+            (_, None) => true,
+            (Some(max), Some(base_max)) => max <= base_max,
+            (None, Some(_)) => false,
+        }
+}
+
+fn range_ok(derived: &Particle, base: &Particle) -> bool {
+    range_within(derived.min, derived.max, base.min, base.max)
+}
+
+fn collect_elements<'p, 'a>(particle: &'p Particle<'a>, elements: &mut Vec<&'p Particle<'a>>) {
+    match particle.term {
+        Term::Element(_) => elements.push(particle),
+        Term::Any(_) => {}
+        Term::Sequence(ref particles) | Term::Choice(ref particles) | Term::All(ref particles) => {
+            for particle in particles {
+                collect_elements(particle, elements);
+            }
+        }
+    }
+}
+
+fn kind(particle: &Particle) -> &'static str {
+    match particle.term {
+        Term::Element(_) => "element",
+        Term::Any(_) => "wildcard",
+        Term::Sequence(_) => "sequence",
+        Term::Choice(_) => "choice",
+        Term::All(_) => "all group",
+    }
+}
+
+fn display(name: &Option<QName>) -> String {
+    name.as_ref()
+        .map_or_else(String::new, |name| name.to_string())
+}
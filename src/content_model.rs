@@ -0,0 +1,626 @@
+//! Content models compiled to automata with counters. Each element and
+//! wildcard particle is a state, and the transitions out of a state are
+//! worked out once, keyed by the names of the elements that take them.
+//! Occurrence bounds are kept as counters on the particles a state is in
+//! instead of being unrolled, so the automaton does not grow with
+//! `maxOccurs`. A matcher drops the configurations that another one in the
+//! same state is at least as good as at every counter, so it does not grow
+//! with the number of children.
+
+use std::collections::HashMap;
+
+use crate::complex_type::ComplexType;
+use crate::parser::SchemaDocument;
+use crate::particle::{content_model, Namespaces, Particle, Term};
+use crate::schema_set::SchemaSet;
+use crate::shared::QName;
+use crate::substitution_group::SubstitutionGroups;
+
+/// A particle of the flattened tree. The root is node 0, and children
+/// come after their parents in document order.
+#[derive(Debug)]
+struct Node<'a> {
+    min: usize,
+    /// `None` for `unbounded`.
+    max: Option<usize>,
+    kind: Kind<'a>,
+    parent: Option<usize>,
+    depth: usize,
+    /// Whether the particle may match nothing at all.
+    emptiable: bool,
+    /// Whether one occurrence may match nothing, in which case its
+    /// `minOccurs` can always be met.
+    term_emptiable: bool,
+    /// Whether the particles after this one in a sequence may all match
+    /// nothing.
+    rest_emptiable: bool,
+}
+
+#[derive(Debug)]
+enum Kind<'a> {
+    /// An element particle and the names it matches, those of the members
+    /// of its substitution group included.
+    Element(Particle<'a>, Vec<QName>),
+    Any(Particle<'a>, Namespaces),
+    Sequence(Vec<usize>),
+    Choice(Vec<usize>),
+    All(Vec<usize>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    /// The first child, from the start of the model.
+    Enter,
+    /// Another occurrence of the particle at the transition's depth.
+    Repeat,
+    /// A later child of the sequence, or another child of the `all` group,
+    /// at the transition's depth.
+    Next(usize),
+}
+
+/// A move from a state to an element or wildcard particle. The particles
+/// the state is in below `depth` are left, then `step` is taken there and
+/// the particles down to `target` are entered.
+#[derive(Clone, Copy, Debug)]
+struct Transition {
+    depth: usize,
+    step: Step,
+    target: usize,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    transitions: Vec<Transition>,
+    /// The transitions to element particles, by the names they match.
+    names: HashMap<QName, Vec<usize>>,
+    /// The transitions to wildcards.
+    wildcards: Vec<usize>,
+}
+
+/// The content model of a complex type compiled to an automaton, which
+/// tells whether a sequence of child elements is valid, which elements may
+/// come next, and whether the content is complete.
+#[derive(Debug)]
+pub struct ContentModel<'a> {
+    particle: Particle<'a>,
+    nodes: Vec<Node<'a>>,
+    start: State,
+    /// The transitions out of each element and wildcard particle, by node.
+    states: Vec<State>,
+}
+
+impl<'a> ContentModel<'a> {
+    /// Compiles a particle. References to global elements also match the
+    /// members of their substitution groups.
+    pub fn new(particle: Particle<'a>, substitution_groups: &SubstitutionGroups<'_, 'a>) -> Self {
+        let mut model = ContentModel {
+            particle,
+            nodes: Vec::new(),
+            start: State::default(),
+            states: Vec::new(),
+        };
+
+        let root = model.particle.clone();
+        model.add(&root, None, substitution_groups);
+
+        let first = model.first_leaves();
+        model.start = model.state(
+            first[0]
+                .iter()
+                .map(|&target| Transition {
+                    depth: 0,
+                    step: Step::Enter,
+                    target,
+                })
+                .collect(),
+        );
+        model.states = (0..model.nodes.len())
+            .map(|id| match model.nodes[id].kind {
+                Kind::Element(..) | Kind::Any(..) => {
+                    let transitions = model.transitions(id, &first);
+                    model.state(transitions)
+                }
+                _ => State::default(),
+            })
+            .collect();
+
+        model
+    }
+
+    /// The content model of a complex type, `None` for empty and simple
+    /// content.
+    pub fn of(
+        schema_set: &SchemaSet<'a>,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+    ) -> Option<Self> {
+        let particle = content_model(schema_set, complex_type, document)?;
+
+        Some(ContentModel::new(
+            particle,
+            &SubstitutionGroups::new(schema_set),
+        ))
+    }
+
+    /// The particle the model was compiled from.
+    pub fn particle(&self) -> &Particle<'a> {
+        &self.particle
+    }
+
+    pub fn matcher(&self) -> Matcher<'_, 'a> {
+        Matcher {
+            model: self,
+            configurations: vec![Configuration {
+                leaf: None,
+                counts: Vec::new(),
+                done: Vec::new(),
+            }],
+        }
+    }
+
+    /// Whether child elements with these names are valid content.
+    pub fn is_valid<'n, I>(&self, names: I) -> bool
+    where
+        I: IntoIterator<Item = &'n QName>,
+    {
+        let mut matcher = self.matcher();
+
+        names.into_iter().all(|name| matcher.next(name).is_some()) && matcher.is_complete()
+    }
+
+    fn add(
+        &mut self,
+        particle: &Particle<'a>,
+        parent: Option<usize>,
+        substitution_groups: &SubstitutionGroups<'_, 'a>,
+    ) -> usize {
+        let id = self.nodes.len();
+
+        self.nodes.push(Node {
+            min: particle.min,
+            max: particle.max,
+            kind: Kind::Sequence(Vec::new()),
+            parent,
+            depth: parent.map_or(0, |parent| self.nodes[parent].depth + 1),
+            emptiable: particle.is_emptiable(),
+            term_emptiable: term_emptiable(particle),
+            rest_emptiable: true,
+        });
+
+        let mut children = |particles: &[Particle<'a>]| -> Vec<usize> {
+            let children: Vec<usize> = particles
+                .iter()
+                .map(|child| self.add(child, Some(id), substitution_groups))
+                .collect();
+
+            for (idx, &child) in children.iter().enumerate() {
+                self.nodes[child].rest_emptiable =
+                    particles[idx + 1..].iter().all(Particle::is_emptiable);
+            }
+
+            children
+        };
+
+        let kind = match particle.term {
+            Term::Element(_) => Kind::Element(
+                particle.clone(),
+                element_names(particle, substitution_groups),
+            ),
+            Term::Any(_) => Kind::Any(particle.clone(), particle.namespaces()),
+            Term::Sequence(ref particles) => Kind::Sequence(children(particles)),
+            Term::Choice(ref particles) => Kind::Choice(children(particles)),
+            Term::All(ref particles) => Kind::All(children(particles)),
+        };
+
+        self.nodes[id].kind = kind;
+        id
+    }
+
+    /// The element and wildcard particles each particle may start with.
+    fn first_leaves(&self) -> Vec<Vec<usize>> {
+        let mut first: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+
+        // Children come after their parents, so they are done first.
+        for id in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[id];
+
+            if node.max == Some(0) {
+                continue;
+            }
+
+            first[id] = match node.kind {
+                Kind::Element(..) | Kind::Any(..) => vec![id],
+                Kind::Sequence(ref children) => {
+                    let mut leaves = Vec::new();
+
+                    for &child in children {
+                        leaves.extend(first[child].iter().cloned());
+
+                        if !self.nodes[child].emptiable {
+                            break;
+                        }
+                    }
+
+                    leaves
+                }
+                Kind::Choice(ref children) | Kind::All(ref children) => children
+                    .iter()
+                    .flat_map(|&child| first[child].iter().cloned())
+                    .collect(),
+            };
+        }
+
+        first
+    }
+
+    /// The transitions out of an element or wildcard particle, from the
+    /// innermost particle it is in outwards.
+    fn transitions(&self, leaf: usize, first: &[Vec<usize>]) -> Vec<Transition> {
+        let mut transitions = Vec::new();
+        let mut child = None;
+        let mut current = Some(leaf);
+
+        while let Some(id) = current {
+            let node = &self.nodes[id];
+            let mut add = |step, targets: &[usize]| {
+                transitions.extend(targets.iter().map(|&target| Transition {
+                    depth: node.depth,
+                    step,
+                    target,
+                }))
+            };
+
+            if node.max.is_none_or(|max| max > 1) {
+                add(Step::Repeat, &first[id]);
+            }
+
+            match (&node.kind, child) {
+                (Kind::Sequence(children), Some(child)) => {
+                    let idx = children.iter().position(|&c| c == child).unwrap_or(0);
+
+                    for &next in &children[idx + 1..] {
+                        add(Step::Next(next), &first[next]);
+
+                        if !self.nodes[next].emptiable {
+                            break;
+                        }
+                    }
+                }
+                (Kind::All(children), Some(child)) => {
+                    for &next in children.iter().filter(|&&next| next != child) {
+                        add(Step::Next(next), &first[next]);
+                    }
+                }
+                _ => {}
+            }
+
+            child = Some(id);
+            current = node.parent;
+        }
+
+        transitions
+    }
+
+    fn state(&self, transitions: Vec<Transition>) -> State {
+        let mut state = State::default();
+
+        for (idx, transition) in transitions.iter().enumerate() {
+            match self.nodes[transition.target].kind {
+                Kind::Element(_, ref names) => {
+                    for name in names {
+                        state.names.entry(name.clone()).or_default().push(idx);
+                    }
+                }
+                _ => state.wildcards.push(idx),
+            }
+        }
+
+        state.transitions = transitions;
+        state
+    }
+
+    fn state_of(&self, configuration: &Configuration) -> &State {
+        match configuration.leaf {
+            Some(leaf) => &self.states[leaf],
+            None => &self.start,
+        }
+    }
+
+    fn leaf(&self, id: usize) -> &Particle<'a> {
+        match self.nodes[id].kind {
+            Kind::Element(ref particle, _) | Kind::Any(ref particle, _) => particle,
+            _ => &self.particle,
+        }
+    }
+
+    /// The particle at `depth` that `id` is in, or `id` itself.
+    fn ancestor(&self, mut id: usize, depth: usize) -> usize {
+        while self.nodes[id].depth > depth {
+            id = self.nodes[id].parent.unwrap_or(0);
+        }
+
+        id
+    }
+
+    /// Whether the particles a configuration is in, from its leaf up to
+    /// those at depth `above`, can all end where they are.
+    fn exits(&self, configuration: &Configuration, leaf: usize, above: usize) -> bool {
+        let mut id = leaf;
+
+        loop {
+            let node = &self.nodes[id];
+
+            if configuration.counts[node.depth] < node.min && !node.term_emptiable {
+                return false;
+            }
+
+            match node.parent {
+                Some(parent) if node.depth > above => {
+                    if !self.rest_emptiable(id, &configuration.done) {
+                        return false;
+                    }
+                    id = parent;
+                }
+                _ => return true,
+            }
+        }
+    }
+
+    /// Whether the particles of the group `id` is in that have not matched
+    /// anything yet may all match nothing.
+    fn rest_emptiable(&self, id: usize, done: &[usize]) -> bool {
+        let parent = match self.nodes[id].parent {
+            Some(parent) => parent,
+            None => return true,
+        };
+
+        match self.nodes[parent].kind {
+            Kind::Sequence(_) => self.nodes[id].rest_emptiable,
+            Kind::All(ref children) => children
+                .iter()
+                .all(|&c| c == id || done.contains(&c) || self.nodes[c].emptiable),
+            _ => true,
+        }
+    }
+
+    /// The configuration a transition leads to, if its counters allow it.
+    fn apply(
+        &self,
+        configuration: &Configuration,
+        transition: &Transition,
+    ) -> Option<Configuration> {
+        let depth = transition.depth;
+        let (mut counts, done) = match configuration.leaf {
+            None => (Vec::new(), Vec::new()),
+            Some(leaf) => {
+                if self.nodes[leaf].depth > depth && !self.exits(configuration, leaf, depth + 1) {
+                    return None;
+                }
+
+                let group = self.ancestor(leaf, depth);
+                let child =
+                    (self.nodes[leaf].depth > depth).then(|| self.ancestor(leaf, depth + 1));
+                let mut counts = configuration.counts[..=depth].to_vec();
+                let mut done: Vec<usize> = configuration
+                    .done
+                    .iter()
+                    .cloned()
+                    .filter(|&c| self.nodes[c].depth <= depth + 1)
+                    .collect();
+
+                match transition.step {
+                    Step::Enter => return None,
+                    Step::Repeat => {
+                        let node = &self.nodes[group];
+
+                        if node.max.is_some_and(|max| counts[depth] >= max) {
+                            return None;
+                        }
+
+                        if let Some(child) = child {
+                            if !self.rest_emptiable(child, &configuration.done) {
+                                return None;
+                            }
+                        }
+
+                        // Once the minimum is met, further occurrences of
+                        // an unbounded particle are all alike.
+                        counts[depth] = match node.max {
+                            None => (counts[depth] + 1).min(node.min.max(1)),
+                            Some(_) => counts[depth] + 1,
+                        };
+                        done.retain(|&c| self.nodes[c].parent != Some(group));
+                    }
+                    Step::Next(next) => {
+                        if let (Kind::All(_), Some(child)) = (&self.nodes[group].kind, child) {
+                            if done.contains(&next) {
+                                return None;
+                            }
+
+                            done.push(child);
+                            done.sort_unstable();
+                        }
+                    }
+                }
+
+                (counts, done)
+            }
+        };
+
+        counts.resize(self.nodes[transition.target].depth + 1, 1);
+
+        Some(Configuration {
+            leaf: Some(transition.target),
+            counts,
+            done,
+        })
+    }
+
+    /// Whether every sequence of children that `other` accepts is also
+    /// accepted by `configuration`: both are in the same state, and at
+    /// each depth its count is the same or better. A lower count leaves
+    /// more room below `maxOccurs` once `minOccurs` is met, or when it can
+    /// always be met, and a higher one is closer to `minOccurs` when there
+    /// is no `maxOccurs`.
+    fn subsumes(&self, configuration: &Configuration, other: &Configuration) -> bool {
+        let leaf = match (configuration.leaf, other.leaf) {
+            (Some(leaf), Some(other_leaf)) if leaf == other_leaf => leaf,
+            (leaf, other_leaf) => return leaf == other_leaf && configuration == other,
+        };
+
+        if configuration.done != other.done {
+            return false;
+        }
+
+        let mut id = leaf;
+
+        loop {
+            let node = &self.nodes[id];
+            let count = configuration.counts[node.depth];
+            let other_count = other.counts[node.depth];
+            let min = if node.term_emptiable { 0 } else { node.min };
+
+            let better = count == other_count
+                || match node.max {
+                    None => count > other_count,
+                    Some(_) => count < other_count && count >= min,
+                };
+
+            if !better {
+                return false;
+            }
+
+            match node.parent {
+                Some(parent) => id = parent,
+                None => return true,
+            }
+        }
+    }
+
+    fn is_complete(&self, configuration: &Configuration) -> bool {
+        match configuration.leaf {
+            None => self.nodes.first().is_none_or(|root| root.emptiable),
+            Some(leaf) => self.exits(configuration, leaf, 0),
+        }
+    }
+}
+
+/// Where the children so far may have left a content model: the element
+/// or wildcard particle that matched the last one, how many times each
+/// particle it is in has occurred, and the children of the `all` groups it
+/// is in that have already matched.
+#[derive(Clone, Debug, PartialEq)]
+struct Configuration {
+    leaf: Option<usize>,
+    /// By depth, from the root.
+    counts: Vec<usize>,
+    done: Vec<usize>,
+}
+
+/// Matches child elements against a content model one at a time.
+#[derive(Clone, Debug)]
+pub struct Matcher<'m, 'a> {
+    model: &'m ContentModel<'a>,
+    configurations: Vec<Configuration>,
+}
+
+impl<'m, 'a> Matcher<'m, 'a> {
+    /// Moves past a child element, returning the element or wildcard
+    /// particle that matched it. When nothing does, the matcher is left as
+    /// it was and `None` is returned.
+    pub fn next(&mut self, name: &QName) -> Option<&'m Particle<'a>> {
+        let model = self.model;
+        let mut next: Vec<Configuration> = Vec::new();
+
+        for configuration in &self.configurations {
+            let state = model.state_of(configuration);
+            let wildcards = state.wildcards.iter().filter(|&&idx| {
+                match model.nodes[state.transitions[idx].target].kind {
+                    Kind::Any(_, ref namespaces) => namespaces.allows(name.namespace.as_deref()),
+                    _ => false,
+                }
+            });
+
+            for &idx in state.names.get(name).into_iter().flatten().chain(wildcards) {
+                if let Some(configuration) = model.apply(configuration, &state.transitions[idx]) {
+                    if !next.iter().any(|c| model.subsumes(c, &configuration)) {
+                        next.retain(|c| !model.subsumes(&configuration, c));
+                        next.push(configuration);
+                    }
+                }
+            }
+        }
+
+        let leaf = next.first()?.leaf?;
+        self.configurations = next;
+
+        Some(model.leaf(leaf))
+    }
+
+    /// The element and wildcard particles the next child may match, in
+    /// document order.
+    pub fn expected(&self) -> Vec<&'m Particle<'a>> {
+        let model = self.model;
+        let mut leaves = Vec::new();
+
+        for configuration in &self.configurations {
+            for transition in &model.state_of(configuration).transitions {
+                if !leaves.contains(&transition.target)
+                    && model.apply(configuration, transition).is_some()
+                {
+                    leaves.push(transition.target);
+                }
+            }
+        }
+
+        leaves.sort_unstable();
+        leaves.into_iter().map(|leaf| model.leaf(leaf)).collect()
+    }
+
+    /// Whether the children so far are a complete match of the model.
+    pub fn is_complete(&self) -> bool {
+        self.configurations
+            .iter()
+            .any(|configuration| self.model.is_complete(configuration))
+    }
+
+    /// The model being matched.
+    pub fn model(&self) -> &'m ContentModel<'a> {
+        self.model
+    }
+}
+
+/// The names an element particle matches: its own, and for a reference to
+/// a global element those of the members of its substitution group.
+fn element_names(particle: &Particle, substitution_groups: &SubstitutionGroups) -> Vec<QName> {
+    let declared = match particle.element_name() {
+        Some(declared) => declared,
+        None => return Vec::new(),
+    };
+    let mut names = vec![declared.clone()];
+
+    if let Term::Element(element) = particle.term {
+        if element.r#ref.is_some() {
+            for member in substitution_groups.allowed(&declared) {
+                if !names.contains(&member.name) {
+                    names.push(member.name.clone());
+                }
+            }
+        }
+    }
+
+    names
+}
+
+/// Whether one occurrence of the particle may match nothing.
+fn term_emptiable(particle: &Particle) -> bool {
+    match particle.term {
+        Term::Element(_) | Term::Any(_) => false,
+        Term::Sequence(ref children) | Term::All(ref children) => {
+            children.iter().all(Particle::is_emptiable)
+        }
+        Term::Choice(ref children) => {
+            children.is_empty() || children.iter().any(Particle::is_emptiable)
+        }
+    }
+}
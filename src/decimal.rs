@@ -0,0 +1,141 @@
+//! Exact values of `decimal` and of the integer types, which are unbounded
+//! and would round if they went through floating point.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A decimal number of any size and precision. Integers are the decimals
+/// without fraction digits.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Decimal {
+    negative: bool,
+    /// The ASCII digits, without leading zeros or trailing zeros after the
+    /// point. Empty for zero.
+    digits: String,
+    /// How many of the digits come after the point.
+    scale: usize,
+}
+
+impl Decimal {
+    /// Parses the lexical form of a `decimal` or an integer, `None` when it
+    /// is not one.
+    pub fn parse(value: &str) -> Option<Self> {
+        let negative = value.starts_with('-');
+        let unsigned = value.strip_prefix(['+', '-']).unwrap_or(value);
+        let (whole, fraction) = match unsigned.find('.') {
+            Some(idx) => (&unsigned[..idx], &unsigned[idx + 1..]),
+            None => (unsigned, ""),
+        };
+
+        if (whole.is_empty() && fraction.is_empty())
+            || !whole.bytes().all(|b| b.is_ascii_digit())
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let fraction = fraction.trim_end_matches('0');
+        let digits = format!("{}{}", whole, fraction)
+            .trim_start_matches('0')
+            .to_owned();
+
+        Some(Decimal {
+            negative: negative && !digits.is_empty(),
+            digits,
+            scale: fraction.len(),
+        })
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.scale == 0
+    }
+
+    /// The digits the `totalDigits` facet counts: those needed to write the
+    /// value without leading or trailing zeros, and at least one.
+    pub fn total_digits(&self) -> usize {
+        self.digits.len().max(self.scale).max(1)
+    }
+
+    /// The digits the `fractionDigits` facet counts.
+    pub fn fraction_digits(&self) -> usize {
+        self.scale
+    }
+
+    /// The value as an `f64`, rounded to the nearest one.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// The digits before the point. Empty when there are none.
+    fn whole(&self) -> &str {
+        &self.digits[..self.digits.len().saturating_sub(self.scale)]
+    }
+
+    /// The digits after the point, with leading zeros for a scale longer
+    /// than the digits.
+    fn fraction(&self) -> String {
+        let fraction = &self.digits[self.digits.len().saturating_sub(self.scale)..];
+        format!("{:0>width$}", fraction, width = self.scale)
+    }
+
+    fn cmp_magnitude(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.whole(), other.whole());
+
+        a.len()
+            .cmp(&b.len())
+            .then_with(|| a.cmp(b))
+            .then_with(|| self.fraction().cmp(&other.fraction()))
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal {
+            negative: value < 0,
+            digits: if value == 0 {
+                String::new()
+            } else {
+                value.unsigned_abs().to_string()
+            },
+            scale: 0,
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.cmp_magnitude(other),
+            (true, true) => other.cmp_magnitude(self),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// The canonical form: no plus sign, no leading or trailing zeros, and
+    /// no point for integers.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+
+        match self.whole() {
+            "" => f.write_str("0")?,
+            whole => f.write_str(whole)?,
+        }
+
+        if self.scale > 0 {
+            write!(f, ".{}", self.fraction())?;
+        }
+
+        Ok(())
+    }
+}
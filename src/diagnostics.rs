@@ -0,0 +1,121 @@
+//! Problems found while parsing in lenient mode, which skips the offending
+//! construct and keeps going instead of failing.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::slice;
+
+use crate::errors::Error;
+use crate::span::Span;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    /// Something the parser does not understand was skipped.
+    Warning,
+    /// Something invalid was skipped, so the model is missing part of the
+    /// schema.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: Error,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(error: Error) -> Self {
+        let severity = match error {
+            Error::UnhandledTag { .. }
+            | Error::ForeignElement { .. }
+            | Error::ForeignAttribute { .. } => Severity::Warning,
+            _ => Severity::Error,
+        };
+
+        Diagnostic { severity, error }
+    }
+
+    pub fn span(&self) -> Option<&Span> {
+        self.error.span()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.error)
+    }
+}
+
+/// The diagnostics of a parse, in the order they were found.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+    // The same problem can be found by more than one pass over a document,
+    // so the hash of each span and message is kept to drop repeats.
+    seen: HashSet<u64>,
+}
+
+impl Diagnostics {
+    pub fn iter(&self) -> slice::Iter<'_, Diagnostic> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter().filter(|d| d.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter().filter(|d| d.severity == Severity::Warning)
+    }
+
+    pub(crate) fn push(&mut self, error: Error) {
+        let mut hasher = DefaultHasher::new();
+        error.span().hash(&mut hasher);
+        error.to_string().hash(&mut hasher);
+
+        if self.seen.insert(hasher.finish()) {
+            self.entries.push(Diagnostic::new(error));
+        }
+    }
+
+    pub(crate) fn append(&mut self, other: &mut Diagnostics) {
+        for diagnostic in mem::take(other).entries {
+            self.push(diagnostic.error);
+        }
+    }
+
+    /// Puts the diagnostics of a single document in document order.
+    pub(crate) fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|d| d.span().map_or(usize::MAX, |span| span.start));
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1,87 +1,123 @@
-use std::str::FromStr;
+use std::collections::HashMap;
 
-use crate::shared::{BlockDefault, Final, Occurrence};
-use crate::traits::TryFrom;
+use crate::complex_type::ComplexType;
+use crate::errors::Error;
+use crate::identity_constraint::IdentityConstraint;
+use crate::shared::{BlockDefault, Final, FormDefault, Occurrence, QName};
+use crate::simple_type::SimpleType;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
 
 use roxmltree::Node;
 
+const SIMPLE_TYPE: &str = "simpleType";
+const COMPLEX_TYPE: &str = "complexType";
+const UNIQUE: &str = "unique";
+const KEY: &str = "key";
+const KEYREF: &str = "keyref";
+
 #[derive(Debug, PartialEq)]
 pub enum DefaultFixed {
     Default(String),
     Fixed(String),
 }
 
+impl DefaultFixed {
+    /// The `default` or `fixed` attribute of an element or attribute
+    /// declaration. The schema-for-schemas check reports having both.
+    pub(crate) fn from_node(node: Node) -> Option<Self> {
+        match (node.attribute("default"), node.attribute("fixed")) {
+            (_, Some(fixed)) => Some(DefaultFixed::Fixed(fixed.to_owned())),
+            (Some(default), None) => Some(DefaultFixed::Default(default.to_owned())),
+            (None, None) => None,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        match *self {
+            DefaultFixed::Default(ref value) | DefaultFixed::Fixed(ref value) => value,
+        }
+    }
+}
+
 #[derive(Debug, Default, PartialEq)]
 pub struct Element {
     pub r#abstract: bool,
     pub block: Option<BlockDefault>,
     pub default_fixed: Option<DefaultFixed>,
     pub r#final: Option<Final>,
+    pub form: Option<FormDefault>,
     pub id: Option<String>,
     pub max_occurrences: Option<Occurrence>,
     pub min_occurrences: Option<Occurrence>,
     pub name: Option<String>,
     pub nillable: bool,
-    // TODO: ref
-    pub substitution_group: Option<String>,
+    pub r#ref: Option<String>,
+    pub substitution_group: Vec<String>,
     pub r#type: Option<String>,
+    /// An anonymous type declared inside the element.
+    pub simple_type: Option<Box<SimpleType>>,
+    pub complex_type: Option<Box<ComplexType>>,
+    pub identity_constraints: Vec<IdentityConstraint>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Element {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let mut element = Element::default();
+impl FromNode for Element {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut element = Element {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
 
         if let Some(abstract_) = node.attribute("abstract") {
             element.r#abstract = abstract_ == "true";
         }
 
-        if let Some(block) = node.attribute("block") {
-            let block = BlockDefault::from_str(block)?;
-            element.block = Some(block);
-        }
+        element.block = context.attribute(node, "block")?;
 
-        element.default_fixed = node
-            .attribute("default")
-            .and_then(|default| Some(DefaultFixed::Default(default.to_owned())));
+        element.default_fixed = DefaultFixed::from_node(node);
 
-        element.default_fixed = node
-            .attribute("fixed")
-            .and_then(|fixed| Some(DefaultFixed::Fixed(fixed.to_owned())));
+        element.r#final = context.attribute(node, "final")?;
 
-        if let Some(final_) = node.attribute("final") {
-            let final_ = Final::from_str(final_)?;
-            element.r#final = Some(final_);
-        }
-
-        element.id = node.attribute("id").and_then(|id| Some(id.to_owned()));
+        element.form = context.attribute(node, "form")?;
 
-        if let Some(max_occurrences) = node.attribute("maxOccurs") {
-            let max_occurrences = Occurrence::from_str(max_occurrences)?;
+        element.id = node.attribute("id").map(|id| id.to_owned());
 
-            element.max_occurrences = Some(max_occurrences);
-        }
+        element.max_occurrences = context.attribute(node, "maxOccurs")?;
+        element.min_occurrences = context.attribute(node, "minOccurs")?;
 
-        if let Some(min_occurrences) = node.attribute("minOccurs") {
-            let min_occurrences = Occurrence::from_str(min_occurrences)?;
+        element.name = node.attribute("name").map(|name| name.to_owned());
 
-            element.min_occurrences = Some(min_occurrences);
+        if let Some(nillable) = node.attribute("nillable") {
+            element.nillable = nillable == "true";
         }
 
-        element.name = node
-            .attribute("name")
-            .and_then(|name| Some(name.to_owned()));
+        element.r#ref = node.attribute("ref").map(|r| r.to_owned());
 
-        if let Some(nillable) = node.attribute("nillable") {
-            element.nillable = nillable == "true";
+        // XSD 1.1 allows a list of heads, XSD 1.0 a single one.
+        if let Some(substitution_group) = node.attribute("substitutionGroup") {
+            element.substitution_group = substitution_group
+                .split_whitespace()
+                .map(|sg| sg.to_owned())
+                .collect();
         }
 
-        element.substitution_group = node
-            .attribute("substitutionGroup")
-            .and_then(|sg| Some(sg.to_owned()));
+        element.r#type = node.attribute("type").map(|ty| ty.to_owned());
 
-        element.r#type = node.attribute("type").and_then(|ty| Some(ty.to_owned()));
+        for child in context.children(node) {
+            let child = child?;
+
+            match child.tag_name().name() {
+                SIMPLE_TYPE => element.simple_type = context.parse(child)?.map(Box::new),
+                COMPLEX_TYPE => element.complex_type = context.parse(child)?.map(Box::new),
+                UNIQUE | KEY | KEYREF => element
+                    .identity_constraints
+                    .extend(context.parse::<IdentityConstraint>(child)?),
+                _ => {}
+            }
+        }
 
         Ok(element)
     }
@@ -0,0 +1,117 @@
+//! Detection and decoding of the character encoding of schema documents, as
+//! described in appendix F of the XML specification.
+
+use std::borrow::Cow;
+
+use crate::errors::Error;
+
+/// Code points for bytes 0x80 to 0x9F in windows-1252. The rest of the
+/// encoding matches ISO-8859-1.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{8D}', '\u{017D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{9D}', '\u{017E}', '\u{0178}',
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+    Windows1252,
+}
+
+/// Decodes a document using its byte order mark or, failing that, the
+/// `encoding` of its XML declaration. Documents without either are UTF-8.
+pub fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, Error> {
+    let (encoding, body) = match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => (Encoding::Utf8, rest),
+        [0xFE, 0xFF, rest @ ..] => (Encoding::Utf16Be, rest),
+        [0xFF, 0xFE, rest @ ..] => (Encoding::Utf16Le, rest),
+        // "<?" without a byte order mark.
+        [0x00, 0x3C, 0x00, 0x3F, ..] => (Encoding::Utf16Be, bytes),
+        [0x3C, 0x00, 0x3F, 0x00, ..] => (Encoding::Utf16Le, bytes),
+        _ => (declared_encoding(bytes)?, bytes),
+    };
+
+    match encoding {
+        Encoding::Utf8 => Ok(Cow::Borrowed(std::str::from_utf8(body)?)),
+        Encoding::Utf16Le => decode_utf16(body, u16::from_le_bytes),
+        Encoding::Utf16Be => decode_utf16(body, u16::from_be_bytes),
+        Encoding::Latin1 => Ok(Cow::Owned(body.iter().map(|&b| b as char).collect())),
+        Encoding::Windows1252 => Ok(Cow::Owned(
+            body.iter()
+                .map(|&b| match b {
+                    0x80..=0x9F => WINDOWS_1252[(b - 0x80) as usize],
+                    _ => b as char,
+                })
+                .collect(),
+        )),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<Cow<'_, str>, Error> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(Error::InvalidEncoding {
+            encoding: "UTF-16".to_owned(),
+            span: None,
+        });
+    }
+
+    let units = bytes.chunks(2).map(|pair| from_bytes([pair[0], pair[1]]));
+
+    let decoded: Result<String, _> = std::char::decode_utf16(units).collect();
+
+    decoded.map(Cow::Owned).map_err(|_| Error::InvalidEncoding {
+        encoding: "UTF-16".to_owned(),
+        span: None,
+    })
+}
+
+/// Reads the `encoding` pseudo-attribute of an XML declaration written in an
+/// ASCII-compatible encoding.
+fn declared_encoding(bytes: &[u8]) -> Result<Encoding, Error> {
+    if !bytes.starts_with(b"<?xml") {
+        return Ok(Encoding::Utf8);
+    }
+
+    let end = match bytes.iter().position(|&b| b == b'>') {
+        Some(end) => end,
+        None => return Ok(Encoding::Utf8),
+    };
+
+    // The declaration is ASCII, whatever encoding the rest is in.
+    let declaration = String::from_utf8_lossy(&bytes[..end]);
+
+    let value = declaration.find("encoding").and_then(|idx| {
+        let rest = declaration[idx + "encoding".len()..]
+            .trim_start()
+            .strip_prefix('=')?
+            .trim_start();
+        let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let rest = &rest[1..];
+
+        rest.find(quote).map(|end| rest[..end].to_owned())
+    });
+
+    let name = match value {
+        Some(name) => name,
+        None => return Ok(Encoding::Utf8),
+    };
+
+    match name.to_ascii_lowercase().as_str() {
+        // UTF-16 cannot be declared in ASCII-compatible bytes, so the
+        // declaration is stale: the document was re-encoded without it
+        // being updated, most often to UTF-8.
+        "utf-8" | "utf8" | "utf-16" | "utf-16le" | "utf-16be" => Ok(Encoding::Utf8),
+        "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "latin-1" | "l1" | "us-ascii"
+        | "ascii" => Ok(Encoding::Latin1),
+        "windows-1252" | "cp1252" => Ok(Encoding::Windows1252),
+        _ => Err(Error::UnsupportedEncoding {
+            encoding: name,
+            span: None,
+        }),
+    }
+}
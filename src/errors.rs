@@ -1,83 +1,349 @@
 use std::error;
 use std::fmt;
 
+use crate::shared::QName;
+use crate::span::Span;
+
+/// An error, along with where in which document it occurred when that is
+/// known. Errors from parsing an attribute value on its own, or from reading
+/// the root document, have no span.
 #[derive(Debug)]
 pub enum Error {
-    UnhandledTag { parent: String, tag: String },
-    InvalidFinal,
-    InvalidFinalDefault,
-    InvalidBlockDefault,
-    InvalidFormDefault,
-    InvalidUse,
-    InvalidRootFolder,
-    ParseInt(std::num::ParseIntError),
-    IO(std::io::Error),
-    ParseXml(roxmltree::Error),
-    EmptyXml,
+    UnhandledTag {
+        parent: String,
+        tag: String,
+        span: Span,
+    },
+    InvalidFinal {
+        value: String,
+        span: Option<Span>,
+    },
+    InvalidFinalDefault {
+        value: String,
+        span: Option<Span>,
+    },
+    InvalidBlockDefault {
+        value: String,
+        span: Option<Span>,
+    },
+    InvalidFormDefault {
+        value: String,
+        span: Option<Span>,
+    },
+    InvalidUse {
+        value: String,
+        span: Option<Span>,
+    },
+    /// A `minOccurs` or `maxOccurs` that is neither a non-negative integer
+    /// nor `unbounded`.
+    InvalidOccurs {
+        value: String,
+        span: Option<Span>,
+    },
+    MissingAttribute {
+        element: String,
+        attribute: String,
+        span: Span,
+    },
+    /// An element from outside the schema namespace, where
+    /// `ParserOptions::allow_foreign_elements` is off.
+    ForeignElement {
+        name: QName,
+        span: Span,
+    },
+    /// A namespaced attribute on a schema element, where
+    /// `ParserOptions::allow_foreign_attributes` is off.
+    ForeignAttribute {
+        element: String,
+        name: QName,
+        span: Span,
+    },
+    /// An element with no child element where one is required.
+    MissingChild {
+        element: String,
+        span: Span,
+    },
+    /// A schema element where the schema-for-schemas does not allow it.
+    UnexpectedElement {
+        parent: String,
+        element: String,
+        span: Span,
+    },
+    /// An unqualified attribute the schema-for-schemas does not define for
+    /// the element.
+    UnexpectedAttribute {
+        element: String,
+        attribute: String,
+        span: Span,
+    },
+    InvalidAttributeValue {
+        attribute: String,
+        value: String,
+        span: Option<Span>,
+    },
+    /// Two attributes or children of an element that exclude each other.
+    ConflictingContent {
+        element: String,
+        first: &'static str,
+        second: &'static str,
+        span: Span,
+    },
+    /// A schema component that breaks a constraint of the spec, along with
+    /// the id of the rule, such as `cos-ct-extends.1.1`.
+    Constraint {
+        rule: &'static str,
+        message: String,
+        span: Span,
+    },
+    /// A content model where an element may match either of two particles,
+    /// breaking Unique Particle Attribution (`cos-nonambig`). `span` is the
+    /// later of the two.
+    AmbiguousContentModel {
+        term: String,
+        first: Box<Span>,
+        span: Span,
+    },
+    /// An instance document that is not valid against its schema, along with
+    /// the id of the validation rule, such as `cvc-complex-type.2.4.a`.
+    /// `span` is in the instance, and `component` is the declaration or type
+    /// the instance was checked against.
+    Invalid {
+        rule: &'static str,
+        message: String,
+        component: Option<Box<Span>>,
+        span: Span,
+    },
+    /// A document that could not be found, spanning the import or include
+    /// that referenced it.
+    SchemaNotFound {
+        location: String,
+        span: Option<Span>,
+    },
+    /// An `xs:redefine`, which is not supported. It is neither loaded nor
+    /// applied, so the components it redefines keep their original
+    /// definitions.
+    UnsupportedRedefine {
+        location: String,
+        span: Span,
+    },
+    InvalidCatalog {
+        span: Span,
+    },
+    IO {
+        error: std::io::Error,
+        span: Option<Span>,
+    },
+    ParseXml {
+        error: roxmltree::Error,
+        span: Span,
+    },
+    /// A document read with the streaming parser that is not well-formed.
+    ReadXml {
+        error: Box<xml::reader::Error>,
+        span: Span,
+    },
+    Utf8 {
+        error: std::str::Utf8Error,
+        span: Option<Span>,
+    },
+    UnsupportedEncoding {
+        encoding: String,
+        span: Option<Span>,
+    },
+    InvalidEncoding {
+        encoding: String,
+        span: Option<Span>,
+    },
+}
+
+impl Error {
+    pub fn span(&self) -> Option<&Span> {
+        match *self {
+            Error::UnhandledTag { ref span, .. }
+            | Error::MissingAttribute { ref span, .. }
+            | Error::MissingChild { ref span, .. }
+            | Error::UnexpectedElement { ref span, .. }
+            | Error::UnexpectedAttribute { ref span, .. }
+            | Error::ConflictingContent { ref span, .. }
+            | Error::Constraint { ref span, .. }
+            | Error::AmbiguousContentModel { ref span, .. }
+            | Error::Invalid { ref span, .. }
+            | Error::ForeignElement { ref span, .. }
+            | Error::ForeignAttribute { ref span, .. }
+            | Error::UnsupportedRedefine { ref span, .. }
+            | Error::InvalidCatalog { ref span }
+            | Error::ParseXml { ref span, .. }
+            | Error::ReadXml { ref span, .. } => Some(span),
+            Error::InvalidFinal { ref span, .. }
+            | Error::InvalidFinalDefault { ref span, .. }
+            | Error::InvalidBlockDefault { ref span, .. }
+            | Error::InvalidFormDefault { ref span, .. }
+            | Error::InvalidUse { ref span, .. }
+            | Error::InvalidOccurs { ref span, .. }
+            | Error::InvalidAttributeValue { ref span, .. }
+            | Error::SchemaNotFound { ref span, .. }
+            | Error::IO { ref span, .. }
+            | Error::Utf8 { ref span, .. }
+            | Error::UnsupportedEncoding { ref span, .. }
+            | Error::InvalidEncoding { ref span, .. } => span.as_ref(),
+        }
+    }
+
+    /// Sets the span of an error that does not have one yet.
+    pub(crate) fn with_span(mut self, new_span: Span) -> Self {
+        match self {
+            Error::InvalidFinal { ref mut span, .. }
+            | Error::InvalidFinalDefault { ref mut span, .. }
+            | Error::InvalidBlockDefault { ref mut span, .. }
+            | Error::InvalidFormDefault { ref mut span, .. }
+            | Error::InvalidUse { ref mut span, .. }
+            | Error::InvalidOccurs { ref mut span, .. }
+            | Error::InvalidAttributeValue { ref mut span, .. }
+            | Error::SchemaNotFound { ref mut span, .. }
+            | Error::IO { ref mut span, .. }
+            | Error::Utf8 { ref mut span, .. }
+            | Error::UnsupportedEncoding { ref mut span, .. }
+            | Error::InvalidEncoding { ref mut span, .. } => {
+                span.get_or_insert(new_span);
+            }
+            Error::UnhandledTag { .. }
+            | Error::MissingAttribute { .. }
+            | Error::MissingChild { .. }
+            | Error::UnexpectedElement { .. }
+            | Error::UnexpectedAttribute { .. }
+            | Error::ConflictingContent { .. }
+            | Error::Constraint { .. }
+            | Error::AmbiguousContentModel { .. }
+            | Error::Invalid { .. }
+            | Error::ForeignElement { .. }
+            | Error::ForeignAttribute { .. }
+            | Error::UnsupportedRedefine { .. }
+            | Error::InvalidCatalog { .. }
+            | Error::ParseXml { .. }
+            | Error::ReadXml { .. } => {}
+        }
+
+        self
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(span) = self.span() {
+            write!(f, "{}: ", span)?;
+        }
+
         match *self {
-            Error::ParseInt(ref e) => e.fmt(f),
-            Error::IO(ref e) => e.fmt(f),
-            Error::ParseXml(ref e) => e.fmt(f),
             Error::UnhandledTag {
                 ref parent,
                 ref tag,
+                ..
+            } => write!(f, "Unhandled tag: {} in {}", tag, parent),
+            Error::InvalidFinal { ref value, .. } => write!(f, "Invalid final: {}", value),
+            Error::InvalidFinalDefault { ref value, .. } => {
+                write!(f, "Invalid final default: {}", value)
+            }
+            Error::InvalidBlockDefault { ref value, .. } => {
+                write!(f, "Invalid block default: {}", value)
+            }
+            Error::InvalidFormDefault { ref value, .. } => {
+                write!(f, "Invalid form default: {}", value)
+            }
+            Error::InvalidUse { ref value, .. } => write!(f, "Invalid use value: {}", value),
+            Error::InvalidOccurs { ref value, .. } => {
+                write!(f, "Invalid occurrence count: {}", value)
+            }
+            Error::MissingAttribute {
+                ref element,
+                ref attribute,
+                ..
+            } => write!(f, "Missing attribute: {} on {}", attribute, element),
+            Error::MissingChild { ref element, .. } => {
+                write!(f, "Missing child element in {}", element)
+            }
+            Error::UnexpectedElement {
+                ref parent,
+                ref element,
+                ..
+            } => write!(f, "Unexpected element: {} in {}", element, parent),
+            Error::UnexpectedAttribute {
+                ref element,
+                ref attribute,
+                ..
+            } => write!(f, "Unexpected attribute: {} on {}", attribute, element),
+            Error::InvalidAttributeValue {
+                ref attribute,
+                ref value,
+                ..
+            } => write!(f, "Invalid {} value: {}", attribute, value),
+            Error::ConflictingContent {
+                ref element,
+                ref first,
+                ref second,
+                ..
+            } => write!(f, "{} cannot have both {} and {}", element, first, second),
+            Error::Constraint {
+                rule, ref message, ..
+            }
+            | Error::Invalid {
+                rule, ref message, ..
+            } => write!(f, "{}: {}", rule, message),
+            Error::AmbiguousContentModel {
+                ref term,
+                ref first,
+                ..
             } => write!(
                 f,
-                "{}: {} in {}",
-                error::Error::description(self),
-                tag,
-                parent
+                "cos-nonambig: {} matches both this particle and the one at {}",
+                term, first
             ),
-            _ => write!(f, "{}", error::Error::description(self)),
+            Error::ForeignElement { ref name, .. } => write!(f, "Foreign element: {}", name),
+            Error::ForeignAttribute {
+                ref element,
+                ref name,
+                ..
+            } => write!(f, "Foreign attribute: {} on {}", name, element),
+            Error::SchemaNotFound { ref location, .. } => {
+                write!(f, "Schema not found: {}", location)
+            }
+            Error::UnsupportedRedefine { ref location, .. } => {
+                write!(f, "Redefine is not supported: {}", location)
+            }
+            Error::InvalidCatalog { .. } => write!(f, "Invalid catalog"),
+            Error::IO { ref error, .. } => error.fmt(f),
+            Error::ParseXml { ref error, .. } => error.fmt(f),
+            Error::ReadXml { ref error, .. } => write!(f, "{}", error.msg()),
+            Error::Utf8 { ref error, .. } => error.fmt(f),
+            Error::UnsupportedEncoding { ref encoding, .. } => {
+                write!(f, "Unsupported encoding: {}", encoding)
+            }
+            Error::InvalidEncoding { ref encoding, .. } => {
+                write!(f, "Invalid {} data", encoding)
+            }
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
-            Error::UnhandledTag { .. } => "Unhandled tag",
-            Error::InvalidFinal => "Invalid final",
-            Error::InvalidFinalDefault => "Invalid final default",
-            Error::InvalidBlockDefault => "Invalid block default",
-            Error::InvalidFormDefault => "Invalid form default",
-            Error::InvalidUse => "Invalid use value",
-            Error::InvalidRootFolder => "Invalid root folder",
-            Error::ParseInt(ref e) => e.description(),
-            Error::IO(ref e) => e.description(),
-            Error::ParseXml(ref e) => e.description(),
-            Error::EmptyXml => "Empty XML document",
-        }
-    }
-
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            Error::ParseInt(ref e) => Some(e),
-            Error::IO(ref e) => Some(e),
-            Error::ParseXml(ref e) => Some(e),
+            Error::IO { ref error, .. } => Some(error),
+            Error::ParseXml { ref error, .. } => Some(error),
+            Error::ReadXml { ref error, .. } => Some(&**error),
+            Error::Utf8 { ref error, .. } => Some(error),
             _ => None,
         }
     }
 }
 
-impl From<std::num::ParseIntError> for Error {
-    fn from(err: std::num::ParseIntError) -> Error {
-        Error::ParseInt(err)
-    }
-}
-
 impl From<std::io::Error> for Error {
-    fn from(err: std::io::Error) -> Error {
-        Error::IO(err)
+    fn from(error: std::io::Error) -> Error {
+        Error::IO { error, span: None }
     }
 }
 
-impl From<roxmltree::Error> for Error {
-    fn from(err: roxmltree::Error) -> Error {
-        Error::ParseXml(err)
+impl From<std::str::Utf8Error> for Error {
+    fn from(error: std::str::Utf8Error) -> Error {
+        Error::Utf8 { error, span: None }
     }
 }
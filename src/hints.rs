@@ -0,0 +1,241 @@
+//! Validation against the schemas an instance names in its
+//! `xsi:schemaLocation` and `xsi:noNamespaceSchemaLocation` attributes.
+//! Following them loads whatever a document points at, so the location for
+//! a namespace can be overridden and hints can be ignored altogether.
+
+use std::collections::HashSet;
+use std::mem;
+
+use roxmltree::Document;
+
+use crate::diagnostics::Diagnostics;
+use crate::encoding;
+use crate::errors::Error;
+use crate::options::ParserOptions;
+use crate::parser::{base_uri, Parser};
+use crate::resolver::SchemaResolver;
+use crate::span::Span;
+use crate::uri;
+use crate::validator::{Validator, XSI_NAMESPACE};
+
+/// A schema an instance asks to be validated against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    /// The namespace it is for, `None` for `xsi:noNamespaceSchemaLocation`.
+    pub namespace: Option<String>,
+    pub location: String,
+    /// The base URI in effect where the hint is, which `location` is
+    /// relative to.
+    pub base: String,
+    /// The attribute the hint is in.
+    pub span: Span,
+}
+
+/// Collects the hints on every element of `document`, in document order.
+/// A `xsi:schemaLocation` lists namespaces and locations in pairs; a
+/// namespace without a location is ignored. A `xsi:noNamespaceSchemaLocation`
+/// is a single location.
+pub fn hints(document: &Document, location: &str, source: Option<&str>) -> Vec<Hint> {
+    let mut hints = Vec::new();
+
+    for node in document.descendants().filter(|node| node.is_element()) {
+        for attribute in node.attributes() {
+            if attribute.namespace() != Some(XSI_NAMESPACE) {
+                continue;
+            }
+
+            let pairs: Vec<(Option<&str>, &str)> = match attribute.name() {
+                "schemaLocation" => attribute
+                    .value()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .chunks_exact(2)
+                    .map(|pair| (Some(pair[0]), pair[1]))
+                    .collect(),
+                "noNamespaceSchemaLocation" => Some(attribute.value().trim())
+                    .filter(|location| !location.is_empty())
+                    .map(|location| (None, location))
+                    .into_iter()
+                    .collect(),
+                _ => continue,
+            };
+
+            let span = match source {
+                Some(source) => Span::attribute(location, source, node, attribute.name()),
+                None => Span::attribute_position(location, node, attribute.name()),
+            };
+
+            hints.extend(pairs.into_iter().map(|(namespace, hinted)| Hint {
+                namespace: namespace.map(str::to_owned),
+                location: hinted.to_owned(),
+                base: base_uri(node, location),
+                span: span.clone(),
+            }));
+        }
+    }
+
+    hints
+}
+
+/// Configures validation against the schemas an instance names. By default
+/// the first hint for each namespace is loaded through the resolver.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaHints {
+    options: ParserOptions,
+    forbid: bool,
+    overrides: Vec<(Option<String>, String)>,
+}
+
+impl SchemaHints {
+    pub fn new() -> Self {
+        SchemaHints::default()
+    }
+
+    /// How the loaded schemas are parsed.
+    pub fn parser_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Whether hints are ignored, so that only overridden locations are
+    /// loaded. An untrusted document then cannot have schemas read from
+    /// locations of its choosing.
+    pub fn forbid(mut self, forbid: bool) -> Self {
+        self.forbid = forbid;
+        self
+    }
+
+    /// Loads the schema at `location` for `namespace`, in place of any hint
+    /// for it. The location is resolved as the root location of
+    /// `Parser::parse_with_resolver` is.
+    pub fn override_location(mut self, namespace: Option<&str>, location: &str) -> Self {
+        self.overrides
+            .push((namespace.map(str::to_owned), location.to_owned()));
+        self
+    }
+
+    /// Loads the schemas for `document` through `resolver`: the overridden
+    /// locations, then the first hint for each other namespace. Schemas
+    /// that cannot be loaded are recorded in `Parser::diagnostics`. When
+    /// none could be, there is no parser and the diagnostics are returned.
+    pub fn load(
+        &self,
+        document: &Document,
+        location: &str,
+        source: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Parser, Diagnostics> {
+        let mut parser = self.load_any(document, location, source, resolver);
+
+        if parser.documents.is_empty() {
+            return Err(mem::take(&mut parser.diagnostics));
+        }
+
+        Ok(parser)
+    }
+
+    /// Loads what `load` does, into a parser that may have no documents.
+    fn load_any(
+        &self,
+        document: &Document,
+        location: &str,
+        source: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Parser {
+        let mut parser = Parser::new(self.options);
+        let mut namespaces = HashSet::new();
+
+        for (namespace, schema_location) in &self.overrides {
+            namespaces.insert(namespace.clone());
+            load(
+                &mut parser,
+                None,
+                schema_location,
+                namespace.as_deref(),
+                None,
+                resolver,
+            );
+        }
+
+        if self.forbid {
+            return parser;
+        }
+
+        for hint in hints(document, location, source) {
+            if namespaces.insert(hint.namespace.clone()) {
+                load(
+                    &mut parser,
+                    Some(&hint.base),
+                    &hint.location,
+                    hint.namespace.as_deref(),
+                    Some(hint.span),
+                    resolver,
+                );
+            }
+        }
+
+        parser
+    }
+
+    /// Parses a document held in a string and validates it against the
+    /// schemas it names, as `load` finds them. Schemas that cannot be
+    /// loaded are reported first.
+    pub fn validate_str(
+        &self,
+        contents: &str,
+        location: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Diagnostics, Error> {
+        let location = location.unwrap_or_default();
+        let document = Document::parse(contents).map_err(|error| Error::ParseXml {
+            span: Span::text_pos(location, contents, error.pos()),
+            error,
+        })?;
+
+        let mut parser = self.load_any(&document, location, Some(contents), resolver);
+        let mut diagnostics = mem::take(&mut parser.diagnostics);
+        let schema_set = parser.schema_set();
+
+        diagnostics.append(&mut Validator::new(&schema_set).run(
+            &document,
+            location,
+            Some(contents),
+        ));
+
+        Ok(diagnostics)
+    }
+}
+
+/// Loads a schema into `parser`, recording why it could not be loaded.
+/// Problems without a location of their own point at `span`, the hint.
+fn load(
+    parser: &mut Parser,
+    base: Option<&str>,
+    schema_location: &str,
+    namespace: Option<&str>,
+    span: Option<Span>,
+    resolver: &dyn SchemaResolver,
+) {
+    let result = resolver
+        .resolve(base, schema_location, namespace)
+        .and_then(|resolved| {
+            let resolved = resolved.ok_or_else(|| Error::SchemaNotFound {
+                location: match base {
+                    Some(base) => uri::resolve(base, schema_location),
+                    None => schema_location.to_owned(),
+                },
+                span: None,
+            })?;
+            let contents = encoding::decode(&resolved.contents)?;
+
+            parser.parse_document(&resolved.location, &contents, None, resolver)
+        });
+
+    if let Err(error) = result {
+        let error = match span {
+            Some(span) => error.with_span(span),
+            None => error,
+        };
+        parser.diagnostics.push(error);
+    }
+}
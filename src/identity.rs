@@ -0,0 +1,408 @@
+//! Evaluation of identity constraints as the elements of an instance are
+//! read. A constraint opens a scope at each element declared with it, which
+//! collects a row of field values for every element its selector picks out.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::ptr;
+
+use crate::diagnostics::Diagnostics;
+use crate::identity_constraint::{IdentityConstraint, IdentityKind};
+use crate::parser::SchemaDocument;
+use crate::shared::QName;
+use crate::span::Span;
+use crate::stream::{report, Position};
+use crate::value::TypedValue;
+use crate::xpath::XPath;
+
+/// An identity constraint with its selector and fields compiled.
+pub(crate) struct Identity<'a> {
+    pub constraint: &'a IdentityConstraint,
+    pub name: QName,
+    /// The key or unique constraint a keyref refers to.
+    pub refer: Option<QName>,
+    /// Whether a keyref refers to this constraint, so that its rows have to
+    /// be kept once its scope closes.
+    pub referenced: bool,
+    selector: XPath,
+    fields: Vec<XPath>,
+}
+
+impl<'a> Identity<'a> {
+    pub(crate) fn new(
+        constraint: &'a IdentityConstraint,
+        document: &SchemaDocument,
+    ) -> Result<Self, String> {
+        let namespaces = &document.schema.namespaces;
+        let selector = XPath::selector(&constraint.selector, namespaces)?;
+        let fields = constraint
+            .fields
+            .iter()
+            .map(|field| XPath::field(field, namespaces))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Identity {
+            constraint,
+            name: QName::new(document.target_namespace.as_deref(), &constraint.name),
+            refer: constraint.refer.as_ref().and_then(|r| document.resolve(r)),
+            referenced: false,
+            selector,
+            fields,
+        })
+    }
+}
+
+/// What an element selected by a field provides once it closes.
+pub(crate) enum FieldValue {
+    /// The value of an element of simple type or with simple content, with
+    /// its normalized lexical form for messages.
+    Simple(TypedValue, String),
+    Nil,
+    Complex,
+}
+
+/// The values of the fields of a selected element, compared in the value
+/// space. Values that are not equal to themselves, such as NaN, never match.
+#[derive(Debug, Hash, PartialEq)]
+struct Key(Vec<TypedValue>);
+
+impl Eq for Key {}
+
+/// An element picked out by the selector of a scope, whose fields are being
+/// filled in.
+struct Target {
+    depth: usize,
+    span: Span,
+    values: Vec<Option<(TypedValue, String)>>,
+    /// Set once a problem with a field has been reported, which leaves the
+    /// element out of the table.
+    invalid: bool,
+}
+
+struct Scope<'v, 'a> {
+    identity: &'v Identity<'a>,
+    depth: usize,
+    targets: Vec<Target>,
+    /// The rows of a key or unique constraint, with where each was found.
+    rows: HashMap<Key, Span>,
+    /// The rows of a keyref, which are looked up when the scope closes.
+    references: Vec<(Key, Vec<String>, Span)>,
+}
+
+/// A field whose value is that of the element at `depth`, once it closes.
+struct Pending {
+    depth: usize,
+    scope: usize,
+    target: usize,
+    field: usize,
+}
+
+/// The rows of a closed key or unique scope, available to the keyrefs of
+/// the element at `depth` and those around it.
+struct Table<'v, 'a> {
+    identity: &'v Identity<'a>,
+    depth: usize,
+    rows: HashMap<Key, Span>,
+}
+
+/// The identity constraints in force at the open elements. Depths count
+/// the open elements, the root being at depth 1.
+#[derive(Default)]
+pub(crate) struct Identities<'v, 'a> {
+    scopes: Vec<Scope<'v, 'a>>,
+    pending: Vec<Pending>,
+    tables: Vec<Table<'v, 'a>>,
+}
+
+impl<'v, 'a> Identities<'v, 'a> {
+    /// Whether an identity constraint is in force, so that elements have to
+    /// be tracked.
+    pub(crate) fn is_open(&self) -> bool {
+        !self.scopes.is_empty()
+    }
+
+    /// An element starts. `names` are those of the open elements, this one
+    /// last, `identities` the constraints it is declared with and
+    /// `attributes` the values of its attributes.
+    pub(crate) fn start<P: Position>(
+        &mut self,
+        names: &[&QName],
+        identities: &[&'v Identity<'a>],
+        attributes: &[(&QName, TypedValue, &str)],
+        position: &P,
+        diagnostics: &mut Diagnostics,
+    ) {
+        let depth = names.len();
+
+        for &identity in identities {
+            self.scopes.push(Scope {
+                identity,
+                depth,
+                targets: Vec::new(),
+                rows: HashMap::new(),
+                references: Vec::new(),
+            });
+        }
+
+        for (index, scope) in self.scopes.iter_mut().enumerate() {
+            let identity = scope.identity;
+
+            if identity.selector.selects(&names[scope.depth..]) {
+                scope.targets.push(Target {
+                    depth,
+                    span: position.span(),
+                    values: vec![None; identity.fields.len()],
+                    invalid: false,
+                });
+            }
+
+            for (target_index, target) in scope.targets.iter_mut().enumerate() {
+                let path = &names[target.depth..];
+
+                for (field, xpath) in identity.fields.iter().enumerate() {
+                    if xpath.selects(path) {
+                        self.pending.push(Pending {
+                            depth,
+                            scope: index,
+                            target: target_index,
+                            field,
+                        });
+                    }
+
+                    for (name, value, lexical) in attributes {
+                        if xpath.selects_attribute(path, name) {
+                            let value = (value.clone(), (*lexical).to_owned());
+                            set(identity, target, field, value, position, diagnostics);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The element at `depth` ends. `value` is only called when a field
+    /// selects it.
+    pub(crate) fn end<P: Position>(
+        &mut self,
+        depth: usize,
+        value: impl FnOnce() -> FieldValue,
+        position: &P,
+        diagnostics: &mut Diagnostics,
+    ) {
+        if self.scopes.is_empty() {
+            self.tables.clear();
+            return;
+        }
+
+        let mut value = Some(value);
+        let mut field_value = None;
+
+        while let Some(pending) = self.pending.pop() {
+            if pending.depth != depth {
+                self.pending.push(pending);
+                break;
+            }
+
+            let scope = &mut self.scopes[pending.scope];
+            let identity = scope.identity;
+            let target = &mut scope.targets[pending.target];
+            let field = pending.field;
+
+            if field_value.is_none() {
+                field_value = value.take().map(|value| value());
+            }
+
+            match field_value {
+                Some(FieldValue::Simple(ref value, ref lexical)) => {
+                    let value = (value.clone(), lexical.clone());
+                    set(identity, target, field, value, position, diagnostics);
+                }
+                Some(FieldValue::Complex) if !target.invalid => {
+                    target.invalid = true;
+                    report(
+                        diagnostics,
+                        "cvc-identity-constraint.3",
+                        position.span(),
+                        Some(&identity.constraint.span),
+                        format!(
+                            "Field {:?} of {} selects an element without a simple type",
+                            identity.constraint.fields[field], identity.name
+                        ),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        for scope in &mut self.scopes {
+            while scope.targets.last().is_some_and(|t| t.depth == depth) {
+                if let Some(target) = scope.targets.pop() {
+                    close_target(scope, target, diagnostics);
+                }
+            }
+        }
+
+        let open = self
+            .scopes
+            .iter()
+            .position(|scope| scope.depth == depth)
+            .unwrap_or(self.scopes.len());
+        let closed: Vec<_> = self.scopes.drain(open..).collect();
+        let (references, tables): (Vec<_>, Vec<_>) = closed
+            .into_iter()
+            .partition(|scope| scope.identity.constraint.kind == IdentityKind::Keyref);
+
+        for scope in tables.into_iter().filter(|s| s.identity.referenced) {
+            self.tables.push(Table {
+                identity: scope.identity,
+                depth,
+                rows: scope.rows,
+            });
+        }
+
+        for scope in references {
+            self.references(scope, depth, diagnostics);
+        }
+
+        // The tables of this element become part of those of its parent.
+        let (inner, outer): (Vec<_>, Vec<_>) =
+            self.tables.drain(..).partition(|t| t.depth == depth);
+        self.tables = outer;
+
+        for mut table in inner.into_iter().filter(|_| depth > 1) {
+            let parent = self
+                .tables
+                .iter_mut()
+                .find(|t| t.depth == depth - 1 && ptr::eq(t.identity, table.identity));
+
+            match parent {
+                Some(parent) => {
+                    for (key, span) in table.rows {
+                        parent.rows.entry(key).or_insert(span);
+                    }
+                }
+                None => {
+                    table.depth -= 1;
+                    self.tables.push(table);
+                }
+            }
+        }
+    }
+
+    /// Looks up the rows of a keyref in the tables of the key or unique
+    /// constraint it refers to.
+    fn references(&self, scope: Scope<'v, 'a>, depth: usize, diagnostics: &mut Diagnostics) {
+        let identity = scope.identity;
+        let refer = match identity.refer {
+            Some(ref refer) => refer,
+            None => return,
+        };
+        let tables: Vec<_> = self
+            .tables
+            .iter()
+            .filter(|t| t.depth == depth && t.identity.name == *refer)
+            .collect();
+
+        for (key, lexical, span) in scope.references {
+            if !tables.iter().any(|t| t.rows.contains_key(&key)) {
+                report(
+                    diagnostics,
+                    "cvc-identity-constraint.4.3",
+                    span,
+                    Some(&identity.constraint.span),
+                    format!(
+                        "Keyref {} has the value {:?}, which is not a value of {}",
+                        identity.name, lexical, refer
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Fills in a field of a target, which may match only one node.
+fn set<P: Position>(
+    identity: &Identity,
+    target: &mut Target,
+    field: usize,
+    value: (TypedValue, String),
+    position: &P,
+    diagnostics: &mut Diagnostics,
+) {
+    if target.values[field].is_none() {
+        target.values[field] = Some(value);
+        return;
+    }
+
+    if !target.invalid {
+        target.invalid = true;
+        report(
+            diagnostics,
+            "cvc-identity-constraint.3",
+            position.span(),
+            Some(&identity.constraint.span),
+            format!(
+                "Field {:?} of {} selects more than one node",
+                identity.constraint.fields[field], identity.name
+            ),
+        );
+    }
+}
+
+/// Adds the row of a target to its scope once all its fields are known.
+fn close_target(scope: &mut Scope, target: Target, diagnostics: &mut Diagnostics) {
+    let identity = scope.identity;
+    let constraint = identity.constraint;
+
+    if target.invalid {
+        return;
+    }
+
+    if let Some(field) = target.values.iter().position(Option::is_none) {
+        if constraint.kind == IdentityKind::Key {
+            report(
+                diagnostics,
+                "cvc-identity-constraint.4.2.1",
+                target.span,
+                Some(&constraint.span),
+                format!(
+                    "Key {} has no value for field {:?}",
+                    identity.name, constraint.fields[field]
+                ),
+            );
+        }
+        return;
+    }
+
+    let (values, lexical): (Vec<_>, Vec<_>) = target.values.into_iter().flatten().unzip();
+    let key = Key(values);
+
+    if constraint.kind == IdentityKind::Keyref {
+        scope.references.push((key, lexical, target.span));
+        return;
+    }
+
+    match scope.rows.entry(key) {
+        Entry::Occupied(first) => {
+            let (rule, kind) = match constraint.kind {
+                IdentityKind::Key => ("cvc-identity-constraint.4.2.2", "key"),
+                _ => ("cvc-identity-constraint.4.1", "unique constraint"),
+            };
+            let first = first.get();
+
+            report(
+                diagnostics,
+                rule,
+                target.span,
+                Some(&constraint.span),
+                format!(
+                    "Duplicate value {:?} for {} {}, first found at line {}, column {}",
+                    lexical, kind, identity.name, first.line, first.column
+                ),
+            );
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(target.span);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! The `unique`, `key` and `keyref` constraints of element declarations,
+//! which pick out elements with a selector and identify them by the values
+//! of their fields.
+
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::shared::QName;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
+
+use roxmltree::Node;
+
+const SELECTOR: &str = "selector";
+const FIELD: &str = "field";
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IdentityKind {
+    #[default]
+    Unique,
+    Key,
+    Keyref,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct IdentityConstraint {
+    pub kind: IdentityKind,
+    pub name: String,
+    /// The key or unique constraint a keyref refers to.
+    pub refer: Option<String>,
+    pub id: Option<String>,
+    /// The XPath of the selector, relative to the declared element.
+    pub selector: String,
+    /// The XPaths of the fields, relative to each selected element.
+    pub fields: Vec<String>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for IdentityConstraint {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut constraint = IdentityConstraint {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        constraint.kind = match node.tag_name().name() {
+            "key" => IdentityKind::Key,
+            "keyref" => IdentityKind::Keyref,
+            _ => IdentityKind::Unique,
+        };
+
+        constraint.name = context.required_attribute(node, "name")?.to_owned();
+
+        if constraint.kind == IdentityKind::Keyref {
+            constraint.refer = Some(context.required_attribute(node, "refer")?.to_owned());
+        }
+
+        constraint.id = node.attribute("id").map(|id| id.to_owned());
+
+        for child in context.children(node) {
+            let child = child?;
+
+            match child.tag_name().name() {
+                SELECTOR => {
+                    constraint.selector = context.required_attribute(child, "xpath")?.to_owned()
+                }
+                FIELD => constraint
+                    .fields
+                    .push(context.required_attribute(child, "xpath")?.to_owned()),
+                _ => {}
+            }
+        }
+
+        Ok(constraint)
+    }
+}
@@ -1,4 +1,9 @@
-use crate::traits::TryFrom;
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::shared::QName;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
 
 use roxmltree::Node;
 
@@ -7,18 +12,24 @@ pub struct Import {
     pub id: Option<String>,
     pub namespace: Option<String>,
     pub schema_location: Option<String>,
+    /// Where the referenced document was loaded from, once it has been.
+    pub resolved_location: Option<String>,
+    /// Index of the loaded document in `Parser::documents`.
+    pub document: Option<usize>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Import {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
+impl FromNode for Import {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
         Ok(Import {
-            id: node.attribute("id").and_then(|i| Some(i.to_owned())),
-            namespace: node.attribute("namespace").and_then(|n| Some(n.to_owned())),
-            schema_location: node
-                .attribute("schemaLocation")
-                .and_then(|s| Some(s.to_owned())),
+            id: node.attribute("id").map(|i| i.to_owned()),
+            namespace: node.attribute("namespace").map(|n| n.to_owned()),
+            schema_location: node.attribute("schemaLocation").map(|s| s.to_owned()),
+            resolved_location: None,
+            document: None,
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
         })
     }
 }
@@ -1,4 +1,9 @@
-use crate::traits::TryFrom;
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::shared::QName;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
 
 use roxmltree::Node;
 
@@ -6,17 +11,25 @@ use roxmltree::Node;
 pub struct Include {
     pub id: Option<String>,
     pub schema_location: Option<String>,
+    /// Where the referenced document was loaded from, once it has been.
+    pub resolved_location: Option<String>,
+    /// Index of the loaded document in `Parser::documents`.
+    pub document: Option<usize>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Include {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
+impl FromNode for Include {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
         Ok(Include {
-            id: node.attribute("id").and_then(|id| Some(id.to_owned())),
+            id: node.attribute("id").map(|id| id.to_owned()),
             schema_location: node
                 .attribute("schemaLocation")
-                .and_then(|schema_location| Some(schema_location.to_owned())),
+                .map(|schema_location| schema_location.to_owned()),
+            resolved_location: None,
+            document: None,
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
         })
     }
 }
@@ -1,14 +1,56 @@
+mod automaton;
+mod blocks;
+mod builtins;
+pub mod catalog;
 pub mod complex_type;
+pub mod constraints;
+pub mod content_model;
+pub mod decimal;
+pub mod diagnostics;
 pub mod element;
+mod encoding;
 pub mod errors;
+pub mod hints;
+mod identity;
+pub mod identity_constraint;
 pub mod import;
 pub mod include;
+pub mod notation;
+pub mod options;
 pub mod parser;
+pub mod particle;
+pub mod pattern;
+pub mod resolver;
 pub mod restriction;
 pub mod schema;
+pub mod schema_set;
 pub mod shared;
 pub mod simple_type;
+pub mod span;
+pub mod stream;
+mod structure;
+pub mod substitution_group;
+pub mod temporal;
 mod traits;
+mod uri;
+pub mod validator;
+pub mod value;
+mod xpath;
 
+pub use crate::catalog::{Catalog, CatalogResolver};
+pub use crate::content_model::ContentModel;
+pub use crate::decimal::Decimal;
+pub use crate::diagnostics::{Diagnostic, Diagnostics, Severity};
 pub use crate::errors::Error;
-pub use crate::parser::{Elements, Parser};
+pub use crate::hints::SchemaHints;
+pub use crate::options::ParserOptions;
+pub use crate::parser::{Elements, Parser, SchemaDocument};
+pub use crate::pattern::{Pattern, PatternError};
+pub use crate::resolver::{ChainResolver, FileResolver, MemoryResolver, SchemaResolver};
+pub use crate::schema_set::SchemaSet;
+pub use crate::span::Span;
+pub use crate::stream::StreamValidator;
+pub use crate::substitution_group::SubstitutionGroups;
+pub use crate::temporal::{DateTime, Duration};
+pub use crate::validator::Validator;
+pub use crate::value::{FacetViolation, SimpleTypeValidator, TypedValue};
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use crate::errors::Error;
+use crate::shared::QName;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
+
+use roxmltree::Node;
+
+/// A notation declaration, which `NOTATION` values name.
+#[derive(Debug, PartialEq)]
+pub struct Notation {
+    pub id: Option<String>,
+    pub name: String,
+    pub public: Option<String>,
+    pub system: Option<String>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for Notation {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        Ok(Notation {
+            id: node.attribute("id").map(|i| i.to_owned()),
+            name: context.required_attribute(node, "name")?.to_owned(),
+            public: node.attribute("public").map(|p| p.to_owned()),
+            system: node.attribute("system").map(|s| s.to_owned()),
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+        })
+    }
+}
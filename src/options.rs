@@ -0,0 +1,132 @@
+use std::io::Read;
+use std::path::Path;
+
+use crate::encoding;
+use crate::errors::Error;
+use crate::parser::Parser;
+use crate::resolver::{FileResolver, SchemaResolver};
+
+/// Configures how schemas are parsed. The defaults, which `Parser::parse`
+/// and friends use, are strict parsing with foreign attributes allowed and
+/// foreign elements rejected, as the schema-for-schemas has it.
+#[derive(Clone, Copy, Debug)]
+pub struct ParserOptions {
+    pub(crate) lenient: bool,
+    pub(crate) allow_foreign_attributes: bool,
+    pub(crate) allow_foreign_elements: bool,
+    pub(crate) check_structure: bool,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            lenient: false,
+            allow_foreign_attributes: true,
+            allow_foreign_elements: false,
+            check_structure: true,
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        ParserOptions::default()
+    }
+
+    /// In lenient mode, constructs that cannot be parsed are skipped and
+    /// recorded in `Parser::diagnostics` instead of failing the parse. Only
+    /// documents that are not well-formed XML, or whose root is not a
+    /// schema, still fail.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Whether schema elements may carry attributes from other namespaces.
+    pub fn allow_foreign_attributes(mut self, allow: bool) -> Self {
+        self.allow_foreign_attributes = allow;
+        self
+    }
+
+    /// Whether elements from other namespaces may appear among schema
+    /// elements. Allowed ones are skipped.
+    pub fn allow_foreign_elements(mut self, allow: bool) -> Self {
+        self.allow_foreign_elements = allow;
+        self
+    }
+
+    /// Whether documents are checked against the schema-for-schemas: the
+    /// children each schema element may have and their order, its attributes
+    /// and their values, and the ones that exclude each other.
+    pub fn check_structure(mut self, check: bool) -> Self {
+        self.check_structure = check;
+        self
+    }
+
+    pub fn parse<P: AsRef<Path>>(&self, file_path: P) -> Result<Parser, Error> {
+        self.parse_with_resolver(&file_path.as_ref().to_string_lossy(), &FileResolver)
+    }
+
+    /// Parses a schema held in a string. Documents it imports or includes
+    /// are read from the filesystem, relative to `base_uri`. Without one,
+    /// relative locations are reported as `Error::SchemaNotFound`.
+    pub fn parse_str(&self, contents: &str, base_uri: Option<&str>) -> Result<Parser, Error> {
+        self.parse_str_with_resolver(contents, base_uri, &FileResolver)
+    }
+
+    /// Like `parse_str`, loading imported and included documents through
+    /// `resolver`.
+    pub fn parse_str_with_resolver(
+        &self,
+        contents: &str,
+        base_uri: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Parser, Error> {
+        let mut parser = Parser::new(*self);
+
+        parser.parse_document(base_uri.unwrap_or_default(), contents, None, resolver)?;
+
+        Ok(parser)
+    }
+
+    /// Like `parse_str`, for a document in any supported encoding.
+    pub fn parse_bytes(&self, contents: &[u8], base_uri: Option<&str>) -> Result<Parser, Error> {
+        self.parse_str(&encoding::decode(contents)?, base_uri)
+    }
+
+    /// Like `parse_bytes`, reading the document from `reader`.
+    pub fn parse_reader<R: Read>(
+        &self,
+        mut reader: R,
+        base_uri: Option<&str>,
+    ) -> Result<Parser, Error> {
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+
+        self.parse_bytes(&contents, base_uri)
+    }
+
+    /// Parses the schema at `location`, loading it and every document it
+    /// imports or includes through `resolver`.
+    pub fn parse_with_resolver(
+        &self,
+        location: &str,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Parser, Error> {
+        let resolved =
+            resolver
+                .resolve(None, location, None)?
+                .ok_or_else(|| Error::SchemaNotFound {
+                    location: location.to_owned(),
+                    span: None,
+                })?;
+
+        let mut parser = Parser::new(*self);
+
+        let contents = encoding::decode(&resolved.contents)?;
+
+        parser.parse_document(&resolved.location, &contents, None, resolver)?;
+
+        Ok(parser)
+    }
+}
@@ -1,131 +1,416 @@
-use std::fs::File;
-use std::io::prelude::*;
-use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
 
-use crate::complex_type::ComplexType;
+use crate::complex_type::{Attribute, AttributeGroup, ComplexType, Group};
+use crate::diagnostics::Diagnostics;
 use crate::element::Element;
+use crate::encoding;
 use crate::errors::Error;
 use crate::import::Import;
 use crate::include::Include;
+use crate::notation::Notation;
+use crate::options::ParserOptions;
+use crate::resolver::SchemaResolver;
 use crate::schema::Schema;
-use crate::simple_type::SimpleType;
-use crate::traits::TryFrom;
+use crate::schema_set::SchemaSet;
+use crate::shared::{FormDefault, QName};
+use crate::simple_type::{Annotation, SimpleType};
+use crate::span::Span;
+use crate::structure;
+use crate::traits::{Context, FromNode, XSD_NAMESPACE};
+use crate::uri;
 use roxmltree::{Document, Node};
 
 const SCHEMA: &str = "schema";
 const IMPORT: &str = "import";
 const INCLUDE: &str = "include";
+const REDEFINE: &str = "redefine";
 const ELEMENT: &str = "element";
 const SIMPLE_TYPE: &str = "simpleType";
 const COMPLEX_TYPE: &str = "complexType";
+const GROUP: &str = "group";
+const ATTRIBUTE: &str = "attribute";
+const ATTRIBUTE_GROUP: &str = "attributeGroup";
+const NOTATION: &str = "notation";
+const ANNOTATION: &str = "annotation";
 
+/// A top-level schema component.
 #[derive(Debug, PartialEq)]
 pub enum Elements {
-    Schema(Schema),
-    Import(Import),
-    Include(Include),
     Element(Element),
     SimpleType(SimpleType),
     ComplexType(Box<ComplexType>),
+    Group(Box<Group>),
+    Attribute(Box<Attribute>),
+    AttributeGroup(Box<AttributeGroup>),
+    Notation(Notation),
+}
+
+/// A loaded schema document and the components declared in it.
+#[derive(Debug, PartialEq)]
+pub struct SchemaDocument {
+    /// Where the document was loaded from, empty for a document parsed
+    /// from memory without a base URI.
+    pub location: String,
+    /// The document's target namespace, or the including document's when
+    /// this is a chameleon include of a schema without one.
+    pub target_namespace: Option<String>,
+    pub schema: Schema,
+    pub imports: Vec<Import>,
+    pub includes: Vec<Include>,
+    pub components: Vec<Elements>,
+    /// The annotations at the top level of the document.
+    pub annotations: Vec<Annotation>,
+}
+
+impl SchemaDocument {
+    /// Whether this document was included without a target namespace of its
+    /// own and took on the including document's.
+    pub fn is_chameleon(&self) -> bool {
+        self.schema.target_namespace.is_none() && self.target_namespace.is_some()
+    }
+
+    /// Resolves a QName-valued attribute of a component of this document.
+    /// Components of a chameleon include take on the including schema's
+    /// target namespace, so their unqualified references do too.
+    pub fn resolve(&self, value: &str) -> Option<QName> {
+        let mut qname = QName::resolve(value, &self.schema.namespaces)?;
+
+        if qname.namespace.is_none() && self.is_chameleon() {
+            qname.namespace = self.target_namespace.clone();
+        }
+
+        Some(qname)
+    }
+
+    pub fn element_form_default(&self) -> &FormDefault {
+        self.schema
+            .element_form_default
+            .as_ref()
+            .unwrap_or(&FormDefault::Unqualified)
+    }
+
+    pub fn attribute_form_default(&self) -> &FormDefault {
+        self.schema
+            .attribute_form_default
+            .as_ref()
+            .unwrap_or(&FormDefault::Unqualified)
+    }
 }
 
 #[derive(Debug)]
 pub struct Parser {
-    pub elements: Vec<Elements>,
-    root_folder: PathBuf,
+    /// Every loaded document, the root document first. Imports and includes
+    /// refer to the documents they loaded by index into this list.
+    pub documents: Vec<SchemaDocument>,
+    /// What lenient parsing skipped, and the hinted schemas `SchemaHints`
+    /// could not load. Otherwise always empty in strict mode.
+    pub diagnostics: Diagnostics,
+    options: ParserOptions,
+    /// Documents by resolved location and target namespace, so that each is
+    /// only loaded once even when schemas import each other.
+    registry: HashMap<(String, Option<String>), usize>,
 }
 
 impl Parser {
     pub fn parse<P: AsRef<Path>>(file_path: P) -> Result<Self, Error> {
-        let root_folder = match file_path.as_ref().parent() {
-            Some(rf) => rf,
-            None => return Err(Error::InvalidRootFolder),
-        };
+        ParserOptions::default().parse(file_path)
+    }
 
-        let contents = Parser::read_file(&file_path)?;
+    /// Parses a schema held in a string. Documents it imports or includes
+    /// are read from the filesystem, relative to `base_uri`. Without one,
+    /// relative locations are reported as `Error::SchemaNotFound`.
+    pub fn parse_str(contents: &str, base_uri: Option<&str>) -> Result<Self, Error> {
+        ParserOptions::default().parse_str(contents, base_uri)
+    }
 
-        let doc = Document::parse(&contents)?;
+    /// Like `parse_str`, loading imported and included documents through
+    /// `resolver`.
+    pub fn parse_str_with_resolver(
+        contents: &str,
+        base_uri: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Self, Error> {
+        ParserOptions::default().parse_str_with_resolver(contents, base_uri, resolver)
+    }
 
-        let mut parser = Parser {
-            elements: Vec::new(),
-            root_folder: root_folder.to_path_buf(),
-        };
+    /// Like `parse_str`, for a document in any supported encoding.
+    pub fn parse_bytes(contents: &[u8], base_uri: Option<&str>) -> Result<Self, Error> {
+        ParserOptions::default().parse_bytes(contents, base_uri)
+    }
 
-        parser.parse_node(doc.root())?;
+    /// Like `parse_bytes`, reading the document from `reader`.
+    pub fn parse_reader<R: Read>(reader: R, base_uri: Option<&str>) -> Result<Self, Error> {
+        ParserOptions::default().parse_reader(reader, base_uri)
+    }
 
-        Ok(parser)
+    /// Parses the schema at `location`, loading it and every document it
+    /// imports or includes through `resolver`.
+    pub fn parse_with_resolver(
+        location: &str,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<Self, Error> {
+        ParserOptions::default().parse_with_resolver(location, resolver)
     }
 
-    fn read_file<P: AsRef<Path>>(file_path: P) -> Result<String, Error> {
-        let mut file = File::open(file_path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
+    pub(crate) fn new(options: ParserOptions) -> Self {
+        Parser {
+            documents: Vec::new(),
+            diagnostics: Diagnostics::default(),
+            options,
+            registry: HashMap::new(),
+        }
+    }
 
-        Ok(contents)
+    /// The document that parsing started from.
+    pub fn root(&self) -> &SchemaDocument {
+        &self.documents[0]
     }
 
-    fn parse_node(&mut self, parent_node: Node) -> Result<(), Error> {
-        for node in parent_node.children().filter(|n| match n.node_type() {
-            roxmltree::NodeType::Element | roxmltree::NodeType::Root => true,
-            _ => false,
-        }) {
-            match node.tag_name().name() {
-                SCHEMA => {
-                    self.elements
-                        .push(Elements::Schema(Schema::try_from(node)?));
+    /// Indexes the global components of every document by qualified name.
+    pub fn schema_set(&self) -> SchemaSet<'_> {
+        SchemaSet::new(self)
+    }
 
-                    self.parse_node(node)?;
-                }
+    /// Loads the document referenced by `node`, an import or include,
+    /// returning the location it was resolved to and its index in
+    /// `documents`. `chameleon_namespace` is the including document's target
+    /// namespace for includes. Errors without a location of their own, such
+    /// as the document not being found, point at `node`.
+    fn load(
+        &mut self,
+        node: Node,
+        context: &Context,
+        schema_location: &str,
+        namespace: Option<&str>,
+        chameleon_namespace: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<(String, usize), Error> {
+        let base = base_uri(node, context.document);
 
-                IMPORT => {
-                    let import = Import::try_from(node)?;
+        let load = |parser: &mut Parser| {
+            // A document parsed from memory without a base URI has nothing to
+            // resolve relative locations against.
+            if base.is_empty() && uri::is_relative_path(schema_location) {
+                return Err(Error::SchemaNotFound {
+                    location: schema_location.to_owned(),
+                    span: None,
+                });
+            }
 
-                    if let Some(ref schema_location) = import.schema_location {
-                        let contents =
-                            Parser::read_file(self.root_folder.as_path().join(schema_location))?;
+            let resolved = resolver
+                .resolve(Some(&base), schema_location, namespace)?
+                .ok_or_else(|| Error::SchemaNotFound {
+                    location: uri::resolve(&base, schema_location),
+                    span: None,
+                })?;
 
-                        let doc = Document::parse(&contents)?;
+            let contents = encoding::decode(&resolved.contents)?;
 
-                        self.parse_node(doc.root())?;
-                    }
+            let document = parser.parse_document(
+                &resolved.location,
+                &contents,
+                chameleon_namespace,
+                resolver,
+            )?;
 
-                    self.elements.push(Elements::Import(import));
-                }
-                INCLUDE => {
-                    let include = Include::try_from(node)?;
+            Ok((resolved.location, document))
+        };
 
-                    if let Some(ref schema_location) = include.schema_location {
-                        let contents =
-                            Parser::read_file(self.root_folder.as_path().join(schema_location))?;
+        load(self).map_err(|e: Error| e.with_span(context.span(node)))
+    }
+
+    pub(crate) fn parse_document(
+        &mut self,
+        location: &str,
+        contents: &str,
+        chameleon_namespace: Option<&str>,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<usize, Error> {
+        let doc = Document::parse(contents).map_err(|error| Error::ParseXml {
+            span: Span::text_pos(location, contents, error.pos()),
+            error,
+        })?;
+
+        let root = doc.root_element();
+
+        let context = Context::new(location, contents, self.options);
+
+        if root.tag_name().namespace() != Some(XSD_NAMESPACE) || root.tag_name().name() != SCHEMA {
+            return Err(Error::UnhandledTag {
+                parent: String::new(),
+                tag: root.tag_name().name().to_owned(),
+                span: context.span(root),
+            });
+        }
+
+        context.check_attributes(root)?;
+
+        if self.options.check_structure {
+            structure::check(root, &context)?;
+        }
 
-                        let doc = Document::parse(&contents)?;
+        let schema = Schema::from_node(root, &context)?;
 
-                        self.parse_node(doc.root())?;
+        // A schema without a target namespace takes on the namespace of the
+        // schema including it, so it is loaded once per including namespace.
+        let target_namespace = schema
+            .target_namespace
+            .clone()
+            .or_else(|| chameleon_namespace.map(|n| n.to_owned()));
+
+        let key = (location.to_owned(), target_namespace.clone());
+
+        if let Some(&document) = self.registry.get(&key) {
+            return Ok(document);
+        }
+
+        let document = self.documents.len();
+
+        self.registry.insert(key, document);
+        self.documents.push(SchemaDocument {
+            location: location.to_owned(),
+            target_namespace,
+            schema,
+            imports: Vec::new(),
+            includes: Vec::new(),
+            components: Vec::new(),
+            annotations: Vec::new(),
+        });
+
+        let result = self.parse_node(root, &context, document, resolver);
+
+        let mut diagnostics = context.diagnostics.borrow_mut();
+        diagnostics.sort();
+        self.diagnostics.append(&mut diagnostics);
+
+        result.map(|_| document)
+    }
+
+    fn parse_node(
+        &mut self,
+        parent_node: Node,
+        context: &Context,
+        document: usize,
+        resolver: &dyn SchemaResolver,
+    ) -> Result<(), Error> {
+        let target_namespace = self.documents[document].target_namespace.clone();
+
+        for node in context.children(parent_node) {
+            let node = node?;
+
+            match node.tag_name().name() {
+                IMPORT => {
+                    let mut import: Import = match context.parse(node)? {
+                        Some(import) => import,
+                        None => continue,
+                    };
+
+                    if let Some(ref schema_location) = import.schema_location {
+                        match self.load(
+                            node,
+                            context,
+                            schema_location,
+                            import.namespace.as_deref(),
+                            None,
+                            resolver,
+                        ) {
+                            Ok((resolved_location, imported)) => {
+                                import.resolved_location = Some(resolved_location);
+                                import.document = Some(imported);
+                            }
+                            Err(error) => context.report(error)?,
+                        }
                     }
 
-                    self.elements.push(Elements::Include(include));
+                    self.documents[document].imports.push(import);
                 }
-                ELEMENT => self
-                    .elements
-                    .push(Elements::Element(Element::try_from(node)?)),
-                SIMPLE_TYPE => self
-                    .elements
-                    .push(Elements::SimpleType(SimpleType::try_from(node)?)),
-                COMPLEX_TYPE => {
-                    self.elements
-                        .push(Elements::ComplexType(Box::new(ComplexType::try_from(
+                INCLUDE => {
+                    let mut include: Include = match context.parse(node)? {
+                        Some(include) => include,
+                        None => continue,
+                    };
+
+                    if let Some(ref schema_location) = include.schema_location {
+                        match self.load(
                             node,
-                        )?)))
-                }
-                unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
-                        parent: parent_node.tag_name().name().to_owned(),
-                        tag: unknown.to_owned(),
-                    })
+                            context,
+                            schema_location,
+                            None,
+                            target_namespace.as_deref(),
+                            resolver,
+                        ) {
+                            Ok((resolved_location, included)) => {
+                                include.resolved_location = Some(resolved_location);
+                                include.document = Some(included);
+                            }
+                            Err(error) => context.report(error)?,
+                        }
+                    }
+
+                    self.documents[document].includes.push(include);
                 }
+                REDEFINE => context.report(Error::UnsupportedRedefine {
+                    location: node.attribute("schemaLocation").unwrap_or("").to_owned(),
+                    span: context.span(node),
+                })?,
+                ELEMENT => self.documents[document]
+                    .components
+                    .extend(context.parse(node)?.map(Elements::Element)),
+                SIMPLE_TYPE => self.documents[document]
+                    .components
+                    .extend(context.parse(node)?.map(Elements::SimpleType)),
+                COMPLEX_TYPE => self.documents[document].components.extend(
+                    context
+                        .parse(node)?
+                        .map(|complex_type| Elements::ComplexType(Box::new(complex_type))),
+                ),
+                GROUP => self.documents[document].components.extend(
+                    context
+                        .parse(node)?
+                        .map(|group| Elements::Group(Box::new(group))),
+                ),
+                ATTRIBUTE => self.documents[document].components.extend(
+                    context
+                        .parse(node)?
+                        .map(|attribute| Elements::Attribute(Box::new(attribute))),
+                ),
+                ATTRIBUTE_GROUP => self.documents[document].components.extend(
+                    context
+                        .parse(node)?
+                        .map(|attribute_group| Elements::AttributeGroup(Box::new(attribute_group))),
+                ),
+                NOTATION => self.documents[document]
+                    .components
+                    .extend(context.parse(node)?.map(Elements::Notation)),
+                ANNOTATION => self.documents[document]
+                    .annotations
+                    .extend(context.parse(node)?),
+                unknown => context.report(Error::UnhandledTag {
+                    parent: parent_node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(node),
+                })?,
             }
         }
 
         Ok(())
     }
 }
+
+/// Returns the base URI in effect at `node`: the document's location with
+/// any `xml:base` attributes on the node and its ancestors applied.
+pub(crate) fn base_uri(node: Node, location: &str) -> String {
+    let mut bases: Vec<&str> = std::iter::once(node)
+        .chain(node.ancestors())
+        .filter_map(|n| n.attribute((roxmltree::NS_XML_URI, "base")))
+        .collect();
+    bases.reverse();
+
+    bases
+        .into_iter()
+        .fold(location.to_owned(), |base, xml_base| {
+            uri::resolve(&base, xml_base)
+        })
+}
@@ -0,0 +1,517 @@
+//! Content models of complex types as trees of particles, with the
+//! children of each model group in document order.
+
+use std::collections::HashSet;
+
+use crate::complex_type::{
+    All, Any, Choice, ComplexType, Content, Group, NestedParticle, Sequence,
+};
+use crate::element::Element;
+use crate::parser::SchemaDocument;
+use crate::schema_set::SchemaSet;
+use crate::shared::{FormDefault, Occurrence, QName};
+use crate::span::Span;
+use crate::traits::XSD_NAMESPACE;
+
+#[derive(Clone, Debug)]
+pub struct Particle<'a> {
+    pub min: usize,
+    /// `None` for `unbounded`.
+    pub max: Option<usize>,
+    pub term: Term<'a>,
+    /// The document the particle is declared in, which its QNames are
+    /// resolved against.
+    pub document: &'a SchemaDocument,
+    pub span: &'a Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum Term<'a> {
+    Element(&'a Element),
+    Any(&'a Any),
+    Sequence(Vec<Particle<'a>>),
+    Choice(Vec<Particle<'a>>),
+    All(Vec<Particle<'a>>),
+}
+
+/// The model group children a complex type, extension or restriction may
+/// have. At most one is expected.
+#[derive(Clone, Copy, Default)]
+pub struct Groups<'a> {
+    pub sequence: Option<&'a Sequence>,
+    pub choice: Option<&'a Choice>,
+    pub all: Option<&'a All>,
+    pub group: Option<&'a Group>,
+}
+
+/// The namespaces a wildcard allows.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Namespaces {
+    Any,
+    /// Any namespace other than the given one, and not unqualified names.
+    Not(Option<String>),
+    Set(Vec<Option<String>>),
+}
+
+impl Namespaces {
+    /// The namespaces of a wildcard's `namespace` attribute, in a document
+    /// with the given target namespace.
+    pub fn new(namespace: Option<&str>, target_namespace: &Option<String>) -> Self {
+        match namespace.map(str::trim) {
+            None | Some("##any") => Namespaces::Any,
+            Some("##other") => Namespaces::Not(target_namespace.clone()),
+            Some(list) => Namespaces::Set(
+                list.split_whitespace()
+                    .map(|namespace| match namespace {
+                        "##targetNamespace" => target_namespace.clone(),
+                        "##local" => None,
+                        namespace => Some(namespace.to_owned()),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn allows(&self, namespace: Option<&str>) -> bool {
+        match *self {
+            Namespaces::Any => true,
+            Namespaces::Not(ref not) => namespace.is_some() && namespace != not.as_deref(),
+            Namespaces::Set(ref set) => set.iter().any(|n| n.as_deref() == namespace),
+        }
+    }
+
+    pub fn is_subset(&self, other: &Namespaces) -> bool {
+        match (self, other) {
+            (_, Namespaces::Any) => true,
+            (Namespaces::Not(not), Namespaces::Not(other_not)) => not == other_not,
+            (Namespaces::Set(set), other) => set.iter().all(|n| other.allows(n.as_deref())),
+            _ => false,
+        }
+    }
+
+    /// Whether some namespace is allowed by both.
+    pub fn intersects(&self, other: &Namespaces) -> bool {
+        match (self, other) {
+            (Namespaces::Any, _) | (_, Namespaces::Any) => true,
+            (Namespaces::Not(_), Namespaces::Not(_)) => true,
+            (Namespaces::Set(set), other) | (other, Namespaces::Set(set)) => {
+                set.iter().any(|n| other.allows(n.as_deref()))
+            }
+        }
+    }
+}
+
+fn occurs(min: &Option<Occurrence>, max: &Option<Occurrence>) -> (usize, Option<usize>) {
+    let min = match min {
+        Some(Occurrence::Limit(n)) => *n,
+        _ => 1,
+    };
+    let max = match max {
+        Some(Occurrence::Limit(n)) => Some(*n),
+        Some(Occurrence::Unbounded) => None,
+        None => Some(1),
+    };
+
+    (min, max)
+}
+
+impl<'a> Particle<'a> {
+    pub fn element(element: &'a Element, document: &'a SchemaDocument) -> Self {
+        let (min, max) = occurs(&element.min_occurrences, &element.max_occurrences);
+
+        Particle {
+            min,
+            max,
+            term: Term::Element(element),
+            document,
+            span: &element.span,
+        }
+    }
+
+    pub fn any(any: &'a Any, document: &'a SchemaDocument) -> Self {
+        let (min, max) = occurs(&any.min_occurrences, &any.max_occurrences);
+
+        Particle {
+            min,
+            max,
+            term: Term::Any(any),
+            document,
+            span: &any.span,
+        }
+    }
+
+    /// The particle of the model group a complex type, extension or
+    /// restriction has, if any.
+    pub fn group(
+        schema_set: &SchemaSet<'a>,
+        groups: Groups<'a>,
+        document: &'a SchemaDocument,
+    ) -> Option<Self> {
+        Builder::new(schema_set).groups(groups, document)
+    }
+
+    /// The least and most number of elements the particle matches, as
+    /// Effective Total Range has it.
+    pub fn total_range(&self) -> (usize, Option<usize>) {
+        let (min, max) = match self.term {
+            Term::Element(_) | Term::Any(_) => return (self.min, self.max),
+            Term::Sequence(ref particles) | Term::All(ref particles) => particles
+                .iter()
+                .map(|p| p.total_range())
+                .fold((0, Some(0)), |(min, max), (p_min, p_max)| {
+                    (min + p_min, max.and_then(|m| p_max.map(|p| m + p)))
+                }),
+            Term::Choice(ref particles) if particles.is_empty() => (0, Some(0)),
+            Term::Choice(ref particles) => particles.iter().map(|p| p.total_range()).fold(
+                (usize::MAX, Some(0)),
+                |(min, max), (p_min, p_max)| {
+                    (min.min(p_min), max.and_then(|m| p_max.map(|p| m.max(p))))
+                },
+            ),
+        };
+
+        let max = match (self.max, max) {
+            (_, Some(0)) | (Some(0), _) => Some(0),
+            (Some(a), Some(b)) => Some(a * b),
+            _ => None,
+        };
+
+        (self.min * min, max)
+    }
+
+    /// The namespaces a wildcard particle allows, none for other terms.
+    pub fn namespaces(&self) -> Namespaces {
+        let any = match self.term {
+            Term::Any(any) => any,
+            _ => return Namespaces::Set(Vec::new()),
+        };
+
+        Namespaces::new(any.namespace.as_deref(), &self.document.target_namespace)
+    }
+
+    pub fn is_emptiable(&self) -> bool {
+        self.total_range().0 == 0
+    }
+
+    /// The expanded name of the element declaration of an element particle.
+    /// Local declarations are qualified according to their form.
+    pub fn element_name(&self) -> Option<QName> {
+        let element = match self.term {
+            Term::Element(element) => element,
+            _ => return None,
+        };
+
+        if let Some(ref r) = element.r#ref {
+            return self.document.resolve(r);
+        }
+
+        let form = element
+            .form
+            .as_ref()
+            .unwrap_or_else(|| self.document.element_form_default());
+        let namespace = match form {
+            FormDefault::Qualified => self.document.target_namespace.as_deref(),
+            FormDefault::Unqualified => None,
+        };
+
+        Some(QName::new(namespace, element.name.as_deref()?))
+    }
+
+    /// The name of the type of an element particle, following `ref` to the
+    /// global declaration. `None` when the type is not named.
+    pub fn element_type(&self, schema_set: &SchemaSet<'a>) -> Option<QName> {
+        let element = match self.term {
+            Term::Element(element) => element,
+            _ => return None,
+        };
+
+        if element.r#ref.is_some() {
+            let global = schema_set.element(&self.element_name()?)?;
+            return global.resolve(global.component.r#type.as_ref()?);
+        }
+
+        self.document.resolve(element.r#type.as_ref()?)
+    }
+
+    /// Removes groups that make no difference to what the particle matches:
+    /// groups of a single particle that occur once, and groups that occur
+    /// once within a group of the same kind.
+    pub fn normalized(self) -> Self {
+        let Particle {
+            min,
+            max,
+            term,
+            document,
+            span,
+        } = self;
+
+        let flatten = |particles: Vec<Particle<'a>>, sequence: bool| {
+            let mut flattened = Vec::new();
+
+            for particle in particles.into_iter().map(Particle::normalized) {
+                let same_kind = match particle.term {
+                    Term::Sequence(_) => sequence,
+                    Term::Choice(_) => !sequence,
+                    _ => false,
+                };
+
+                match particle.term {
+                    Term::Sequence(children) | Term::Choice(children)
+                        if same_kind && particle.min == 1 && particle.max == Some(1) =>
+                    {
+                        flattened.extend(children)
+                    }
+                    term => flattened.push(Particle { term, ..particle }),
+                }
+            }
+
+            flattened
+        };
+
+        let term = match term {
+            Term::Sequence(particles) => Term::Sequence(flatten(particles, true)),
+            Term::Choice(particles) => Term::Choice(flatten(particles, false)),
+            Term::All(particles) => {
+                Term::All(particles.into_iter().map(Particle::normalized).collect())
+            }
+            term => term,
+        };
+
+        match term {
+            Term::Sequence(mut particles) | Term::Choice(mut particles)
+                if particles.len() == 1 && min == 1 && max == Some(1) =>
+            {
+                particles.remove(0)
+            }
+            term => Particle {
+                min,
+                max,
+                term,
+                document,
+                span,
+            },
+        }
+    }
+}
+
+/// The content model of a complex type: its own particle, after those of
+/// the types it extends. `None` for empty and simple content.
+pub fn content_model<'a>(
+    schema_set: &SchemaSet<'a>,
+    complex_type: &'a ComplexType,
+    document: &'a SchemaDocument,
+) -> Option<Particle<'a>> {
+    content_model_of(schema_set, complex_type, document, &mut HashSet::new())
+}
+
+fn content_model_of<'a>(
+    schema_set: &SchemaSet<'a>,
+    component: &'a ComplexType,
+    document: &'a SchemaDocument,
+    seen: &mut HashSet<*const ComplexType>,
+) -> Option<Particle<'a>> {
+    // Circular derivations are reported by the constraint checks, as
+    // ct-props-correct.3.
+    if !seen.insert(component) {
+        return None;
+    }
+
+    let extension = match component.complex_content {
+        Some(ref complex_content) => match complex_content.content {
+            Content::Restriction(ref restriction) => {
+                return Particle::group(
+                    schema_set,
+                    Groups {
+                        sequence: restriction.sequence.as_deref(),
+                        choice: restriction.choice.as_deref(),
+                        all: restriction.all.as_deref(),
+                        group: restriction.group.as_deref(),
+                    },
+                    document,
+                )
+            }
+            Content::Extension(ref extension) => extension,
+        },
+        None => {
+            return Particle::group(
+                schema_set,
+                Groups {
+                    sequence: component.sequence.as_ref(),
+                    choice: component.choice.as_ref(),
+                    all: component.all.as_ref(),
+                    group: component.group.as_ref(),
+                },
+                document,
+            )
+        }
+    };
+
+    let own = Particle::group(
+        schema_set,
+        Groups {
+            sequence: extension.sequence.as_ref(),
+            choice: extension.choice.as_ref(),
+            all: extension.all.as_ref(),
+            group: extension.group.as_ref(),
+        },
+        document,
+    );
+    let base = document
+        .resolve(&extension.base)
+        .filter(|base| base.namespace.as_deref() != Some(XSD_NAMESPACE))
+        .and_then(|base| schema_set.complex_type(&base))
+        .and_then(|base| content_model_of(schema_set, base.component, base.document, seen));
+
+    match (base, own) {
+        (Some(base), Some(own)) => Some(Particle {
+            min: 1,
+            max: Some(1),
+            span: own.span,
+            term: Term::Sequence(vec![base, own]),
+            document,
+        }),
+        (base, own) => base.or(own),
+    }
+}
+
+/// Builds the particles of model groups, replacing references to named
+/// groups with the groups they name.
+struct Builder<'s, 'a> {
+    schema_set: &'s SchemaSet<'a>,
+    /// The named groups being expanded. A group that refers to itself is
+    /// left out where it does.
+    expanding: Vec<*const Group>,
+}
+
+impl<'s, 'a> Builder<'s, 'a> {
+    fn new(schema_set: &'s SchemaSet<'a>) -> Self {
+        Builder {
+            schema_set,
+            expanding: Vec::new(),
+        }
+    }
+
+    fn groups(&mut self, groups: Groups<'a>, document: &'a SchemaDocument) -> Option<Particle<'a>> {
+        match groups {
+            Groups {
+                sequence: Some(sequence),
+                ..
+            } => Some(self.sequence(sequence, document)),
+            Groups {
+                choice: Some(choice),
+                ..
+            } => Some(self.choice(choice, document)),
+            Groups { all: Some(all), .. } => Some(self.all(all, document)),
+            Groups {
+                group: Some(group), ..
+            } => self.reference(group, document),
+            _ => None,
+        }
+    }
+
+    fn sequence(&mut self, sequence: &'a Sequence, document: &'a SchemaDocument) -> Particle<'a> {
+        let (min, max) = occurs(&sequence.min_occurrences, &sequence.max_occurrences);
+
+        Particle {
+            min,
+            max,
+            term: Term::Sequence(self.nested(&sequence.particles, document)),
+            document,
+            span: &sequence.span,
+        }
+    }
+
+    fn choice(&mut self, choice: &'a Choice, document: &'a SchemaDocument) -> Particle<'a> {
+        let (min, max) = occurs(&choice.min_occurrences, &choice.max_occurrences);
+
+        Particle {
+            min,
+            max,
+            term: Term::Choice(self.nested(&choice.particles, document)),
+            document,
+            span: &choice.span,
+        }
+    }
+
+    fn nested(
+        &mut self,
+        nested: &'a [NestedParticle],
+        document: &'a SchemaDocument,
+    ) -> Vec<Particle<'a>> {
+        let mut particles = Vec::new();
+
+        for particle in nested {
+            match *particle {
+                NestedParticle::Element(ref element) => {
+                    particles.push(Particle::element(element, document))
+                }
+                NestedParticle::Any(ref any) => particles.push(Particle::any(any, document)),
+                NestedParticle::Choice(ref choice) => particles.push(self.choice(choice, document)),
+                NestedParticle::Sequence(ref sequence) => {
+                    particles.push(self.sequence(sequence, document))
+                }
+                NestedParticle::Group(ref group) => {
+                    particles.extend(self.reference(group, document))
+                }
+            }
+        }
+
+        particles
+    }
+
+    fn all(&mut self, all: &'a All, document: &'a SchemaDocument) -> Particle<'a> {
+        let (min, max) = occurs(&all.min_occurrences, &all.max_occurrences);
+
+        Particle {
+            min,
+            max,
+            term: Term::All(
+                all.elements
+                    .iter()
+                    .map(|e| Particle::element(e, document))
+                    .collect(),
+            ),
+            document,
+            span: &all.span,
+        }
+    }
+
+    /// The particle of a reference to a named group: the occurrences of
+    /// the reference around the model group of the definition. Unresolved
+    /// and circular references are reported by the constraint checks, as
+    /// src-resolve and mg-props-correct.2.
+    fn reference(
+        &mut self,
+        group: &'a Group,
+        document: &'a SchemaDocument,
+    ) -> Option<Particle<'a>> {
+        let name = document.resolve(group.r#ref.as_ref()?)?;
+        let definition = self.schema_set.group(&name)?;
+        let component: *const Group = definition.component;
+
+        if self.expanding.contains(&component) {
+            return None;
+        }
+
+        self.expanding.push(component);
+        let particle = self.groups(
+            Groups {
+                sequence: definition.component.sequence.as_ref(),
+                choice: definition.component.choice.as_ref(),
+                all: definition.component.all.as_ref(),
+                group: None,
+            },
+            definition.document,
+        );
+        self.expanding.pop();
+
+        let (min, max) = occurs(&group.min_occurrences, &group.max_occurrences);
+
+        Some(Particle {
+            min,
+            max,
+            span: &group.span,
+            ..particle?
+        })
+    }
+}
@@ -0,0 +1,412 @@
+//! Regular expressions of the pattern facet. Their dialect differs from
+//! the usual ones: a pattern matches the whole value, `^` and `$` are
+//! ordinary characters, classes can be subtracted as in `[a-z-[aeiou]]`,
+//! and there are escapes for XML name characters and Unicode blocks. A
+//! pattern is translated to an equivalent expression of the `regex` crate,
+//! which matches in linear time.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use regex::Regex;
+
+use crate::blocks::BLOCKS;
+
+/// The characters `\i` matches: those that may start an XML name.
+const NAME_START: &str = "A-Z_:a-z\\x{C0}-\\x{D6}\\x{D8}-\\x{F6}\\x{F8}-\\x{2FF}\\x{370}-\\x{37D}\
+    \\x{37F}-\\x{1FFF}\\x{200C}-\\x{200D}\\x{2070}-\\x{218F}\\x{2C00}-\\x{2FEF}\
+    \\x{3001}-\\x{D7FF}\\x{F900}-\\x{FDCF}\\x{FDF0}-\\x{FFFD}\\x{10000}-\\x{EFFFF}";
+
+/// The characters `\c` matches besides those of `\i`.
+const NAME_CHAR: &str = "\\-.0-9\\x{B7}\\x{300}-\\x{36F}\\x{203F}-\\x{2040}";
+
+const CATEGORIES: &[&str] = &[
+    "L", "Lu", "Ll", "Lt", "Lm", "Lo", "M", "Mn", "Mc", "Me", "N", "Nd", "Nl", "No", "P", "Pc",
+    "Pd", "Ps", "Pe", "Pi", "Pf", "Po", "Z", "Zs", "Zl", "Zp", "S", "Sm", "Sc", "Sk", "So", "C",
+    "Cc", "Cf", "Co", "Cn",
+];
+
+/// A compiled pattern.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    source: String,
+    regex: Regex,
+}
+
+/// A pattern that is not a regular expression of the dialect, and the
+/// offset in characters where that was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.position)
+    }
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        let mut translator = Translator {
+            chars: pattern.chars().collect(),
+            position: 0,
+        };
+        let expression = translator.reg_exp()?;
+
+        if translator.peek().is_some() {
+            return translator.error("Unmatched )");
+        }
+
+        let regex =
+            Regex::new(&format!(r"\A(?:{})\z", expression)).map_err(|err| PatternError {
+                position: 0,
+                message: err.to_string(),
+            })?;
+
+        Ok(Pattern {
+            source: pattern.to_owned(),
+            regex,
+        })
+    }
+
+    /// Whether the whole of `value` matches.
+    pub fn is_match(&self, value: &str) -> bool {
+        self.regex.is_match(value)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Patterns compiled once and shared by each value they are checked
+/// against. Patterns that do not compile are kept as `None`.
+#[derive(Debug, Default)]
+pub(crate) struct Patterns(RefCell<HashMap<String, Option<Rc<Pattern>>>>);
+
+impl Patterns {
+    pub(crate) fn get(&self, source: &str) -> Option<Rc<Pattern>> {
+        self.0
+            .borrow_mut()
+            .entry(source.to_owned())
+            .or_insert_with(|| Pattern::new(source).ok().map(Rc::new))
+            .clone()
+    }
+}
+
+/// What an escape stands for: a single character, or a class in the syntax
+/// of the `regex` crate, which may be used inside another class.
+enum Escape {
+    Char(char),
+    Class(String),
+}
+
+struct Translator {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Translator {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).cloned()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.position += 1;
+        c
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, PatternError> {
+        Err(PatternError {
+            position: self.position.min(self.chars.len()),
+            message: message.to_owned(),
+        })
+    }
+
+    fn expect(&mut self, c: char, message: &str) -> Result<(), PatternError> {
+        match self.peek() {
+            Some(next) if next == c => {
+                self.position += 1;
+                Ok(())
+            }
+            _ => self.error(message),
+        }
+    }
+
+    fn reg_exp(&mut self) -> Result<String, PatternError> {
+        let mut expression = self.branch()?;
+
+        while self.peek() == Some('|') {
+            self.position += 1;
+            expression.push('|');
+            expression.push_str(&self.branch()?);
+        }
+
+        Ok(expression)
+    }
+
+    fn branch(&mut self) -> Result<String, PatternError> {
+        let mut branch = String::new();
+
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+
+            branch.push_str(&self.piece()?);
+        }
+
+        Ok(branch)
+    }
+
+    fn piece(&mut self) -> Result<String, PatternError> {
+        let mut piece = self.atom()?;
+
+        match self.peek() {
+            Some(quantifier @ '?') | Some(quantifier @ '*') | Some(quantifier @ '+') => {
+                self.position += 1;
+                piece.push(quantifier);
+            }
+            Some('{') => {
+                self.position += 1;
+                piece.push_str(&self.quantity()?);
+            }
+            _ => {}
+        }
+
+        Ok(piece)
+    }
+
+    fn quantity(&mut self) -> Result<String, PatternError> {
+        let min = match self.number()? {
+            Some(min) => min,
+            None => return self.error("Expected the minimum of a quantifier"),
+        };
+
+        let quantity = if self.peek() == Some(',') {
+            self.position += 1;
+
+            match self.number()? {
+                Some(max) if max < min => {
+                    return self.error("Quantifier maximum below its minimum")
+                }
+                Some(max) => format!("{{{},{}}}", min, max),
+                None => format!("{{{},}}", min),
+            }
+        } else {
+            format!("{{{}}}", min)
+        };
+
+        self.expect('}', "Unclosed quantifier")?;
+        Ok(quantity)
+    }
+
+    fn number(&mut self) -> Result<Option<u32>, PatternError> {
+        let start = self.position;
+
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+
+        if start == self.position {
+            return Ok(None);
+        }
+
+        let digits: String = self.chars[start..self.position].iter().collect();
+        match digits.parse() {
+            Ok(number) => Ok(Some(number)),
+            Err(_) => self.error("Quantifier too large"),
+        }
+    }
+
+    fn atom(&mut self) -> Result<String, PatternError> {
+        match self.next() {
+            Some('(') => {
+                let group = self.reg_exp()?;
+                self.expect(')', "Unclosed group")?;
+                Ok(format!("(?:{})", group))
+            }
+            Some('[') => self.class(),
+            Some('.') => Ok(r"[^\n\r]".to_owned()),
+            Some('\\') => match self.escape()? {
+                Escape::Char(c) => Ok(literal(c)),
+                Escape::Class(class) => Ok(class),
+            },
+            Some('?') | Some('*') | Some('+') | Some('{') | Some('}') | Some(']') => {
+                self.position -= 1;
+                self.error("Unexpected metacharacter")
+            }
+            Some(c) => Ok(literal(c)),
+            None => self.error("Unexpected end of pattern"),
+        }
+    }
+
+    /// A character class expression, after its `[`.
+    fn class(&mut self) -> Result<String, PatternError> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.position += 1;
+        }
+
+        let mut items = String::new();
+        let mut subtracted = None;
+
+        loop {
+            match self.peek() {
+                None => return self.error("Unclosed character class"),
+                Some(']') if items.is_empty() => return self.error("Empty character class"),
+                Some(']') => break,
+                Some('-') if self.peek_at(1) == Some('[') && !items.is_empty() => {
+                    self.position += 2;
+                    subtracted = Some(self.class()?);
+
+                    if self.peek() != Some(']') {
+                        return self.error("Subtraction must end its character class");
+                    }
+                    break;
+                }
+                Some('[') => return self.error("Unescaped [ in character class"),
+                Some('-') if !items.is_empty() && self.peek_at(1) != Some(']') => {
+                    return self.error("Unescaped - inside a character class")
+                }
+                Some(_) => items.push_str(&self.class_item()?),
+            }
+        }
+
+        self.position += 1;
+
+        let class = format!("[{}{}]", if negated { "^" } else { "" }, items);
+        Ok(match subtracted {
+            Some(subtracted) => format!("[{}--{}]", class, subtracted),
+            None => class,
+        })
+    }
+
+    /// A single character, a range or an escape inside a class.
+    fn class_item(&mut self) -> Result<String, PatternError> {
+        let start = match self.next() {
+            Some('\\') => match self.escape()? {
+                Escape::Char(c) => c,
+                Escape::Class(class) => return Ok(class),
+            },
+            Some(c) => c,
+            None => return self.error("Unclosed character class"),
+        };
+
+        let is_range =
+            self.peek() == Some('-') && self.peek_at(1).is_some_and(|c| c != ']' && c != '[');
+        if !is_range {
+            return Ok(literal(start));
+        }
+
+        self.position += 1;
+        let end = match self.next() {
+            Some('\\') => match self.escape()? {
+                Escape::Char(c) => c,
+                Escape::Class(_) => return self.error("A range cannot end with a class escape"),
+            },
+            Some(c) => c,
+            None => return self.error("Unclosed character class"),
+        };
+
+        if end < start {
+            return self.error("Range out of order");
+        }
+
+        Ok(format!("{}-{}", literal(start), literal(end)))
+    }
+
+    /// An escape, after its `\`.
+    fn escape(&mut self) -> Result<Escape, PatternError> {
+        let escape = match self.next() {
+            Some('n') => Escape::Char('\n'),
+            Some('r') => Escape::Char('\r'),
+            Some('t') => Escape::Char('\t'),
+            Some(
+                c @ ('\\' | '|' | '.' | '?' | '*' | '+' | '(' | ')' | '{' | '}' | '-' | '[' | ']'
+                | '^'),
+            ) => Escape::Char(c),
+            Some('s') => Escape::Class(r"[\x20\t\n\r]".to_owned()),
+            Some('S') => Escape::Class(r"[^\x20\t\n\r]".to_owned()),
+            Some('i') => Escape::Class(format!("[{}]", NAME_START)),
+            Some('I') => Escape::Class(format!("[^{}]", NAME_START)),
+            Some('c') => Escape::Class(format!("[{}{}]", NAME_START, NAME_CHAR)),
+            Some('C') => Escape::Class(format!("[^{}{}]", NAME_START, NAME_CHAR)),
+            Some('d') => Escape::Class(r"\p{Nd}".to_owned()),
+            Some('D') => Escape::Class(r"\P{Nd}".to_owned()),
+            Some('w') => Escape::Class(r"[^\p{P}\p{Z}\p{C}]".to_owned()),
+            Some('W') => Escape::Class(r"[\p{P}\p{Z}\p{C}]".to_owned()),
+            Some(p @ 'p') | Some(p @ 'P') => Escape::Class(self.property(p == 'P')?),
+            Some(_) => {
+                self.position -= 1;
+                return self.error("Unknown escape");
+            }
+            None => return self.error("Pattern ends with \\"),
+        };
+
+        Ok(escape)
+    }
+
+    /// A category or block escape, after its `\p` or `\P`.
+    fn property(&mut self, negated: bool) -> Result<String, PatternError> {
+        self.expect('{', "Expected { after \\p")?;
+
+        let start = self.position;
+        while self.peek().is_some_and(|c| c != '}') {
+            self.position += 1;
+        }
+        let name: String = self.chars[start..self.position].iter().collect();
+        self.expect('}', "Unclosed property escape")?;
+
+        if let Some(block) = name.strip_prefix("Is") {
+            let mut ranges = String::new();
+
+            for &(_, first, last) in BLOCKS.iter().filter(|&&(b, _, _)| b == block) {
+                // Surrogates are not characters, so only the rest of a
+                // block can match.
+                for (first, last) in [(first, last.min(0xD7FF)), (first.max(0xE000), last)] {
+                    if first <= last {
+                        ranges.push_str(&format!("\\x{{{:X}}}-\\x{{{:X}}}", first, last));
+                    }
+                }
+            }
+
+            if ranges.is_empty() {
+                if BLOCKS.iter().any(|&(b, _, _)| b == block) {
+                    return Ok(if negated { r"[\s\S]" } else { r"[^\s\S]" }.to_owned());
+                }
+
+                self.position = start;
+                return self.error("Unknown Unicode block");
+            }
+
+            return Ok(format!("[{}{}]", if negated { "^" } else { "" }, ranges));
+        }
+
+        if !CATEGORIES.contains(&name.as_str()) {
+            self.position = start;
+            return self.error("Unknown Unicode category");
+        }
+
+        Ok(format!("\\{}{{{}}}", if negated { 'P' } else { 'p' }, name))
+    }
+}
+
+fn literal(c: char) -> String {
+    regex::escape(c.encode_utf8(&mut [0; 4]))
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+
+use crate::errors::Error;
+use crate::uri;
+
+/// A schema document located by a `SchemaResolver`.
+#[derive(Debug, PartialEq)]
+pub struct ResolvedSchema {
+    /// Where the document was found. Locations referenced by the document
+    /// are resolved against it.
+    pub location: String,
+    pub contents: Vec<u8>,
+}
+
+/// Maps the `schemaLocation` of an `xs:import` or `xs:include` to the
+/// document it refers to. The location of an `xs:redefine` is never
+/// resolved: redefine is rejected with `Error::UnsupportedRedefine`, and
+/// lenient parsing skips it along with the components of that document.
+pub trait SchemaResolver {
+    /// Resolves `schema_location`, referenced from the document at `base`
+    /// (`None` for the root document). `namespace` is the namespace of an
+    /// import. Returns `Ok(None)` if this resolver does not know the
+    /// location, so that another resolver can be tried.
+    fn resolve(
+        &self,
+        base: Option<&str>,
+        schema_location: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<ResolvedSchema>, Error>;
+}
+
+impl<R: SchemaResolver + ?Sized> SchemaResolver for Box<R> {
+    fn resolve(
+        &self,
+        base: Option<&str>,
+        schema_location: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<ResolvedSchema>, Error> {
+        (**self).resolve(base, schema_location, namespace)
+    }
+}
+
+/// Resolves `schema_location` against `base`, per RFC 3986.
+fn absolute(base: Option<&str>, schema_location: &str) -> String {
+    match base {
+        Some(base) => uri::resolve(base, schema_location),
+        None => schema_location.to_owned(),
+    }
+}
+
+/// Reads schemas from the filesystem. Locations are paths or `file:` URIs.
+#[derive(Debug, Default)]
+pub struct FileResolver;
+
+impl SchemaResolver for FileResolver {
+    fn resolve(
+        &self,
+        base: Option<&str>,
+        schema_location: &str,
+        _namespace: Option<&str>,
+    ) -> Result<Option<ResolvedSchema>, Error> {
+        let location = absolute(base, schema_location);
+
+        let path = match uri::to_file_path(&location) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        match fs::read(path) {
+            Ok(contents) => Ok(Some(ResolvedSchema { location, contents })),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::from(e)),
+        }
+    }
+}
+
+/// Serves schemas from memory, keyed by location. A location is looked up
+/// relative to the referencing document, or as written when there is none.
+#[derive(Debug, Default)]
+pub struct MemoryResolver {
+    schemas: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        MemoryResolver::default()
+    }
+
+    pub fn insert<L: Into<String>, C: Into<Vec<u8>>>(&mut self, location: L, contents: C) {
+        self.schemas.insert(location.into(), contents.into());
+    }
+
+    fn get(&self, location: String) -> Option<ResolvedSchema> {
+        self.schemas.get(&location).map(|contents| ResolvedSchema {
+            location,
+            contents: contents.clone(),
+        })
+    }
+}
+
+impl SchemaResolver for MemoryResolver {
+    fn resolve(
+        &self,
+        base: Option<&str>,
+        schema_location: &str,
+        _namespace: Option<&str>,
+    ) -> Result<Option<ResolvedSchema>, Error> {
+        Ok(self.get(absolute(base, schema_location)))
+    }
+}
+
+/// Tries each resolver in turn and returns the first match.
+#[derive(Default)]
+pub struct ChainResolver {
+    resolvers: Vec<Box<dyn SchemaResolver>>,
+}
+
+impl ChainResolver {
+    pub fn new() -> Self {
+        ChainResolver::default()
+    }
+
+    pub fn push<R: SchemaResolver + 'static>(&mut self, resolver: R) {
+        self.resolvers.push(Box::new(resolver));
+    }
+}
+
+impl SchemaResolver for ChainResolver {
+    fn resolve(
+        &self,
+        base: Option<&str>,
+        schema_location: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<ResolvedSchema>, Error> {
+        for resolver in &self.resolvers {
+            if let Some(resolved) = resolver.resolve(base, schema_location, namespace)? {
+                return Ok(Some(resolved));
+            }
+        }
+
+        Ok(None)
+    }
+}
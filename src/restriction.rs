@@ -1,4 +1,11 @@
-use crate::traits::TryFrom;
+use std::collections::HashMap;
+
+use crate::complex_type::{All, AnyAttribute, Attribute, AttributeGroup, Choice, Group, Sequence};
+use crate::errors::Error;
+use crate::shared::QName;
+use crate::simple_type::SimpleType;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
 
 use roxmltree::Node;
 
@@ -9,8 +16,20 @@ const MIN_LENGTH: &str = "minLength";
 const MAX_LENGTH: &str = "maxLength";
 const MIN_INCLUSIVE: &str = "minInclusive";
 const MAX_INCLUSIVE: &str = "maxInclusive";
+const MIN_EXCLUSIVE: &str = "minExclusive";
+const MAX_EXCLUSIVE: &str = "maxExclusive";
+const WHITE_SPACE: &str = "whiteSpace";
 const FRACTION_DIGITS: &str = "fractionDigits";
 const TOTAL_DIGITS: &str = "totalDigits";
+const SEQUENCE: &str = "sequence";
+const CHOICE: &str = "choice";
+const ALL: &str = "all";
+const GROUP: &str = "group";
+const ATTRIBUTE: &str = "attribute";
+const ATTRIBUTE_GROUP: &str = "attributeGroup";
+const ANY_ATTRIBUTE: &str = "anyAttribute";
+const ANNOTATION: &str = "annotation";
+const SIMPLE_TYPE: &str = "simpleType";
 
 #[derive(Debug, PartialEq)]
 pub enum Restrictions {
@@ -21,58 +40,135 @@ pub enum Restrictions {
     MaxLength(String),
     MinInclusive(String),
     MaxInclusive(String),
+    MinExclusive(String),
+    MaxExclusive(String),
+    WhiteSpace(String),
     FractionDigits(String),
     TotalDigits(String),
 }
 
+impl Restrictions {
+    /// The name of the facet and its value.
+    pub fn facet(&self) -> (&'static str, &str) {
+        match *self {
+            Restrictions::Enumeration(ref v) => (ENUMERATION, v),
+            Restrictions::Pattern(ref v) => (PATTERN, v),
+            Restrictions::Length(ref v) => (LENGTH, v),
+            Restrictions::MinLength(ref v) => (MIN_LENGTH, v),
+            Restrictions::MaxLength(ref v) => (MAX_LENGTH, v),
+            Restrictions::MinInclusive(ref v) => (MIN_INCLUSIVE, v),
+            Restrictions::MaxInclusive(ref v) => (MAX_INCLUSIVE, v),
+            Restrictions::MinExclusive(ref v) => (MIN_EXCLUSIVE, v),
+            Restrictions::MaxExclusive(ref v) => (MAX_EXCLUSIVE, v),
+            Restrictions::WhiteSpace(ref v) => (WHITE_SPACE, v),
+            Restrictions::FractionDigits(ref v) => (FRACTION_DIGITS, v),
+            Restrictions::TotalDigits(ref v) => (TOTAL_DIGITS, v),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Restriction {
-    pub base: String,
+    /// The base type by name, when it is not given inline as `simple_type`.
+    pub base: Option<String>,
+    pub simple_type: Option<Box<SimpleType>>,
     pub restrictions: Vec<Restrictions>,
+    /// The content model of a restricted complex type.
+    pub sequence: Option<Box<Sequence>>,
+    pub choice: Option<Box<Choice>>,
+    pub all: Option<Box<All>>,
+    pub group: Option<Box<Group>>,
+    pub attributes: Vec<Attribute>,
+    pub attribute_groups: Vec<AttributeGroup>,
+    pub any_attribute: Option<Box<AnyAttribute>>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Restriction {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
+impl FromNode for Restriction {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
         let mut restriction = Restriction {
-            // TODO: Remove unwrap()
-            base: node.attribute("base").unwrap().to_owned(),
+            base: node.attribute("base").map(str::to_owned),
+            simple_type: None,
             restrictions: Vec::new(),
+            sequence: None,
+            choice: None,
+            all: None,
+            group: None,
+            attributes: Vec::new(),
+            attribute_groups: Vec::new(),
+            any_attribute: None,
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
         };
 
-        for child in node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-        {
-            let r = match child.tag_name().name() {
-                ENUMERATION => {
-                    Restrictions::Enumeration(child.attribute("value").unwrap().to_owned())
+        for child in context.children(node) {
+            let child = child?;
+
+            let facet: fn(String) -> Restrictions = match child.tag_name().name() {
+                ENUMERATION => Restrictions::Enumeration,
+                PATTERN => Restrictions::Pattern,
+                LENGTH => Restrictions::Length,
+                MIN_LENGTH => Restrictions::MinLength,
+                MAX_LENGTH => Restrictions::MaxLength,
+                MIN_INCLUSIVE => Restrictions::MinInclusive,
+                MAX_INCLUSIVE => Restrictions::MaxInclusive,
+                MIN_EXCLUSIVE => Restrictions::MinExclusive,
+                MAX_EXCLUSIVE => Restrictions::MaxExclusive,
+                WHITE_SPACE => Restrictions::WhiteSpace,
+                FRACTION_DIGITS => Restrictions::FractionDigits,
+                TOTAL_DIGITS => Restrictions::TotalDigits,
+                SEQUENCE => {
+                    restriction.sequence = context.parse(child)?.map(Box::new);
+                    continue;
+                }
+                CHOICE => {
+                    restriction.choice = context.parse(child)?.map(Box::new);
+                    continue;
+                }
+                ALL => {
+                    restriction.all = context.parse(child)?.map(Box::new);
+                    continue;
                 }
-                PATTERN => Restrictions::Pattern(child.attribute("value").unwrap().to_owned()),
-                LENGTH => Restrictions::Length(child.attribute("value").unwrap().to_owned()),
-                MIN_LENGTH => Restrictions::MinLength(child.attribute("value").unwrap().to_owned()),
-                MAX_LENGTH => Restrictions::MaxLength(child.attribute("value").unwrap().to_owned()),
-                MIN_INCLUSIVE => {
-                    Restrictions::MinInclusive(child.attribute("value").unwrap().to_owned())
+                GROUP => {
+                    restriction.group = context.parse(child)?.map(Box::new);
+                    continue;
                 }
-                MAX_INCLUSIVE => {
-                    Restrictions::MaxInclusive(child.attribute("value").unwrap().to_owned())
+                ATTRIBUTE => {
+                    restriction
+                        .attributes
+                        .extend(context.parse::<Attribute>(child)?);
+                    continue;
                 }
-                FRACTION_DIGITS => {
-                    Restrictions::FractionDigits(child.attribute("value").unwrap().to_owned())
+                ATTRIBUTE_GROUP => {
+                    restriction
+                        .attribute_groups
+                        .extend(context.parse::<AttributeGroup>(child)?);
+                    continue;
                 }
-                TOTAL_DIGITS => {
-                    Restrictions::TotalDigits(child.attribute("value").unwrap().to_owned())
+                ANY_ATTRIBUTE => {
+                    restriction.any_attribute = context.parse(child)?.map(Box::new);
+                    continue;
                 }
+                SIMPLE_TYPE => {
+                    restriction.simple_type = context.parse(child)?.map(Box::new);
+                    continue;
+                }
+                ANNOTATION => continue,
                 unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
+                    context.report(crate::errors::Error::UnhandledTag {
                         parent: node.tag_name().name().to_owned(),
                         tag: unknown.to_owned(),
-                    })
+                        span: context.span(child),
+                    })?;
+                    continue;
                 }
             };
-            restriction.restrictions.push(r);
+
+            match context.required_attribute(child, "value") {
+                Ok(value) => restriction.restrictions.push(facet(value.to_owned())),
+                Err(error) => context.report(error)?,
+            }
         }
 
         Ok(restriction)
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use crate::complex_type::{Attribute, AttributeGroup, ComplexType, Group};
+use crate::element::Element;
+use crate::notation::Notation;
+use crate::parser::{Elements, Parser, SchemaDocument};
+use crate::shared::QName;
+use crate::simple_type::SimpleType;
+
+/// A top-level component along with the document that declares it, which
+/// is needed to resolve the QNames it references.
+#[derive(Debug)]
+pub struct Global<'a, T> {
+    pub name: QName,
+    pub document: &'a SchemaDocument,
+    pub component: &'a T,
+}
+
+impl<'a, T> Clone for Global<'a, T> {
+    fn clone(&self) -> Self {
+        Global {
+            name: self.name.clone(),
+            document: self.document,
+            component: self.component,
+        }
+    }
+}
+
+impl<'a, T> Global<'a, T> {
+    /// Resolves a QName-valued attribute of this component.
+    pub fn resolve(&self, value: &str) -> Option<QName> {
+        self.document.resolve(value)
+    }
+}
+
+#[derive(Debug)]
+struct Index<'a, T> {
+    components: Vec<Global<'a, T>>,
+    names: HashMap<QName, usize>,
+}
+
+impl<'a, T> Default for Index<'a, T> {
+    fn default() -> Self {
+        Index {
+            components: Vec::new(),
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, T> Index<'a, T> {
+    fn insert(&mut self, document: &'a SchemaDocument, name: Option<&String>, component: &'a T) {
+        let name = match name {
+            Some(name) => QName::new(document.target_namespace.as_deref(), name),
+            None => return,
+        };
+
+        let idx = self.components.len();
+        self.names.entry(name.clone()).or_insert(idx);
+        self.components.push(Global {
+            name,
+            document,
+            component,
+        });
+    }
+
+    fn get(&self, name: &QName) -> Option<&Global<'a, T>> {
+        self.names.get(name).map(|&idx| &self.components[idx])
+    }
+}
+
+/// The global components of a parsed schema, indexed by qualified name.
+#[derive(Debug, Default)]
+pub struct SchemaSet<'a> {
+    elements: Index<'a, Element>,
+    complex_types: Index<'a, ComplexType>,
+    simple_types: Index<'a, SimpleType>,
+    groups: Index<'a, Group>,
+    attributes: Index<'a, Attribute>,
+    attribute_groups: Index<'a, AttributeGroup>,
+    notations: Index<'a, Notation>,
+}
+
+impl<'a> SchemaSet<'a> {
+    pub fn new(parser: &'a Parser) -> Self {
+        let mut schema_set = SchemaSet::default();
+
+        for document in &parser.documents {
+            for component in &document.components {
+                match component {
+                    Elements::Element(element) => {
+                        schema_set
+                            .elements
+                            .insert(document, element.name.as_ref(), element)
+                    }
+                    Elements::ComplexType(complex_type) => schema_set.complex_types.insert(
+                        document,
+                        complex_type.name.as_ref(),
+                        complex_type,
+                    ),
+                    Elements::SimpleType(simple_type) => schema_set.simple_types.insert(
+                        document,
+                        simple_type.name.as_ref(),
+                        simple_type,
+                    ),
+                    Elements::Group(group) => {
+                        schema_set
+                            .groups
+                            .insert(document, group.name.as_ref(), group)
+                    }
+                    Elements::Attribute(attribute) => {
+                        schema_set
+                            .attributes
+                            .insert(document, attribute.name.as_ref(), attribute)
+                    }
+                    Elements::AttributeGroup(attribute_group) => schema_set
+                        .attribute_groups
+                        .insert(document, attribute_group.name.as_ref(), attribute_group),
+                    Elements::Notation(notation) => {
+                        schema_set
+                            .notations
+                            .insert(document, Some(&notation.name), notation)
+                    }
+                }
+            }
+        }
+
+        schema_set
+    }
+
+    pub fn element(&self, name: &QName) -> Option<&Global<'a, Element>> {
+        self.elements.get(name)
+    }
+
+    pub fn elements(&self) -> &[Global<'a, Element>] {
+        &self.elements.components
+    }
+
+    pub fn complex_type(&self, name: &QName) -> Option<&Global<'a, ComplexType>> {
+        self.complex_types.get(name)
+    }
+
+    pub fn complex_types(&self) -> &[Global<'a, ComplexType>] {
+        &self.complex_types.components
+    }
+
+    pub fn simple_type(&self, name: &QName) -> Option<&Global<'a, SimpleType>> {
+        self.simple_types.get(name)
+    }
+
+    pub fn simple_types(&self) -> &[Global<'a, SimpleType>] {
+        &self.simple_types.components
+    }
+
+    pub fn group(&self, name: &QName) -> Option<&Global<'a, Group>> {
+        self.groups.get(name)
+    }
+
+    pub fn groups(&self) -> &[Global<'a, Group>] {
+        &self.groups.components
+    }
+
+    pub fn attribute(&self, name: &QName) -> Option<&Global<'a, Attribute>> {
+        self.attributes.get(name)
+    }
+
+    pub fn attributes(&self) -> &[Global<'a, Attribute>] {
+        &self.attributes.components
+    }
+
+    pub fn attribute_group(&self, name: &QName) -> Option<&Global<'a, AttributeGroup>> {
+        self.attribute_groups.get(name)
+    }
+
+    pub fn attribute_groups(&self) -> &[Global<'a, AttributeGroup>] {
+        &self.attribute_groups.components
+    }
+
+    pub fn notation(&self, name: &QName) -> Option<&Global<'a, Notation>> {
+        self.notations.get(name)
+    }
+
+    pub fn notations(&self) -> &[Global<'a, Notation>] {
+        &self.notations.components
+    }
+}
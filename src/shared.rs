@@ -1,11 +1,13 @@
-use std::default::Default;
+use std::collections::HashMap;
+use std::fmt;
 use std::str::FromStr;
 
 use crate::errors::Error;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 pub enum FormDefault {
     Qualified,
+    #[default]
     Unqualified,
 }
 
@@ -16,17 +18,14 @@ impl FromStr for FormDefault {
         match s {
             "qualified" => Ok(FormDefault::Qualified),
             "unqualified" => Ok(FormDefault::Unqualified),
-            _ => Err(Error::InvalidFormDefault),
+            _ => Err(Error::InvalidFormDefault {
+                value: s.to_owned(),
+                span: None,
+            }),
         }
     }
 }
 
-impl Default for FormDefault {
-    fn default() -> Self {
-        FormDefault::Unqualified
-    }
-}
-
 #[derive(Debug, PartialEq)]
 pub enum Final {
     Extension,
@@ -42,30 +41,51 @@ impl FromStr for Final {
             "extension" => Ok(Final::Extension),
             "restriction" => Ok(Final::Restriction),
             "#all" => Ok(Final::All),
-            _ => Err(Error::InvalidFinal),
+            _ => Err(Error::InvalidFinal {
+                value: s.to_owned(),
+                span: None,
+            }),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum BlockDefault {
-    Extension,
-    Restriction,
-    Substitution,
-    All,
+/// What a `block` or `blockDefault` attribute blocks: `#all`, or a list of
+/// `extension`, `restriction` and `substitution`.
+#[derive(Debug, Default, PartialEq)]
+pub struct BlockDefault {
+    pub extension: bool,
+    pub restriction: bool,
+    pub substitution: bool,
 }
 
 impl FromStr for BlockDefault {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "extension" => Ok(BlockDefault::Extension),
-            "restriction" => Ok(BlockDefault::Restriction),
-            "substitution" => Ok(BlockDefault::Substitution),
-            "#all" => Ok(BlockDefault::All),
-            _ => Err(Error::InvalidBlockDefault),
+        let mut block = BlockDefault::default();
+
+        if s.trim() == "#all" {
+            block.extension = true;
+            block.restriction = true;
+            block.substitution = true;
+            return Ok(block);
         }
+
+        for token in s.split_whitespace() {
+            match token {
+                "extension" => block.extension = true,
+                "restriction" => block.restriction = true,
+                "substitution" => block.substitution = true,
+                _ => {
+                    return Err(Error::InvalidBlockDefault {
+                        value: s.to_owned(),
+                        span: None,
+                    })
+                }
+            }
+        }
+
+        Ok(block)
     }
 }
 
@@ -88,7 +108,10 @@ impl FromStr for FinalDefault {
             "list" => Ok(FinalDefault::List),
             "union" => Ok(FinalDefault::Union),
             "#all" => Ok(FinalDefault::All),
-            _ => Err(Error::InvalidFinalDefault),
+            _ => Err(Error::InvalidFinalDefault {
+                value: s.to_owned(),
+                span: None,
+            }),
         }
     }
 }
@@ -107,8 +130,53 @@ impl FromStr for Occurrence {
             "unbounded" => Ok(Occurrence::Unbounded),
             num => match num.parse::<usize>() {
                 Ok(n) => Ok(Occurrence::Limit(n)),
-                Err(e) => Err(Error::ParseInt(e)),
+                Err(_) => Err(Error::InvalidOccurs {
+                    value: s.to_owned(),
+                    span: None,
+                }),
             },
         }
     }
 }
+
+/// A namespace-qualified name, as referenced by `type`, `ref` and
+/// `substitutionGroup` attributes.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct QName {
+    pub namespace: Option<String>,
+    pub name: String,
+}
+
+impl QName {
+    pub fn new(namespace: Option<&str>, name: &str) -> Self {
+        QName {
+            namespace: namespace.map(|n| n.to_owned()),
+            name: name.to_owned(),
+        }
+    }
+
+    /// Resolves a (possibly prefixed) name against a set of namespace
+    /// declarations keyed as in `Schema::namespaces`. Unprefixed names take
+    /// the default namespace, if one is declared.
+    pub fn resolve(value: &str, namespaces: &HashMap<String, String>) -> Option<Self> {
+        match value.find(':') {
+            Some(idx) => {
+                let namespace = namespaces.get(&value[..idx])?;
+                Some(QName::new(Some(namespace), &value[idx + 1..]))
+            }
+            None => Some(QName::new(
+                namespaces.get("default").map(|n| n.as_str()),
+                value,
+            )),
+        }
+    }
+}
+
+impl fmt::Display for QName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.namespace {
+            Some(ref namespace) => write!(f, "{{{}}}{}", namespace, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
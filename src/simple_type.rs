@@ -1,11 +1,18 @@
+use std::collections::HashMap;
 use std::default::Default;
 
+use crate::errors::Error;
 use crate::restriction::Restriction;
-use crate::traits::TryFrom;
+use crate::shared::QName;
+use crate::span::Span;
+use crate::traits::{Context, FromNode};
 
 use roxmltree::Node;
 
 const RESTRICTION: &str = "restriction";
+const LIST: &str = "list";
+const UNION: &str = "union";
+const SIMPLE_TYPE: &str = "simpleType";
 const ANNOTATION: &str = "annotation";
 const DOCUMENTATION: &str = "documentation";
 const APPINFO: &str = "appinfo";
@@ -14,32 +21,38 @@ const APPINFO: &str = "appinfo";
 pub struct SimpleType {
     pub name: Option<String>,
     pub restriction: Option<Restriction>,
+    pub list: Option<Box<List>>,
+    pub union: Option<Box<Union>>,
     pub annotation: Option<Annotation>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for SimpleType {
-    type Error = crate::errors::Error;
-
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let mut simple_type = SimpleType::default();
+impl FromNode for SimpleType {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut simple_type = SimpleType {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
 
         if let Some(name) = node.attribute("name") {
             simple_type.name = Some(name.to_owned());
         }
 
-        for child in node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-        {
+        for child in context.children(node) {
+            let child = child?;
+
             match child.tag_name().name() {
-                RESTRICTION => simple_type.restriction = Some(Restriction::try_from(child)?),
-                ANNOTATION => simple_type.annotation = Some(Annotation::try_from(child)?),
-                unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
-                        parent: node.tag_name().name().to_owned(),
-                        tag: unknown.to_owned(),
-                    })
-                }
+                RESTRICTION => simple_type.restriction = context.parse(child)?,
+                LIST => simple_type.list = context.parse(child)?.map(Box::new),
+                UNION => simple_type.union = context.parse(child)?.map(Box::new),
+                ANNOTATION => simple_type.annotation = context.parse(child)?,
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
             }
         }
 
@@ -47,33 +60,120 @@ impl<'a, 'd> TryFrom<Node<'a, 'd>> for SimpleType {
     }
 }
 
+/// A simple type whose values are whitespace-separated lists of values of
+/// its item type.
+#[derive(Debug, Default, PartialEq)]
+pub struct List {
+    pub item_type: Option<String>,
+    /// An anonymous item type.
+    pub simple_type: Option<Box<SimpleType>>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for List {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut list = List {
+            item_type: node.attribute("itemType").map(|t| t.to_owned()),
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        for child in context.children(node) {
+            let child = child?;
+
+            match child.tag_name().name() {
+                SIMPLE_TYPE => list.simple_type = context.parse(child)?.map(Box::new),
+                ANNOTATION => {}
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
+            }
+        }
+
+        Ok(list)
+    }
+}
+
+/// A simple type whose values are those of any of its member types, tried
+/// in order.
+#[derive(Debug, Default, PartialEq)]
+pub struct Union {
+    pub member_types: Vec<String>,
+    /// Anonymous member types, which come after the named ones.
+    pub simple_types: Vec<SimpleType>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
+}
+
+impl FromNode for Union {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut union = Union {
+            member_types: node
+                .attribute("memberTypes")
+                .map(|m| m.split_whitespace().map(|t| t.to_owned()).collect())
+                .unwrap_or_default(),
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
+
+        for child in context.children(node) {
+            let child = child?;
+
+            match child.tag_name().name() {
+                SIMPLE_TYPE => union
+                    .simple_types
+                    .extend(context.parse::<SimpleType>(child)?),
+                ANNOTATION => {}
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
+            }
+        }
+
+        Ok(union)
+    }
+}
+
 #[derive(Debug, Default, PartialEq)]
 pub struct Annotation {
     pub documentation: Vec<String>,
     pub app_info: Vec<String>,
+    pub extra_attributes: HashMap<QName, String>,
+    pub span: Span,
 }
 
-impl<'a, 'd> TryFrom<Node<'a, 'd>> for Annotation {
-    type Error = crate::errors::Error;
+impl FromNode for Annotation {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error> {
+        let mut annotation = Annotation {
+            extra_attributes: context.extra_attributes(node),
+            span: context.span(node),
+            ..Default::default()
+        };
 
-    fn try_from(node: Node) -> Result<Self, Self::Error> {
-        let mut annotation = Annotation::default();
+        for child in context.children(node) {
+            let child = child?;
 
-        for child in node
-            .children()
-            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
-        {
             match child.tag_name().name() {
                 DOCUMENTATION => annotation
                     .documentation
-                    .push(child.text().unwrap().to_owned()),
-                APPINFO => annotation.app_info.push(child.text().unwrap().to_owned()),
-                unknown => {
-                    return Err(crate::errors::Error::UnhandledTag {
-                        parent: node.tag_name().name().to_owned(),
-                        tag: unknown.to_owned(),
-                    })
-                }
+                    .push(child.text().unwrap_or_default().to_owned()),
+                // Kept as written, as it is usually markup from another
+                // namespace.
+                APPINFO => annotation
+                    .app_info
+                    .push(context.inner_markup(child).to_owned()),
+                unknown => context.report(crate::errors::Error::UnhandledTag {
+                    parent: node.tag_name().name().to_owned(),
+                    tag: unknown.to_owned(),
+                    span: context.span(child),
+                })?,
             }
         }
 
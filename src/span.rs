@@ -0,0 +1,187 @@
+use std::fmt;
+
+use roxmltree::{Node, TextPos};
+
+/// Where a component, or the cause of an error, is in its schema document.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Span {
+    /// Location of the document, as in `SchemaDocument::location`.
+    pub document: String,
+    /// 1-based line of the first character.
+    pub line: u32,
+    /// 1-based column of the first character, counted in characters.
+    pub column: u32,
+    /// Byte offset of the first character.
+    pub start: usize,
+    /// Byte offset just past the last character.
+    pub end: usize,
+}
+
+impl Span {
+    /// The span of an element, from its start tag to the end of its end tag.
+    pub(crate) fn node(document: &str, source: &str, node: Node) -> Self {
+        let start = node.pos();
+        let end = if node.is_element() {
+            element_end(source, start)
+        } else {
+            start
+        };
+
+        Span::from_pos(document, node.node_pos(), start, end)
+    }
+
+    /// The span of an attribute of `node`, from its name to its closing
+    /// quote, or of the node itself if it has no such attribute.
+    pub(crate) fn attribute(document: &str, source: &str, node: Node, name: &str) -> Self {
+        let attribute = match node.attributes().iter().find(|a| a.name() == name) {
+            Some(attribute) => attribute,
+            None => return Span::node(document, source, node),
+        };
+
+        let start = attribute.pos();
+        let value = attribute.value_pos();
+        let end = source
+            .as_bytes()
+            .get(value.wrapping_sub(1))
+            .and_then(|&quote| source[value..].find(quote as char))
+            .map_or(value, |len| value + len + 1);
+
+        Span::from_pos(document, node.document().text_pos_from(start), start, end)
+    }
+
+    /// The content of an element, between its start and end tags.
+    pub(crate) fn inner<'s>(source: &'s str, node: Node) -> &'s str {
+        let start = start_tag_end(source, node.pos());
+
+        if source[..start].ends_with("/>") {
+            return "";
+        }
+
+        let end = element_end(source, node.pos());
+        let close = source[start..end]
+            .rfind("</")
+            .map_or(end, |idx| start + idx);
+
+        &source[start..close]
+    }
+
+    /// The position of a node, for a document whose source is not at hand.
+    pub(crate) fn position(document: &str, node: Node) -> Self {
+        Span::from_pos(document, node.node_pos(), node.pos(), node.pos())
+    }
+
+    /// The position of an attribute of `node`, or of the node itself if it
+    /// has no such attribute, for a document whose source is not at hand.
+    pub(crate) fn attribute_position(document: &str, node: Node, name: &str) -> Self {
+        match node.attributes().iter().find(|a| a.name() == name) {
+            Some(attribute) => Span::from_pos(
+                document,
+                node.document().text_pos_from(attribute.pos()),
+                attribute.pos(),
+                attribute.pos(),
+            ),
+            None => Span::position(document, node),
+        }
+    }
+
+    /// The span of a single position, as reported by the XML parser.
+    pub(crate) fn text_pos(document: &str, source: &str, pos: TextPos) -> Self {
+        let start = source
+            .split_inclusive('\n')
+            .take(pos.row.saturating_sub(1) as usize)
+            .map(str::len)
+            .sum::<usize>();
+        let start = source[start..]
+            .char_indices()
+            .nth(pos.col.saturating_sub(1) as usize)
+            .map_or(source.len(), |(idx, _)| start + idx);
+
+        Span::from_pos(document, pos, start, start)
+    }
+
+    fn from_pos(document: &str, pos: TextPos, start: usize, end: usize) -> Self {
+        Span {
+            document: document.to_owned(),
+            line: pos.row,
+            column: pos.col,
+            start,
+            end,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.document.is_empty() {
+            write!(f, "{}:{}", self.line, self.column)
+        } else {
+            write!(f, "{}:{}:{}", self.document, self.line, self.column)
+        }
+    }
+}
+
+/// Finds the end of the element starting at `start`. The source is known to
+/// be well-formed, so it is enough to skip markup that can hide a `<` or
+/// `>` and to count nested tags.
+fn element_end(source: &str, start: usize) -> usize {
+    let mut depth = 0;
+    let mut idx = start;
+
+    while let Some(offset) = source[idx..].find('<') {
+        let tag = idx + offset;
+        let rest = &source[tag..];
+
+        idx = if rest.starts_with("<!--") {
+            skip_past(source, tag, "-->")
+        } else if rest.starts_with("<![CDATA[") {
+            skip_past(source, tag, "]]>")
+        } else if rest.starts_with("<?") {
+            skip_past(source, tag, "?>")
+        } else if rest.starts_with("</") {
+            let end = skip_past(source, tag, ">");
+
+            depth -= 1;
+            if depth == 0 {
+                return end;
+            }
+
+            end
+        } else {
+            let end = start_tag_end(source, tag);
+
+            if source[..end].ends_with("/>") {
+                if depth == 0 {
+                    return end;
+                }
+            } else {
+                depth += 1;
+            }
+
+            end
+        };
+    }
+
+    source.len()
+}
+
+fn skip_past(source: &str, from: usize, pattern: &str) -> usize {
+    source[from..]
+        .find(pattern)
+        .map_or(source.len(), |idx| from + idx + pattern.len())
+}
+
+/// Finds the end of a start tag, skipping over quoted attribute values.
+fn start_tag_end(source: &str, from: usize) -> usize {
+    let mut quote = None;
+
+    for (idx, c) in source[from..].char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return from + idx + 1,
+            _ => {}
+        }
+    }
+
+    source.len()
+}
@@ -0,0 +1,1187 @@
+//! Validation driven by parse events, for documents too large to hold in
+//! memory. Only the open elements are kept, each with the state of its
+//! content model, so memory grows with the depth of the document rather than
+//! its size.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::io::Read;
+use std::mem;
+
+use xml::common::Position as _;
+use xml::namespace::Namespace;
+use xml::reader::{ParserConfig, XmlEvent};
+
+use crate::complex_type::{ComplexType, ProcessContents, Usage};
+use crate::content_model::Matcher;
+use crate::diagnostics::Diagnostics;
+use crate::element::{DefaultFixed, Element};
+use crate::errors::Error;
+use crate::identity::{FieldValue, Identities};
+use crate::parser::SchemaDocument;
+use crate::particle::{Particle, Term};
+use crate::restriction::Restriction;
+use crate::shared::{BlockDefault, QName};
+use crate::span::Span;
+use crate::validator::{
+    AttributeUse, AttributeWildcard, Declaration, IdKind, Type, Validator, XSI_NAMESPACE,
+};
+use crate::value::{FacetViolation, TypedValue};
+
+/// Where an event is in the instance. It is only turned into a span when a
+/// problem is reported there.
+pub trait Position {
+    fn span(&self) -> Span;
+}
+
+impl Position for Span {
+    fn span(&self) -> Span {
+        self.clone()
+    }
+}
+
+/// The line and column of an event read from a stream. Byte offsets are not
+/// tracked, so the span starts and ends at 0.
+#[derive(Clone, Copy, Debug)]
+pub struct LinePosition<'l> {
+    pub location: &'l str,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl<'l> Position for LinePosition<'l> {
+    fn span(&self) -> Span {
+        Span {
+            document: self.location.to_owned(),
+            line: self.line,
+            column: self.column,
+            start: 0,
+            end: 0,
+        }
+    }
+}
+
+/// Validates a document from its parse events: `start_element`, followed by
+/// the `namespace` declarations and `attribute`s of its start tag, then
+/// `text` and child elements, then `end_element`.
+pub struct StreamValidator<'v, 's, 'a, P: Position = Span> {
+    validator: &'v Validator<'s, 'a>,
+    /// The element whose start tag is being read.
+    start: Option<Start<P>>,
+    stack: Vec<Frame<'v, 'a, P>>,
+    identities: Identities<'v, 'a>,
+    /// The values of the attributes of the start tag, for identity
+    /// constraints.
+    attribute_values: Vec<(QName, TypedValue)>,
+    /// The IDs declared so far, with where each was first declared.
+    ids: HashMap<String, Span>,
+    /// References to IDs that had not been declared when they were read.
+    idrefs: Vec<(String, Span)>,
+    diagnostics: Diagnostics,
+}
+
+struct Start<P> {
+    name: QName,
+    position: P,
+    namespaces: Vec<(Option<String>, String)>,
+    attributes: Vec<(QName, String, P)>,
+}
+
+struct Frame<'v, 'a, P> {
+    name: QName,
+    position: P,
+    namespaces: Vec<(Option<String>, String)>,
+    state: State<'v, 'a>,
+}
+
+enum State<'v, 'a> {
+    /// A value of a simple type, or the simple content of a complex type.
+    /// `invalid` is set once a child element has been reported.
+    Simple {
+        declaration: Declaration<'a>,
+        ty: Type<'a>,
+        restrictions: Vec<&'a Restriction>,
+        complex_type: Option<&'a ComplexType>,
+        text: String,
+        invalid: bool,
+    },
+    /// Complex content, with `None` for a model that allows no children.
+    /// Once a child does not match, the rest are validated laxly.
+    Complex {
+        complex_type: &'a ComplexType,
+        model: Option<(Matcher<'v, 'a>, &'v Particle<'a>)>,
+        failed: bool,
+        text_reported: bool,
+    },
+    /// An element with `xsi:nil`, which has to be empty.
+    Nil {
+        declaration: Declaration<'a>,
+        content: bool,
+    },
+    /// Children are validated against their global declarations, where
+    /// there are any.
+    Lax,
+    /// Nothing inside is validated.
+    Skip,
+}
+
+/// How the parent of an element has it validated.
+enum Assessment<'a> {
+    Declared(Declaration<'a>),
+    Lax,
+    Skip,
+}
+
+impl<'v, 's, 'a, P: Position> StreamValidator<'v, 's, 'a, P> {
+    pub fn new(validator: &'v Validator<'s, 'a>) -> Self {
+        StreamValidator {
+            validator,
+            start: None,
+            stack: Vec::new(),
+            identities: Identities::default(),
+            attribute_values: Vec::new(),
+            ids: HashMap::new(),
+            idrefs: Vec::new(),
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    pub fn start_element(&mut self, name: QName, position: P) {
+        self.open();
+        self.start = Some(Start {
+            name,
+            position,
+            namespaces: Vec::new(),
+            attributes: Vec::new(),
+        });
+    }
+
+    /// A namespace declared on the element whose start tag is being read.
+    pub fn namespace(&mut self, prefix: Option<&str>, uri: &str) {
+        if let Some(ref mut start) = self.start {
+            start
+                .namespaces
+                .push((prefix.map(str::to_owned), uri.to_owned()));
+        }
+    }
+
+    pub fn attribute(&mut self, name: QName, value: &str, position: P) {
+        if let Some(ref mut start) = self.start {
+            start.attributes.push((name, value.to_owned(), position));
+        }
+    }
+
+    pub fn text(&mut self, text: &str, position: P) {
+        self.open();
+
+        let frame = match self.stack.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        match frame.state {
+            State::Simple {
+                text: ref mut value,
+                ..
+            } => value.push_str(text),
+            State::Complex {
+                complex_type,
+                ref model,
+                ref mut text_reported,
+                ..
+            } => {
+                if complex_type.mixed || *text_reported || text.trim().is_empty() {
+                    return;
+                }
+
+                let (rule, content) = match *model {
+                    Some(_) => ("cvc-complex-type.2.3", "element-only"),
+                    None => ("cvc-complex-type.2.1", "empty"),
+                };
+
+                *text_reported = true;
+                report(
+                    &mut self.diagnostics,
+                    rule,
+                    position.span(),
+                    Some(&complex_type.span),
+                    format!(
+                        "Element {} cannot have character content, as its content is {}",
+                        frame.name, content
+                    ),
+                );
+            }
+            State::Nil {
+                ref mut content, ..
+            } => *content |= !text.trim().is_empty(),
+            State::Lax | State::Skip => {}
+        }
+    }
+
+    pub fn end_element(&mut self) {
+        self.open();
+
+        let frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let name = &frame.name;
+        let mut typed = None;
+
+        match frame.state {
+            State::Simple {
+                declaration,
+                ty,
+                ref restrictions,
+                ref text,
+                invalid: false,
+                ..
+            } => typed = self.element_value(&frame, ty, restrictions, declaration, text),
+            State::Complex {
+                model: Some((ref matcher, particle)),
+                failed: false,
+                ..
+            } if !matcher.is_complete() => report(
+                &mut self.diagnostics,
+                "cvc-complex-type.2.4.b",
+                frame.position.span(),
+                Some(particle.span),
+                format!(
+                    "The content of element {} is not complete, {} is expected",
+                    name,
+                    describe(&matcher.expected())
+                ),
+            ),
+            State::Nil {
+                declaration,
+                content: true,
+            } => report(
+                &mut self.diagnostics,
+                "cvc-elt.3.2.1",
+                frame.position.span(),
+                Some(&declaration.element.span),
+                format!("Element {} is nil, so it must have no content", name),
+            ),
+            _ => {}
+        }
+
+        let value = || match frame.state {
+            State::Simple {
+                declaration,
+                ref text,
+                invalid: false,
+                ..
+            } => {
+                let lexical = element_text(declaration.element, text).trim();
+                let typed = typed.unwrap_or_else(|| TypedValue::String(lexical.to_owned()));
+                FieldValue::Simple(typed, lexical.to_owned())
+            }
+            State::Nil { .. } => FieldValue::Nil,
+            _ => FieldValue::Complex,
+        };
+
+        self.identities.end(
+            self.stack.len() + 1,
+            value,
+            &frame.position,
+            &mut self.diagnostics,
+        );
+    }
+
+    /// The problems found so far.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Ends the document, reporting the references to IDs that were never
+    /// declared.
+    pub fn finish(mut self) -> Diagnostics {
+        self.open();
+
+        for (value, span) in mem::take(&mut self.idrefs) {
+            if !self.ids.contains_key(&value) {
+                report(
+                    &mut self.diagnostics,
+                    "cvc-id.1",
+                    span,
+                    None,
+                    format!("There is no ID {:?} in the document", value),
+                );
+            }
+        }
+
+        self.diagnostics
+    }
+
+    /// Validates the start tag that has been read, once it is complete.
+    fn open(&mut self) {
+        let start = match self.start.take() {
+            Some(start) => start,
+            None => return,
+        };
+
+        let (declaration, state) = match self.assess(&start) {
+            Assessment::Declared(declaration) => {
+                (Some(declaration), self.element(&start, declaration))
+            }
+            Assessment::Lax => match self.validator.schema_set.element(&start.name) {
+                Some(global) => {
+                    let declaration = global.into();
+                    (Some(declaration), self.element(&start, declaration))
+                }
+                None => (None, State::Lax),
+            },
+            Assessment::Skip => (None, State::Skip),
+        };
+
+        self.identity_constraints(&start, declaration);
+        self.stack.push(Frame {
+            name: start.name,
+            position: start.position,
+            namespaces: start.namespaces,
+            state,
+        });
+    }
+
+    /// Opens the identity constraints of an element and matches it against
+    /// the selectors and fields of those already open.
+    fn identity_constraints(&mut self, start: &Start<P>, declaration: Option<Declaration<'a>>) {
+        let validator = self.validator;
+        let constraints = declaration.map_or(&[][..], |d| &d.element.identity_constraints[..]);
+
+        if self.identities.is_open() || !constraints.is_empty() {
+            let identities: Vec<_> = constraints
+                .iter()
+                .filter_map(|constraint| validator.identity(constraint))
+                .collect();
+            let names: Vec<_> = self
+                .stack
+                .iter()
+                .map(|frame| &frame.name)
+                .chain(std::iter::once(&start.name))
+                .collect();
+            let attribute_values = &self.attribute_values;
+            let attributes: Vec<_> = start
+                .attributes
+                .iter()
+                .filter(|(a, _, _)| a.namespace.as_deref() != Some(XSI_NAMESPACE))
+                .map(|(name, value, _)| {
+                    let typed = attribute_values
+                        .iter()
+                        .find(|(a, _)| a == name)
+                        .map_or_else(
+                            || TypedValue::String(value.trim().to_owned()),
+                            |(_, typed)| typed.clone(),
+                        );
+                    (name, typed, value.trim())
+                })
+                .collect();
+
+            self.identities.start(
+                &names,
+                &identities,
+                &attributes,
+                &start.position,
+                &mut self.diagnostics,
+            );
+        }
+
+        self.attribute_values.clear();
+    }
+
+    /// Matches an element against the content of its parent.
+    fn assess(&mut self, start: &Start<P>) -> Assessment<'a> {
+        let validator = self.validator;
+        let name = &start.name;
+
+        let parent = match self.stack.last_mut() {
+            Some(parent) => parent,
+            None => {
+                return match validator.schema_set.element(name) {
+                    Some(global) => Assessment::Declared(global.into()),
+                    None => {
+                        report(
+                            &mut self.diagnostics,
+                            "cvc-elt.1.a",
+                            start.position.span(),
+                            None,
+                            format!("Cannot find the declaration of element {}", name),
+                        );
+                        Assessment::Skip
+                    }
+                };
+            }
+        };
+
+        let (rule, component, message) = match parent.state {
+            State::Simple {
+                ref mut invalid,
+                complex_type,
+                declaration,
+                ..
+            } => {
+                if *invalid {
+                    return Assessment::Skip;
+                }
+                *invalid = true;
+
+                match complex_type {
+                    Some(complex_type) => (
+                        "cvc-complex-type.2.2",
+                        &complex_type.span,
+                        format!(
+                            "Element {} has simple content, so it cannot have child elements",
+                            parent.name
+                        ),
+                    ),
+                    None => (
+                        "cvc-type.3.1.2",
+                        &declaration.element.span,
+                        format!(
+                            "Element {} has a simple type, so it cannot have child elements",
+                            parent.name
+                        ),
+                    ),
+                }
+            }
+            State::Complex {
+                complex_type,
+                model: None,
+                ref mut failed,
+                ..
+            } => {
+                if *failed {
+                    return Assessment::Skip;
+                }
+                *failed = true;
+
+                (
+                    "cvc-complex-type.2.1",
+                    &complex_type.span,
+                    format!("Element {} must be empty", parent.name),
+                )
+            }
+            State::Complex { failed: true, .. } | State::Lax => return Assessment::Lax,
+            State::Complex {
+                model: Some((ref mut matcher, particle)),
+                ref mut failed,
+                ..
+            } => match matcher.next(name) {
+                Some(matched) => return self.matched(start, matched),
+                None => {
+                    *failed = true;
+
+                    let expected = matcher.expected();
+                    let (rule, expected) = if expected.is_empty() {
+                        ("cvc-complex-type.2.4.d", "no more elements are".to_owned())
+                    } else {
+                        (
+                            "cvc-complex-type.2.4.a",
+                            format!("{} is", describe(&expected)),
+                        )
+                    };
+
+                    report(
+                        &mut self.diagnostics,
+                        rule,
+                        start.position.span(),
+                        Some(particle.span),
+                        format!(
+                            "Element {} is not expected here, {} expected",
+                            name, expected
+                        ),
+                    );
+                    return Assessment::Lax;
+                }
+            },
+            State::Nil {
+                ref mut content, ..
+            } => {
+                *content = true;
+                return Assessment::Skip;
+            }
+            State::Skip => return Assessment::Skip,
+        };
+
+        report(
+            &mut self.diagnostics,
+            rule,
+            start.position.span(),
+            Some(component),
+            message,
+        );
+        Assessment::Skip
+    }
+
+    fn matched(&mut self, start: &Start<P>, particle: &Particle<'a>) -> Assessment<'a> {
+        let schema_set = self.validator.schema_set;
+        let name = &start.name;
+
+        match particle.term {
+            Term::Element(element) if element.r#ref.is_none() => {
+                Assessment::Declared(Declaration {
+                    element,
+                    document: particle.document,
+                })
+            }
+            // A reference matches its global declaration or a member of
+            // its substitution group.
+            Term::Element(_) => match schema_set.element(name) {
+                Some(global) => Assessment::Declared(global.into()),
+                None => Assessment::Lax,
+            },
+            Term::Any(any) => match any.process_contents {
+                ProcessContents::Skip => Assessment::Skip,
+                ProcessContents::Lax => Assessment::Lax,
+                ProcessContents::Strict => match schema_set.element(name) {
+                    Some(global) => Assessment::Declared(global.into()),
+                    None => {
+                        report(
+                            &mut self.diagnostics,
+                            "cvc-complex-type.2.4.c",
+                            start.position.span(),
+                            Some(&any.span),
+                            format!(
+                                "The matching wildcard is strict, but element {} is not declared",
+                                name
+                            ),
+                        );
+                        Assessment::Skip
+                    }
+                },
+            },
+            Term::Sequence(_) | Term::Choice(_) | Term::All(_) => Assessment::Skip,
+        }
+    }
+
+    /// Validates the start tag of an element against its declaration,
+    /// returning how its content is to be validated.
+    fn element(&mut self, start: &Start<P>, declaration: Declaration<'a>) -> State<'v, 'a> {
+        let validator = self.validator;
+        let name = &start.name;
+        let element = declaration.element;
+        let component = Some(&element.span);
+
+        if element.r#abstract {
+            report(
+                &mut self.diagnostics,
+                "cvc-elt.2",
+                start.position.span(),
+                component,
+                format!("Element {} is abstract", name),
+            );
+        }
+
+        let declared = validator.element_type(declaration, 0);
+        let ty = match xsi_attribute(start, "type") {
+            Some(xsi_type) => self.xsi_type(start, xsi_type, declared, declaration),
+            None => declared,
+        };
+
+        if let Some((nil, position)) = xsi_attribute(start, "nil") {
+            if !element.nillable {
+                report(
+                    &mut self.diagnostics,
+                    "cvc-elt.3.1",
+                    position.span(),
+                    component,
+                    format!(
+                        "Element {} is not nillable, so it cannot have xsi:nil",
+                        name
+                    ),
+                );
+            } else if nil.trim() == "true" || nil.trim() == "1" {
+                if let Some(DefaultFixed::Fixed(_)) = element.default_fixed {
+                    report(
+                        &mut self.diagnostics,
+                        "cvc-elt.3.2.2",
+                        start.position.span(),
+                        component,
+                        format!("Element {} has a fixed value, so it cannot be nil", name),
+                    );
+                }
+
+                if let Type::Complex(complex_type, document) = ty {
+                    self.attributes(start, complex_type, document);
+                }
+
+                return State::Nil {
+                    declaration,
+                    content: false,
+                };
+            }
+        }
+
+        match ty {
+            Type::Any => State::Lax,
+            Type::Complex(complex_type, document) => {
+                self.attributes(start, complex_type, document);
+
+                if complex_type.simple_content.is_some() {
+                    let (ty, restrictions) = validator.simple_content(complex_type, document, 0);
+
+                    return State::Simple {
+                        declaration,
+                        ty,
+                        restrictions,
+                        complex_type: Some(complex_type),
+                        text: String::new(),
+                        invalid: false,
+                    };
+                }
+
+                State::Complex {
+                    complex_type,
+                    model: validator
+                        .content_model(complex_type)
+                        .map(|model| (model.matcher(), model.particle())),
+                    failed: false,
+                    text_reported: false,
+                }
+            }
+            simple => {
+                for (attribute, _, position) in start
+                    .attributes
+                    .iter()
+                    .filter(|(a, _, _)| a.namespace.as_deref() != Some(XSI_NAMESPACE))
+                {
+                    report(
+                        &mut self.diagnostics,
+                        "cvc-type.3.1.1",
+                        position.span(),
+                        component,
+                        format!(
+                            "Element {} has a simple type, so it cannot have attribute {}",
+                            name, attribute
+                        ),
+                    );
+                }
+
+                State::Simple {
+                    declaration,
+                    ty: simple,
+                    restrictions: Vec::new(),
+                    complex_type: None,
+                    text: String::new(),
+                    invalid: false,
+                }
+            }
+        }
+    }
+
+    /// The type named by `xsi:type`, which has to be derived from the
+    /// declared type by methods that neither the element nor that type
+    /// block. Otherwise the declared type is used.
+    fn xsi_type(
+        &mut self,
+        start: &Start<P>,
+        (value, position): (&str, &P),
+        declared: Type<'a>,
+        declaration: Declaration<'a>,
+    ) -> Type<'a> {
+        let validator = self.validator;
+        let element = &start.name;
+        let component = Some(&declaration.element.span);
+        let ty = self
+            .instance_qname(&start.namespaces, value)
+            .and_then(|name| validator.lookup(&name));
+
+        let (rule, message) = match ty {
+            Some(ty) if validator.derives(ty, declared, &blocked(declaration, declared)) => {
+                return ty
+            }
+            Some(ty) if validator.derives(ty, declared, &BlockDefault::default()) => (
+                "cvc-elt.4.3",
+                format!(
+                    "Type {} is derived from {}, the type of element {}, by a blocked method",
+                    ty, declared, element
+                ),
+            ),
+            Some(ty) => (
+                "cvc-elt.4.3",
+                format!(
+                    "Type {} is not derived from {}, the type of element {}",
+                    ty, declared, element
+                ),
+            ),
+            None => (
+                "cvc-elt.4.2",
+                format!("Cannot resolve xsi:type {} to a type definition", value),
+            ),
+        };
+
+        report(
+            &mut self.diagnostics,
+            rule,
+            position.span(),
+            component,
+            message,
+        );
+        declared
+    }
+
+    fn attributes(
+        &mut self,
+        start: &Start<P>,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+    ) {
+        let validator = self.validator;
+        let name = &start.name;
+        let mut uses = Vec::new();
+        let mut id_attribute = None;
+        let wildcard = validator.attribute_uses(complex_type, document, &mut uses, 0);
+
+        for (attribute_name, value, position) in &start.attributes {
+            if attribute_name.namespace.as_deref() == Some(XSI_NAMESPACE) {
+                continue;
+            }
+
+            let used = uses
+                .iter()
+                .find(|u| u.name == *attribute_name)
+                .filter(|u| u.attribute.usage != Usage::Prohibited);
+
+            let wildcard_use;
+            let (used, matched) = match used {
+                Some(used) => (used, false),
+                None => match self.wildcard_attribute(
+                    name,
+                    (attribute_name, position),
+                    wildcard.as_ref(),
+                    complex_type,
+                ) {
+                    Some(used) => {
+                        wildcard_use = used;
+                        (&wildcard_use, true)
+                    }
+                    None => continue,
+                },
+            };
+
+            let component = Some(&used.attribute.span);
+            let ty = match validator.attribute_type(used) {
+                Some(ty) => ty,
+                None => continue,
+            };
+
+            let typed = validator
+                .simple_type(ty)
+                .validate_in(value, &|v| self.instance_qname(&start.namespaces, v));
+
+            if let Ok(ref typed) = typed {
+                self.attribute_values
+                    .push((attribute_name.clone(), typed.clone()));
+
+                let is_id = self.ids(ty, typed, position, component) == Some(IdKind::Id);
+
+                // Two declared uses of type ID are a schema error, reported
+                // by the constraint checker; only a wildcard adds one here.
+                if let Some((first, first_matched)) = id_attribute.filter(|_| is_id) {
+                    let rule = match (first_matched, matched) {
+                        (false, false) => None,
+                        (true, true) => Some("cvc-complex-type.5.1"),
+                        _ => Some("cvc-complex-type.5.2"),
+                    };
+
+                    if let Some(rule) = rule {
+                        report(
+                            &mut self.diagnostics,
+                            rule,
+                            position.span(),
+                            component,
+                            format!(
+                                "Element {} has both {} and {} of type ID, but may only have one",
+                                name, first, attribute_name
+                            ),
+                        );
+                    }
+                } else if is_id {
+                    id_attribute = Some((attribute_name, matched));
+                }
+            }
+
+            match (typed, used.default_fixed()) {
+                (Err(FacetViolation { rule, message }), _) => report(
+                    &mut self.diagnostics,
+                    rule,
+                    position.span(),
+                    component,
+                    format!(
+                        "{}, in attribute {} of element {}",
+                        message, attribute_name, name
+                    ),
+                ),
+                (Ok(typed), Some((DefaultFixed::Fixed(fixed), fixed_document)))
+                    if !validator.is_fixed(ty, (&typed, value), fixed, fixed_document) =>
+                {
+                    report(
+                        &mut self.diagnostics,
+                        "cvc-attribute.4",
+                        position.span(),
+                        component,
+                        format!(
+                            "The value {:?} of attribute {} is not its fixed value {:?}",
+                            value, attribute_name, fixed
+                        ),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        for used in uses.iter().filter(|u| u.attribute.usage == Usage::Required) {
+            if !start.attributes.iter().any(|(a, _, _)| *a == used.name) {
+                report(
+                    &mut self.diagnostics,
+                    "cvc-complex-type.4",
+                    start.position.span(),
+                    Some(&used.attribute.span),
+                    format!("Attribute {} must appear on element {}", used.name, name),
+                );
+            }
+        }
+    }
+
+    /// The global declaration an attribute without a use of its own is
+    /// validated against, if the wildcard of the type allows it and does
+    /// not skip it.
+    fn wildcard_attribute(
+        &mut self,
+        element: &QName,
+        (name, position): (&QName, &P),
+        wildcard: Option<&AttributeWildcard<'a>>,
+        complex_type: &'a ComplexType,
+    ) -> Option<AttributeUse<'a>> {
+        let wildcard = match wildcard.filter(|w| w.allows(name.namespace.as_deref())) {
+            Some(wildcard) => wildcard,
+            None => {
+                report(
+                    &mut self.diagnostics,
+                    "cvc-complex-type.3.2.2",
+                    position.span(),
+                    Some(&complex_type.span),
+                    format!("Attribute {} is not allowed on element {}", name, element),
+                );
+                return None;
+            }
+        };
+
+        let any_attribute = wildcard.any_attribute;
+        let global = match any_attribute.process_contents {
+            ProcessContents::Skip => return None,
+            ProcessContents::Lax => self.validator.schema_set.attribute(name)?,
+            ProcessContents::Strict => match self.validator.schema_set.attribute(name) {
+                Some(global) => global,
+                None => {
+                    report(
+                        &mut self.diagnostics,
+                        "cvc-complex-type.3.2.2",
+                        position.span(),
+                        Some(&any_attribute.span),
+                        format!(
+                            "The matching attribute wildcard is strict, but attribute {} is not declared",
+                            name
+                        ),
+                    );
+                    return None;
+                }
+            },
+        };
+
+        Some(AttributeUse {
+            name: name.clone(),
+            attribute: global.component,
+            document: global.document,
+            declaration: Some((global.component, global.document)),
+        })
+    }
+
+    /// Checks the value of an element of simple type or with simple
+    /// content, which takes on the default when the element is empty.
+    fn element_value(
+        &mut self,
+        frame: &Frame<'v, 'a, P>,
+        ty: Type<'a>,
+        restrictions: &[&'a Restriction],
+        declaration: Declaration<'a>,
+        text: &str,
+    ) -> Option<TypedValue> {
+        let validator = self.validator;
+        let (name, position) = (&frame.name, &frame.position);
+        let element = declaration.element;
+        let value = element_text(element, text);
+
+        let typed = validator
+            .simple_type(ty)
+            .validate_restricted(value, restrictions, &|v| {
+                self.instance_qname(&frame.namespaces, v)
+            });
+
+        let typed = match typed {
+            Ok(typed) => typed,
+            Err(FacetViolation { rule, message }) => {
+                report(
+                    &mut self.diagnostics,
+                    rule,
+                    position.span(),
+                    Some(&element.span),
+                    format!("{}, in element {}", message, name),
+                );
+                return None;
+            }
+        };
+        self.ids(ty, &typed, position, Some(&element.span));
+
+        if let Some(DefaultFixed::Fixed(ref fixed)) = element.default_fixed {
+            if !validator.is_fixed(ty, (&typed, value), fixed, declaration.document) {
+                report(
+                    &mut self.diagnostics,
+                    "cvc-elt.5.2.2.2.2",
+                    position.span(),
+                    Some(&element.span),
+                    format!(
+                        "The value {:?} of element {} is not its fixed value {:?}",
+                        value, name, fixed
+                    ),
+                );
+            }
+        }
+
+        Some(typed)
+    }
+
+    /// Records the IDs a valid value declares or refers to, returning which
+    /// it does. An ID may only be declared once in a document.
+    fn ids(
+        &mut self,
+        ty: Type<'a>,
+        typed: &TypedValue,
+        position: &P,
+        component: Option<&Span>,
+    ) -> Option<IdKind> {
+        let kind = self.validator.id_kind(ty)?;
+        let values = match typed {
+            TypedValue::List(items) => &items[..],
+            value => std::slice::from_ref(value),
+        };
+
+        for value in values {
+            let value = match value {
+                TypedValue::String(value) => value,
+                _ => continue,
+            };
+
+            match kind {
+                IdKind::Id => match self.ids.entry(value.clone()) {
+                    Entry::Occupied(first) => {
+                        let first = first.get();
+                        let message = format!(
+                            "ID {:?} is already declared at line {}, column {}",
+                            value, first.line, first.column
+                        );
+                        report(
+                            &mut self.diagnostics,
+                            "cvc-id.2",
+                            position.span(),
+                            component,
+                            message,
+                        );
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(position.span());
+                    }
+                },
+                IdKind::Idref if !self.ids.contains_key(value) => {
+                    self.idrefs.push((value.clone(), position.span()))
+                }
+                IdKind::Idref => {}
+            }
+        }
+
+        Some(kind)
+    }
+
+    /// Resolves a QName in an instance against `namespaces`, those declared
+    /// on the innermost element, and the namespaces in scope around it. A
+    /// prefix bound to the empty URI is undeclared.
+    fn instance_qname(
+        &self,
+        namespaces: &[(Option<String>, String)],
+        value: &str,
+    ) -> Option<QName> {
+        let value = value.trim();
+        let (prefix, local) = match value.find(':') {
+            Some(idx) => (Some(&value[..idx]), &value[idx + 1..]),
+            None => (None, value),
+        };
+
+        let namespace = std::iter::once(namespaces)
+            .chain(self.stack.iter().rev().map(|frame| &frame.namespaces[..]))
+            .flatten()
+            .find(|(p, _)| p.as_deref() == prefix)
+            .map(|(_, uri)| uri.as_str())
+            .filter(|uri| !uri.is_empty());
+
+        match (prefix, namespace) {
+            (Some(_), None) => None,
+            (_, namespace) => Some(QName::new(namespace, local)),
+        }
+    }
+}
+
+impl<'s, 'a> Validator<'s, 'a> {
+    /// Validates a document read from `reader` with a streaming parser,
+    /// without building a tree. Spans have lines and columns, but no byte
+    /// offsets.
+    pub fn validate_reader<R: Read>(
+        &self,
+        reader: R,
+        location: Option<&str>,
+    ) -> Result<Diagnostics, Error> {
+        let location = location.unwrap_or_default();
+        let mut parser = ParserConfig::new()
+            .whitespace_to_characters(true)
+            .cdata_to_characters(true)
+            .create_reader(reader);
+        let mut stream = StreamValidator::new(self);
+        let mut scopes: Vec<Namespace> = Vec::new();
+
+        loop {
+            let event = parser.next();
+            let position = parser.position();
+            let position = LinePosition {
+                location,
+                line: position.row as u32 + 1,
+                column: position.column as u32 + 1,
+            };
+
+            match event {
+                Ok(XmlEvent::StartElement {
+                    name,
+                    attributes,
+                    namespace,
+                }) => {
+                    let element_namespace = name.namespace.as_deref().filter(|n| !n.is_empty());
+                    stream.start_element(QName::new(element_namespace, &name.local_name), position);
+
+                    // The reader reports every namespace in scope, so only
+                    // those that differ from the parent's were declared here.
+                    let inherited = scopes.last();
+
+                    for (prefix, uri) in &namespace {
+                        if inherited.and_then(|scope| scope.get(prefix)) != Some(uri) {
+                            let prefix = Some(prefix).filter(|p| !p.is_empty());
+                            stream.namespace(prefix, uri);
+                        }
+                    }
+
+                    scopes.push(namespace);
+
+                    for attribute in attributes {
+                        let name = &attribute.name;
+                        stream.attribute(
+                            QName::new(name.namespace.as_deref(), &name.local_name),
+                            &attribute.value,
+                            position,
+                        );
+                    }
+                }
+                Ok(XmlEvent::Characters(text)) => stream.text(&text, position),
+                Ok(XmlEvent::EndElement { .. }) => {
+                    scopes.pop();
+                    stream.end_element();
+                }
+                Ok(XmlEvent::EndDocument) => break,
+                Ok(_) => {}
+                Err(error) => {
+                    return Err(Error::ReadXml {
+                        span: position.span(),
+                        error: Box::new(error),
+                    })
+                }
+            }
+        }
+
+        Ok(stream.finish())
+    }
+}
+
+pub(crate) fn report(
+    diagnostics: &mut Diagnostics,
+    rule: &'static str,
+    span: Span,
+    component: Option<&Span>,
+    message: String,
+) {
+    diagnostics.push(Error::Invalid {
+        rule,
+        message,
+        component: component.map(|span| Box::new(span.clone())),
+        span,
+    });
+}
+
+/// The value of an element, which takes on its default when it is empty.
+fn element_text<'t>(element: &'t Element, text: &'t str) -> &'t str {
+    match element.default_fixed {
+        Some(ref default_fixed) if text.is_empty() => default_fixed.value(),
+        _ => text,
+    }
+}
+
+/// The derivations an element blocks for `xsi:type`: those in its own
+/// `block` and in that of its declared type, each defaulting to the
+/// `blockDefault` of its schema.
+fn blocked(declaration: Declaration, declared: Type) -> BlockDefault {
+    let element = declaration.element.block.as_ref().or(declaration
+        .document
+        .schema
+        .block_default
+        .as_ref());
+    let ty = match declared {
+        Type::Complex(complex_type, document) => complex_type
+            .block
+            .as_ref()
+            .or(document.schema.block_default.as_ref()),
+        _ => None,
+    };
+
+    let mut blocked = BlockDefault::default();
+
+    for block in element.into_iter().chain(ty) {
+        blocked.extension |= block.extension;
+        blocked.restriction |= block.restriction;
+    }
+
+    blocked
+}
+
+/// An attribute in the schema instance namespace, such as `xsi:nil`.
+fn xsi_attribute<'t, P>(start: &'t Start<P>, name: &str) -> Option<(&'t str, &'t P)> {
+    start
+        .attributes
+        .iter()
+        .find(|(a, _, _)| a.name == name && a.namespace.as_deref() == Some(XSI_NAMESPACE))
+        .map(|(_, value, position)| (value.as_str(), position))
+}
+
+/// The names of the elements and wildcards a content model expects, for
+/// messages.
+fn describe(expected: &[&Particle]) -> String {
+    let names: Vec<String> = expected
+        .iter()
+        .map(|particle| match particle.term {
+            Term::Element(_) => particle
+                .element_name()
+                .map_or_else(String::new, |name| name.to_string()),
+            _ => "any element".to_owned(),
+        })
+        .collect();
+
+    match names.len() {
+        0 => "nothing".to_owned(),
+        1 => names[0].clone(),
+        _ => format!("one of {}", names.join(", ")),
+    }
+}
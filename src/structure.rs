@@ -0,0 +1,698 @@
+//! Checks schema documents against the schema-for-schemas: which children
+//! each schema element may have and in what order, which attributes it may
+//! carry and their values, and the attributes and children that exclude
+//! each other.
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use crate::builtins::{is_ncname, is_qname};
+use crate::complex_type::Usage;
+use crate::errors::Error;
+use crate::shared::{BlockDefault, Final, FinalDefault, FormDefault, Occurrence};
+use crate::span::Span;
+use crate::traits::{Context, XSD_NAMESPACE};
+
+use roxmltree::Node;
+
+/// A content model over the local names of child elements.
+#[derive(Clone, Copy)]
+enum Model {
+    Name(&'static str),
+    Sequence(&'static [Model]),
+    Choice(&'static [Model]),
+    Optional(&'static Model),
+    Repeated(&'static Model),
+    OneOrMore(&'static Model),
+    /// Any content, which is not checked.
+    Any,
+}
+
+use self::Model::*;
+
+const ANNOTATION: Model = Optional(&Name("annotation"));
+const FACET: Model = Choice(&[
+    Name("minExclusive"),
+    Name("minInclusive"),
+    Name("maxExclusive"),
+    Name("maxInclusive"),
+    Name("totalDigits"),
+    Name("fractionDigits"),
+    Name("length"),
+    Name("minLength"),
+    Name("maxLength"),
+    Name("enumeration"),
+    Name("whiteSpace"),
+    Name("pattern"),
+]);
+const ATTRIBUTES: Model = Sequence(&[
+    Repeated(&Choice(&[Name("attribute"), Name("attributeGroup")])),
+    Optional(&Name("anyAttribute")),
+]);
+const PARTICLE: Model = Choice(&[Name("group"), Name("all"), Name("choice"), Name("sequence")]);
+const NESTED_PARTICLE: Model = Choice(&[
+    Name("element"),
+    Name("group"),
+    Name("choice"),
+    Name("sequence"),
+    Name("any"),
+]);
+
+const SCHEMA: Model = Sequence(&[
+    Repeated(&Choice(&[
+        Name("include"),
+        Name("import"),
+        Name("redefine"),
+        Name("annotation"),
+    ])),
+    Repeated(&Sequence(&[
+        Choice(&[
+            Name("simpleType"),
+            Name("complexType"),
+            Name("group"),
+            Name("attributeGroup"),
+            Name("element"),
+            Name("attribute"),
+            Name("notation"),
+        ]),
+        Repeated(&Name("annotation")),
+    ])),
+]);
+const ELEMENT: Model = Sequence(&[
+    ANNOTATION,
+    Optional(&Choice(&[Name("simpleType"), Name("complexType")])),
+    Repeated(&Choice(&[Name("unique"), Name("key"), Name("keyref")])),
+]);
+const COMPLEX_TYPE: Model = Sequence(&[
+    ANNOTATION,
+    Choice(&[
+        Name("simpleContent"),
+        Name("complexContent"),
+        Sequence(&[Optional(&PARTICLE), ATTRIBUTES]),
+    ]),
+]);
+const DERIVATION: Model = Sequence(&[
+    ANNOTATION,
+    Choice(&[Name("restriction"), Name("extension")]),
+]);
+const SIMPLE_RESTRICTION: Model =
+    Sequence(&[ANNOTATION, Optional(&Name("simpleType")), Repeated(&FACET)]);
+const SIMPLE_CONTENT_RESTRICTION: Model = Sequence(&[
+    ANNOTATION,
+    Optional(&Name("simpleType")),
+    Repeated(&FACET),
+    ATTRIBUTES,
+]);
+const SIMPLE_CONTENT_EXTENSION: Model = Sequence(&[ANNOTATION, ATTRIBUTES]);
+const COMPLEX_CONTENT_DERIVATION: Model = Sequence(&[ANNOTATION, Optional(&PARTICLE), ATTRIBUTES]);
+const GROUP_CONTENT: Model = Sequence(&[ANNOTATION, Repeated(&NESTED_PARTICLE)]);
+const ALL: Model = Sequence(&[ANNOTATION, Repeated(&Name("element"))]);
+const ATTRIBUTE: Model = Sequence(&[ANNOTATION, Optional(&Name("simpleType"))]);
+const ATTRIBUTE_GROUP: Model = Sequence(&[ANNOTATION, ATTRIBUTES]);
+const GROUP: Model = Sequence(&[
+    ANNOTATION,
+    Choice(&[Name("all"), Name("choice"), Name("sequence")]),
+]);
+const SIMPLE_TYPE: Model = Sequence(&[
+    ANNOTATION,
+    Choice(&[Name("restriction"), Name("list"), Name("union")]),
+]);
+const UNION: Model = Sequence(&[ANNOTATION, Repeated(&Name("simpleType"))]);
+const REDEFINE: Model = Repeated(&Choice(&[
+    Name("annotation"),
+    Name("simpleType"),
+    Name("complexType"),
+    Name("group"),
+    Name("attributeGroup"),
+]));
+const IDENTITY_CONSTRAINT: Model =
+    Sequence(&[ANNOTATION, Name("selector"), OneOrMore(&Name("field"))]);
+const ANNOTATION_CONTENT: Model = Repeated(&Choice(&[Name("appinfo"), Name("documentation")]));
+
+/// The type of an attribute value.
+#[derive(Clone, Copy)]
+enum Kind {
+    String,
+    Boolean,
+    NonNegativeInteger,
+    PositiveInteger,
+    NcName,
+    QName,
+    QNames,
+    MinOccurs,
+    MaxOccurs,
+    Final,
+    BlockDefault,
+    FinalDefault,
+    Form,
+    Use,
+    /// `#all` or a list of the given words.
+    Set(&'static [&'static str]),
+    /// One of the given words.
+    Enumeration(&'static [&'static str]),
+}
+
+struct Attribute {
+    name: &'static str,
+    kind: Kind,
+    required: bool,
+}
+
+const fn optional(name: &'static str, kind: Kind) -> Attribute {
+    Attribute {
+        name,
+        kind,
+        required: false,
+    }
+}
+
+const fn required(name: &'static str, kind: Kind) -> Attribute {
+    Attribute {
+        name,
+        kind,
+        required: true,
+    }
+}
+
+const ID: Attribute = optional("id", Kind::NcName);
+const MIN_OCCURS: Attribute = optional("minOccurs", Kind::MinOccurs);
+const MAX_OCCURS: Attribute = optional("maxOccurs", Kind::MaxOccurs);
+const COMPLEX_DERIVATIONS: Kind = Kind::Set(&["extension", "restriction"]);
+const SIMPLE_DERIVATIONS: Kind = Kind::Set(&["list", "union", "restriction"]);
+const PROCESS_CONTENTS: Kind = Kind::Enumeration(&["skip", "lax", "strict"]);
+
+const SCHEMA_ATTRIBUTES: &[Attribute] = &[
+    optional("attributeFormDefault", Kind::Form),
+    optional("blockDefault", Kind::BlockDefault),
+    optional("elementFormDefault", Kind::Form),
+    optional("finalDefault", Kind::FinalDefault),
+    ID,
+    optional("targetNamespace", Kind::String),
+    optional("version", Kind::String),
+];
+const TOP_LEVEL_ELEMENT_ATTRIBUTES: &[Attribute] = &[
+    required("name", Kind::NcName),
+    optional("type", Kind::QName),
+    optional("substitutionGroup", Kind::QNames),
+    optional("default", Kind::String),
+    optional("fixed", Kind::String),
+    optional("nillable", Kind::Boolean),
+    optional("abstract", Kind::Boolean),
+    optional("final", Kind::Final),
+    optional("block", Kind::BlockDefault),
+    ID,
+];
+const LOCAL_ELEMENT_ATTRIBUTES: &[Attribute] = &[
+    optional("name", Kind::NcName),
+    optional("ref", Kind::QName),
+    optional("type", Kind::QName),
+    MIN_OCCURS,
+    MAX_OCCURS,
+    optional("default", Kind::String),
+    optional("fixed", Kind::String),
+    optional("nillable", Kind::Boolean),
+    optional("block", Kind::BlockDefault),
+    optional("form", Kind::Form),
+    ID,
+];
+const TOP_LEVEL_ATTRIBUTE_ATTRIBUTES: &[Attribute] = &[
+    required("name", Kind::NcName),
+    optional("type", Kind::QName),
+    optional("default", Kind::String),
+    optional("fixed", Kind::String),
+    ID,
+];
+const LOCAL_ATTRIBUTE_ATTRIBUTES: &[Attribute] = &[
+    optional("name", Kind::NcName),
+    optional("ref", Kind::QName),
+    optional("type", Kind::QName),
+    optional("use", Kind::Use),
+    optional("default", Kind::String),
+    optional("fixed", Kind::String),
+    optional("form", Kind::Form),
+    ID,
+];
+const TOP_LEVEL_COMPLEX_TYPE_ATTRIBUTES: &[Attribute] = &[
+    required("name", Kind::NcName),
+    optional("abstract", Kind::Boolean),
+    optional("mixed", Kind::Boolean),
+    optional("block", COMPLEX_DERIVATIONS),
+    optional("final", COMPLEX_DERIVATIONS),
+    ID,
+];
+const LOCAL_COMPLEX_TYPE_ATTRIBUTES: &[Attribute] = &[optional("mixed", Kind::Boolean), ID];
+const COMPLEX_CONTENT_ATTRIBUTES: &[Attribute] = &[optional("mixed", Kind::Boolean), ID];
+const TOP_LEVEL_SIMPLE_TYPE_ATTRIBUTES: &[Attribute] = &[
+    required("name", Kind::NcName),
+    optional("final", SIMPLE_DERIVATIONS),
+    ID,
+];
+const BASE_ATTRIBUTES: &[Attribute] = &[required("base", Kind::QName), ID];
+const SIMPLE_RESTRICTION_ATTRIBUTES: &[Attribute] = &[optional("base", Kind::QName), ID];
+const PARTICLE_ATTRIBUTES: &[Attribute] = &[MIN_OCCURS, MAX_OCCURS, ID];
+const ANY_ATTRIBUTES: &[Attribute] = &[
+    optional("namespace", Kind::String),
+    optional("processContents", PROCESS_CONTENTS),
+    MIN_OCCURS,
+    MAX_OCCURS,
+    ID,
+];
+const ANY_ATTRIBUTE_ATTRIBUTES: &[Attribute] = &[
+    optional("namespace", Kind::String),
+    optional("processContents", PROCESS_CONTENTS),
+    ID,
+];
+const NAMED_ATTRIBUTES: &[Attribute] = &[required("name", Kind::NcName), ID];
+const GROUP_REFERENCE_ATTRIBUTES: &[Attribute] =
+    &[required("ref", Kind::QName), MIN_OCCURS, MAX_OCCURS, ID];
+const REFERENCE_ATTRIBUTES: &[Attribute] = &[required("ref", Kind::QName), ID];
+const LIST_ATTRIBUTES: &[Attribute] = &[optional("itemType", Kind::QName), ID];
+const UNION_ATTRIBUTES: &[Attribute] = &[optional("memberTypes", Kind::QNames), ID];
+const INCLUDE_ATTRIBUTES: &[Attribute] = &[required("schemaLocation", Kind::String), ID];
+const IMPORT_ATTRIBUTES: &[Attribute] = &[
+    optional("namespace", Kind::String),
+    optional("schemaLocation", Kind::String),
+    ID,
+];
+const NOTATION_ATTRIBUTES: &[Attribute] = &[
+    required("name", Kind::NcName),
+    optional("public", Kind::String),
+    optional("system", Kind::String),
+    ID,
+];
+const KEYREF_ATTRIBUTES: &[Attribute] = &[
+    required("name", Kind::NcName),
+    required("refer", Kind::QName),
+    ID,
+];
+const XPATH_ATTRIBUTES: &[Attribute] = &[required("xpath", Kind::String), ID];
+const FACET_ATTRIBUTES: &[Attribute] = &[
+    required("value", Kind::String),
+    optional("fixed", Kind::Boolean),
+    ID,
+];
+const LENGTH_FACET_ATTRIBUTES: &[Attribute] = &[
+    required("value", Kind::NonNegativeInteger),
+    optional("fixed", Kind::Boolean),
+    ID,
+];
+const TOTAL_DIGITS_ATTRIBUTES: &[Attribute] = &[
+    required("value", Kind::PositiveInteger),
+    optional("fixed", Kind::Boolean),
+    ID,
+];
+const WHITE_SPACE_ATTRIBUTES: &[Attribute] = &[
+    required(
+        "value",
+        Kind::Enumeration(&["preserve", "replace", "collapse"]),
+    ),
+    optional("fixed", Kind::Boolean),
+    ID,
+];
+const PATTERN_ATTRIBUTES: &[Attribute] = &[required("value", Kind::String), ID];
+const SOURCE_ATTRIBUTES: &[Attribute] = &[optional("source", Kind::String)];
+const ID_ATTRIBUTES: &[Attribute] = &[ID];
+
+/// Checks `root`, a schema element, and everything in it, reporting each
+/// violation through `context`.
+pub(crate) fn check(root: Node, context: &Context) -> Result<(), Error> {
+    check_element(root, context)
+}
+
+fn check_element(node: Node, context: &Context) -> Result<(), Error> {
+    let (content, attributes) = match rules(node) {
+        Some(rules) => rules,
+        // Elements the schema-for-schemas does not define are left to the
+        // parser, which reports them as unhandled.
+        None => return Ok(()),
+    };
+
+    check_attributes(node, attributes, context)?;
+    check_conflicts(node, context)?;
+
+    if let Any = content {
+        return Ok(());
+    }
+
+    let children: Vec<Node> = node
+        .children()
+        .filter(|n| n.is_element() && n.tag_name().namespace() == Some(XSD_NAMESPACE))
+        .collect();
+
+    check_content(node, &children, content, context)?;
+
+    for child in children {
+        check_element(child, context)?;
+    }
+
+    Ok(())
+}
+
+/// The content model and attributes of a schema element, which depend on
+/// where it appears.
+fn rules(node: Node) -> Option<(Model, &'static [Attribute])> {
+    let parent = node.parent_element().map_or("", |p| p.tag_name().name());
+    let top_level = parent == "schema" || parent == "redefine";
+    let grandparent = node
+        .parent_element()
+        .and_then(|p| p.parent_element())
+        .map_or("", |p| p.tag_name().name());
+
+    let rules = match node.tag_name().name() {
+        "schema" => (SCHEMA, SCHEMA_ATTRIBUTES),
+        "element" if top_level => (ELEMENT, TOP_LEVEL_ELEMENT_ATTRIBUTES),
+        "element" => (ELEMENT, LOCAL_ELEMENT_ATTRIBUTES),
+        "attribute" if top_level => (ATTRIBUTE, TOP_LEVEL_ATTRIBUTE_ATTRIBUTES),
+        "attribute" => (ATTRIBUTE, LOCAL_ATTRIBUTE_ATTRIBUTES),
+        "complexType" if top_level => (COMPLEX_TYPE, TOP_LEVEL_COMPLEX_TYPE_ATTRIBUTES),
+        "complexType" => (COMPLEX_TYPE, LOCAL_COMPLEX_TYPE_ATTRIBUTES),
+        "simpleContent" => (DERIVATION, ID_ATTRIBUTES),
+        "complexContent" => (DERIVATION, COMPLEX_CONTENT_ATTRIBUTES),
+        "restriction" if parent == "simpleContent" => (SIMPLE_CONTENT_RESTRICTION, BASE_ATTRIBUTES),
+        "restriction" if parent == "complexContent" => {
+            (COMPLEX_CONTENT_DERIVATION, BASE_ATTRIBUTES)
+        }
+        "restriction" => (SIMPLE_RESTRICTION, SIMPLE_RESTRICTION_ATTRIBUTES),
+        "extension" if parent == "simpleContent" => (SIMPLE_CONTENT_EXTENSION, BASE_ATTRIBUTES),
+        "extension" => (COMPLEX_CONTENT_DERIVATION, BASE_ATTRIBUTES),
+        // The model group of a named group definition has no occurrences.
+        "sequence" | "choice" | "all"
+            if parent == "group" && (grandparent == "schema" || grandparent == "redefine") =>
+        {
+            let content = if node.tag_name().name() == "all" {
+                ALL
+            } else {
+                GROUP_CONTENT
+            };
+            (content, ID_ATTRIBUTES)
+        }
+        "sequence" | "choice" => (GROUP_CONTENT, PARTICLE_ATTRIBUTES),
+        "all" => (ALL, PARTICLE_ATTRIBUTES),
+        "any" => (ANNOTATION, ANY_ATTRIBUTES),
+        "anyAttribute" => (ANNOTATION, ANY_ATTRIBUTE_ATTRIBUTES),
+        "group" if top_level => (GROUP, NAMED_ATTRIBUTES),
+        "group" => (ANNOTATION, GROUP_REFERENCE_ATTRIBUTES),
+        "attributeGroup" if top_level => (ATTRIBUTE_GROUP, NAMED_ATTRIBUTES),
+        "attributeGroup" => (ANNOTATION, REFERENCE_ATTRIBUTES),
+        "simpleType" if top_level => (SIMPLE_TYPE, TOP_LEVEL_SIMPLE_TYPE_ATTRIBUTES),
+        "simpleType" => (SIMPLE_TYPE, ID_ATTRIBUTES),
+        "list" => (ATTRIBUTE, LIST_ATTRIBUTES),
+        "union" => (UNION, UNION_ATTRIBUTES),
+        "include" => (ANNOTATION, INCLUDE_ATTRIBUTES),
+        "import" => (ANNOTATION, IMPORT_ATTRIBUTES),
+        "redefine" => (REDEFINE, INCLUDE_ATTRIBUTES),
+        "notation" => (ANNOTATION, NOTATION_ATTRIBUTES),
+        "unique" | "key" => (IDENTITY_CONSTRAINT, NAMED_ATTRIBUTES),
+        "keyref" => (IDENTITY_CONSTRAINT, KEYREF_ATTRIBUTES),
+        "selector" | "field" => (ANNOTATION, XPATH_ATTRIBUTES),
+        "length" | "minLength" | "maxLength" | "fractionDigits" => {
+            (ANNOTATION, LENGTH_FACET_ATTRIBUTES)
+        }
+        "totalDigits" => (ANNOTATION, TOTAL_DIGITS_ATTRIBUTES),
+        "whiteSpace" => (ANNOTATION, WHITE_SPACE_ATTRIBUTES),
+        "pattern" | "enumeration" => (ANNOTATION, PATTERN_ATTRIBUTES),
+        "minExclusive" | "minInclusive" | "maxExclusive" | "maxInclusive" => {
+            (ANNOTATION, FACET_ATTRIBUTES)
+        }
+        "annotation" => (ANNOTATION_CONTENT, ID_ATTRIBUTES),
+        "appinfo" | "documentation" => (Any, SOURCE_ATTRIBUTES),
+        _ => return None,
+    };
+
+    Some(rules)
+}
+
+fn check_attributes(node: Node, attributes: &[Attribute], context: &Context) -> Result<(), Error> {
+    let element = node.tag_name().name();
+
+    // Namespaced attributes are foreign ones, which the options govern.
+    for attribute in node.attributes().iter().filter(|a| a.namespace().is_none()) {
+        let span = Span::attribute(context.document, context.source, node, attribute.name());
+
+        match attributes.iter().find(|a| a.name == attribute.name()) {
+            Some(expected) => {
+                if let Err(error) = check_value(attribute.name(), attribute.value(), expected.kind)
+                {
+                    context.report(error.with_span(span))?;
+                }
+            }
+            None => context.report(Error::UnexpectedAttribute {
+                element: element.to_owned(),
+                attribute: attribute.name().to_owned(),
+                span,
+            })?,
+        }
+    }
+
+    for attribute in attributes.iter().filter(|a| a.required) {
+        if node.attribute(attribute.name).is_none() {
+            context.report(Error::MissingAttribute {
+                element: element.to_owned(),
+                attribute: attribute.name.to_owned(),
+                span: context.span(node),
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks a value, returning an error without a span. Values the model
+/// parses are checked by the same code, so that lenient parsing reports
+/// them once.
+fn check_value(attribute: &str, value: &str, kind: Kind) -> Result<(), Error> {
+    let valid = match kind {
+        Kind::String => true,
+        Kind::Boolean => ["true", "false", "1", "0"].contains(&value),
+        Kind::NonNegativeInteger => is_integer(value, 0),
+        Kind::PositiveInteger => is_integer(value, 1),
+        Kind::NcName => is_ncname(value),
+        Kind::QName => is_qname(value),
+        Kind::QNames => value.split_whitespace().all(is_qname),
+        Kind::MinOccurs if value == "unbounded" => false,
+        Kind::MinOccurs | Kind::MaxOccurs => return Occurrence::from_str(value).map(|_| ()),
+        Kind::Final => return Final::from_str(value).map(|_| ()),
+        Kind::BlockDefault => return BlockDefault::from_str(value).map(|_| ()),
+        Kind::FinalDefault => return FinalDefault::from_str(value).map(|_| ()),
+        Kind::Form => return FormDefault::from_str(value).map(|_| ()),
+        Kind::Use => return Usage::from_str(value).map(|_| ()),
+        Kind::Set(words) => {
+            value.trim() == "#all" || value.split_whitespace().all(|word| words.contains(&word))
+        }
+        Kind::Enumeration(words) => words.contains(&value.trim()),
+    };
+
+    if valid {
+        return Ok(());
+    }
+
+    if let Kind::MinOccurs = kind {
+        return Err(Error::InvalidOccurs {
+            value: value.to_owned(),
+            span: None,
+        });
+    }
+
+    Err(Error::InvalidAttributeValue {
+        attribute: attribute.to_owned(),
+        value: value.to_owned(),
+        span: None,
+    })
+}
+
+fn is_integer(value: &str, min: u64) -> bool {
+    let value = value.trim();
+    let digits = value.strip_prefix('+').unwrap_or(value);
+
+    !digits.is_empty()
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && (min == 0 || digits.chars().any(|c| c != '0'))
+}
+
+/// The name characters of XML 1.0, fifth edition, without the colon.
+/// Attributes and children that exclude each other, and the choice between
+/// a name and a reference.
+fn check_conflicts(node: Node, context: &Context) -> Result<(), Error> {
+    let has = |name: &str| node.attribute(name).is_some();
+    let has_child = |name: &str| {
+        node.children()
+            .any(|n| n.tag_name().namespace() == Some(XSD_NAMESPACE) && n.tag_name().name() == name)
+    };
+    let conflict = |first: &'static str, second: &'static str| {
+        context.report(Error::ConflictingContent {
+            element: node.tag_name().name().to_owned(),
+            first,
+            second,
+            span: context.span(node),
+        })
+    };
+    let missing = |attribute: &str| {
+        context.report(Error::MissingAttribute {
+            element: node.tag_name().name().to_owned(),
+            attribute: attribute.to_owned(),
+            span: context.span(node),
+        })
+    };
+
+    let parent = node.parent_element().map_or("", |p| p.tag_name().name());
+    let top_level = parent == "schema" || parent == "redefine";
+
+    match node.tag_name().name() {
+        "element" | "attribute" => {
+            if has("default") && has("fixed") {
+                conflict("default", "fixed")?;
+            }
+
+            if !top_level {
+                if has("name") && has("ref") {
+                    conflict("name", "ref")?;
+                } else if !has("name") && !has("ref") {
+                    missing("name")?;
+                }
+            }
+
+            let inline_types: &[&str] = if node.tag_name().name() == "element" {
+                &["simpleType", "complexType"]
+            } else {
+                &["simpleType"]
+            };
+
+            for inline_type in inline_types.iter().filter(|t| has_child(t)) {
+                if has("type") {
+                    conflict("type", inline_type)?;
+                }
+
+                if has("ref") {
+                    conflict("ref", inline_type)?;
+                }
+            }
+
+            if has("ref") {
+                for attribute in ["type", "nillable", "default", "fixed", "form", "block"] {
+                    if has(attribute) {
+                        conflict("ref", attribute)?;
+                    }
+                }
+            }
+
+            if node.tag_name().name() == "attribute"
+                && has("default")
+                && node.attribute("use").is_some_and(|u| u != "optional")
+            {
+                conflict("default", "use")?;
+            }
+        }
+        "restriction" if parent == "simpleType" => {
+            if has("base") && has_child("simpleType") {
+                conflict("base", "simpleType")?;
+            } else if !has("base") && !has_child("simpleType") {
+                missing("base")?;
+            }
+        }
+        "list" => {
+            if has("itemType") && has_child("simpleType") {
+                conflict("itemType", "simpleType")?;
+            } else if !has("itemType") && !has_child("simpleType") {
+                missing("itemType")?;
+            }
+        }
+        "union" if !has("memberTypes") && !has_child("simpleType") => {
+            missing("memberTypes")?;
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn check_content(
+    node: Node,
+    children: &[Node],
+    content: Model,
+    context: &Context,
+) -> Result<(), Error> {
+    let names: Vec<&str> = children.iter().map(|c| c.tag_name().name()).collect();
+
+    let mut furthest = 0;
+    let ends = step(content, &names, &BTreeSet::from([0]), &mut furthest);
+
+    if ends.contains(&names.len()) {
+        return Ok(());
+    }
+
+    // The first child that no way through the content model accepts.
+    let error = match children.get(furthest) {
+        Some(child) => Error::UnexpectedElement {
+            parent: node.tag_name().name().to_owned(),
+            element: child.tag_name().name().to_owned(),
+            span: context.span(*child),
+        },
+        None => Error::MissingChild {
+            element: node.tag_name().name().to_owned(),
+            span: context.span(node),
+        },
+    };
+
+    context.report(error)
+}
+
+/// The positions in `names` that matching `model` can end at, starting from
+/// any of `from`. `furthest` is raised to the furthest position reached.
+fn step(
+    model: Model,
+    names: &[&str],
+    from: &BTreeSet<usize>,
+    furthest: &mut usize,
+) -> BTreeSet<usize> {
+    let ends = match model {
+        Name(name) => from
+            .iter()
+            .filter(|&&pos| names.get(pos) == Some(&name))
+            .map(|pos| pos + 1)
+            .collect(),
+        Sequence(models) => models.iter().fold(from.clone(), |positions, &m| {
+            step(m, names, &positions, furthest)
+        }),
+        Choice(models) => models
+            .iter()
+            .flat_map(|&m| step(m, names, from, furthest))
+            .collect(),
+        Optional(&m) => {
+            let mut ends = step(m, names, from, furthest);
+            ends.extend(from);
+            ends
+        }
+        Repeated(&m) => repeat(m, names, from.clone(), furthest),
+        OneOrMore(&m) => {
+            let first = step(m, names, from, furthest);
+            repeat(m, names, first, furthest)
+        }
+        Any => from.iter().map(|_| names.len()).collect(),
+    };
+
+    if let Some(&max) = ends.iter().next_back() {
+        *furthest = (*furthest).max(max);
+    }
+
+    ends
+}
+
+fn repeat(
+    model: Model,
+    names: &[&str],
+    from: BTreeSet<usize>,
+    furthest: &mut usize,
+) -> BTreeSet<usize> {
+    let mut ends = from.clone();
+    let mut frontier = from;
+
+    while !frontier.is_empty() {
+        frontier = step(model, names, &frontier, furthest)
+            .difference(&ends)
+            .cloned()
+            .collect();
+        ends.extend(&frontier);
+    }
+
+    ends
+}
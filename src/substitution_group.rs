@@ -0,0 +1,106 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::element::Element;
+use crate::schema_set::{Global, SchemaSet};
+use crate::shared::QName;
+
+/// Substitution group membership of the global elements in a `SchemaSet`.
+#[derive(Debug)]
+pub struct SubstitutionGroups<'s, 'a> {
+    schema_set: &'s SchemaSet<'a>,
+    members: HashMap<QName, Vec<QName>>,
+}
+
+impl<'s, 'a> SubstitutionGroups<'s, 'a> {
+    pub fn new(schema_set: &'s SchemaSet<'a>) -> Self {
+        let mut members: HashMap<QName, Vec<QName>> = HashMap::new();
+
+        for element in schema_set.elements() {
+            for head in &element.component.substitution_group {
+                if let Some(head) = element.resolve(head) {
+                    members.entry(head).or_default().push(element.name.clone());
+                }
+            }
+        }
+
+        SubstitutionGroups {
+            schema_set,
+            members,
+        }
+    }
+
+    /// Returns the direct and transitive members of `head`'s substitution
+    /// group, abstract ones included, in breadth-first order.
+    pub fn members(&self, head: &QName) -> Vec<&'s Global<'a, Element>> {
+        let mut members = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+
+        seen.insert(head);
+        queue.push_back(head);
+
+        while let Some(name) = queue.pop_front() {
+            for member in self.members.get(name).into_iter().flatten() {
+                if !seen.insert(member) {
+                    continue;
+                }
+
+                if let Some(element) = self.schema_set.element(member) {
+                    members.push(element);
+                }
+                queue.push_back(member);
+            }
+        }
+
+        members
+    }
+
+    /// Returns the elements that may appear at a particle that references
+    /// `head`: the head itself and its members, leaving out abstract
+    /// elements, and only the head if it blocks substitution.
+    pub fn allowed(&self, head: &QName) -> Vec<&'s Global<'a, Element>> {
+        let element = match self.schema_set.element(head) {
+            Some(element) => element,
+            None => return Vec::new(),
+        };
+
+        let mut allowed = Vec::new();
+
+        if !element.component.r#abstract {
+            allowed.push(element);
+        }
+
+        if !blocks_substitution(element) {
+            allowed.extend(
+                self.members(head)
+                    .into_iter()
+                    .filter(|member| !member.component.r#abstract),
+            );
+        }
+
+        allowed
+    }
+
+    /// Like `allowed`, for a particle declared with `ref` inside `context`.
+    /// Particles without a resolvable `ref` allow no global elements.
+    pub fn allowed_at<T>(
+        &self,
+        particle: &Element,
+        context: &Global<T>,
+    ) -> Vec<&'s Global<'a, Element>> {
+        match particle.r#ref.as_ref().and_then(|r| context.resolve(r)) {
+            Some(head) => self.allowed(&head),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn blocks_substitution(element: &Global<Element>) -> bool {
+    let block = element
+        .component
+        .block
+        .as_ref()
+        .or(element.document.schema.block_default.as_ref());
+
+    block.is_some_and(|block| block.substitution)
+}
@@ -0,0 +1,462 @@
+//! Values of the date, time and duration types. Dates and times are kept
+//! as their fields and timezone, and compared on a timeline where values
+//! without a timezone may fall anywhere within fourteen hours of UTC, so
+//! some pairs of values are not ordered. Fractions of a second are kept to
+//! the nanosecond.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::builtins;
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+const NANOS_PER_DAY: i128 = 86_400 * NANOS_PER_SECOND;
+
+/// The furthest a timezone may be from UTC.
+const MAX_OFFSET: i128 = 14 * 3_600 * NANOS_PER_SECOND;
+
+const PRIMITIVES: &[&str] = &[
+    "dateTime",
+    "time",
+    "date",
+    "gYearMonth",
+    "gYear",
+    "gMonthDay",
+    "gDay",
+    "gMonth",
+];
+
+/// A value of `dateTime`, `time`, `date` or one of the Gregorian types,
+/// with the fields its primitive type has.
+#[derive(Clone, Debug)]
+pub struct DateTime {
+    pub primitive: &'static str,
+    pub year: Option<i64>,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    pub hour: Option<u8>,
+    pub minute: Option<u8>,
+    pub second: Option<u8>,
+    pub nanosecond: u32,
+    /// The offset from UTC in minutes.
+    pub timezone: Option<i16>,
+}
+
+impl DateTime {
+    /// Parses a whitespace-collapsed value of the primitive type, `None`
+    /// when it is not one. A time of `24:00:00` is the start of the next
+    /// day.
+    pub fn parse(primitive: &str, value: &str) -> Option<Self> {
+        let primitive = *PRIMITIVES.iter().find(|&&p| p == primitive)?;
+
+        if !builtins::is_valid(primitive, value) {
+            return None;
+        }
+
+        let (fields, timezone) = builtins::strip_timezone(value);
+        let mut date_time = DateTime {
+            primitive,
+            year: None,
+            month: None,
+            day: None,
+            hour: None,
+            minute: None,
+            second: None,
+            nanosecond: 0,
+            timezone: match timezone {
+                "" => None,
+                "Z" => Some(0),
+                offset => {
+                    let minutes: i16 =
+                        offset[1..3].parse::<i16>().ok()? * 60 + offset[4..].parse::<i16>().ok()?;
+                    Some(if offset.starts_with('-') {
+                        -minutes
+                    } else {
+                        minutes
+                    })
+                }
+            },
+        };
+
+        match primitive {
+            "dateTime" => {
+                let (date, time) = fields.split_at(fields.find('T')?);
+                date_time.date(date)?;
+                date_time.time(&time[1..])?;
+            }
+            "time" => date_time.time(fields)?,
+            "date" => date_time.date(fields)?,
+            "gYearMonth" => {
+                let (year, month) = fields.split_at(fields.rfind('-')?);
+                date_time.year = Some(year.parse().ok()?);
+                date_time.month = Some(month[1..].parse().ok()?);
+            }
+            "gYear" => date_time.year = Some(fields.parse().ok()?),
+            "gMonthDay" => {
+                date_time.month = Some(fields.get(2..4)?.parse().ok()?);
+                date_time.day = Some(fields.get(5..7)?.parse().ok()?);
+            }
+            "gDay" => date_time.day = Some(fields.get(3..5)?.parse().ok()?),
+            _ => date_time.month = Some(fields.get(2..4)?.parse().ok()?),
+        }
+
+        if date_time.hour == Some(24) {
+            date_time.hour = Some(0);
+
+            if let (Some(year), Some(month), Some(day)) =
+                (date_time.year, date_time.month, date_time.day)
+            {
+                let (year, month, day) = civil_from_days(days_from_civil(year, month, day) + 1);
+                date_time.year = Some(year);
+                date_time.month = Some(month);
+                date_time.day = Some(day);
+            }
+        }
+
+        Some(date_time)
+    }
+
+    fn date(&mut self, date: &str) -> Option<()> {
+        let mut parts = date.rsplitn(3, '-');
+        self.day = Some(parts.next()?.parse().ok()?);
+        self.month = Some(parts.next()?.parse().ok()?);
+        self.year = Some(parts.next()?.parse().ok()?);
+        Some(())
+    }
+
+    fn time(&mut self, time: &str) -> Option<()> {
+        self.hour = Some(time.get(0..2)?.parse().ok()?);
+        self.minute = Some(time.get(3..5)?.parse().ok()?);
+        self.second = Some(time.get(6..8)?.parse().ok()?);
+
+        if let Some(fraction) = time.get(9..) {
+            let digits: String = fraction
+                .chars()
+                .chain("000000000".chars())
+                .take(9)
+                .collect();
+            self.nanosecond = digits.parse().ok()?;
+        }
+
+        Some(())
+    }
+
+    /// Nanoseconds from the epoch to the value as a local time, with the
+    /// fields it does not have taken from the last moment of 1972 that
+    /// agrees with the others.
+    fn local(&self) -> i128 {
+        let year = self.year.unwrap_or(1972);
+        let month = self.month.unwrap_or(12);
+        let day = self
+            .day
+            .unwrap_or_else(|| days_in_month(year, month))
+            .min(days_in_month(year, month));
+        let seconds = i128::from(self.hour.unwrap_or(0)) * 3_600
+            + i128::from(self.minute.unwrap_or(0)) * 60
+            + i128::from(self.second.unwrap_or(0));
+
+        days_from_civil(year, month, day) * NANOS_PER_DAY
+            + seconds * NANOS_PER_SECOND
+            + i128::from(self.nanosecond)
+    }
+
+    /// The value on the UTC timeline, `None` without a timezone.
+    fn utc(&self) -> Option<i128> {
+        let offset = i128::from(self.timezone?) * 60 * NANOS_PER_SECOND;
+        Some(self.local() - offset)
+    }
+}
+
+impl PartialEq for DateTime {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl Eq for DateTime {}
+
+impl Hash for DateTime {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.primitive.hash(state);
+        self.utc().ok_or_else(|| self.local()).hash(state);
+    }
+}
+
+impl PartialOrd for DateTime {
+    /// Values of different primitive types are not ordered, nor are a value
+    /// with a timezone and one without that is within fourteen hours of it.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.primitive != other.primitive {
+            return None;
+        }
+
+        match (self.utc(), other.utc()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            (None, None) => Some(self.local().cmp(&other.local())),
+            (Some(a), None) => compare_floating(a, other.local()),
+            (None, Some(b)) => compare_floating(b, self.local()).map(Ordering::reverse),
+        }
+    }
+}
+
+/// Compares a UTC time with a local time whose timezone is not known.
+fn compare_floating(utc: i128, local: i128) -> Option<Ordering> {
+    if utc < local - MAX_OFFSET {
+        Some(Ordering::Less)
+    } else if utc > local + MAX_OFFSET {
+        Some(Ordering::Greater)
+    } else {
+        None
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.year {
+            Some(year) if year < 0 => write!(f, "-{:04}", -i128::from(year))?,
+            Some(year) => write!(f, "{:04}", year)?,
+            None if self.month.is_some() || self.day.is_some() => f.write_str("-")?,
+            None => {}
+        }
+
+        match (self.month, self.day) {
+            (Some(month), Some(day)) => write!(f, "-{:02}-{:02}", month, day)?,
+            (Some(month), None) => write!(f, "-{:02}", month)?,
+            (None, Some(day)) => write!(f, "--{:02}", day)?,
+            (None, None) => {}
+        }
+
+        if let (Some(hour), Some(minute), Some(second)) = (self.hour, self.minute, self.second) {
+            if self.year.is_some() {
+                f.write_str("T")?;
+            }
+
+            write!(f, "{:02}:{:02}:{:02}", hour, minute, second)?;
+
+            if self.nanosecond > 0 {
+                let fraction = format!("{:09}", self.nanosecond);
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+
+        match self.timezone {
+            Some(0) => f.write_str("Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// A value of `duration` or of one of its subtypes: a number of months and
+/// a number of nanoseconds, both with the sign of the duration.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Duration {
+    pub months: i64,
+    pub nanoseconds: i128,
+}
+
+impl Duration {
+    /// Parses a whitespace-collapsed duration, `None` when it is not one or
+    /// a component is too large to hold.
+    pub fn parse(value: &str) -> Option<Self> {
+        if !builtins::is_valid("duration", value) {
+            return None;
+        }
+
+        let negative = value.starts_with('-');
+        let rest = value.trim_start_matches('-').strip_prefix('P')?;
+        let (date, time) = match rest.find('T') {
+            Some(idx) => (&rest[..idx], &rest[idx + 1..]),
+            None => (rest, ""),
+        };
+
+        let mut months: i64 = 0;
+        let mut nanoseconds: i128 = 0;
+
+        for (part, is_time) in [(date, false), (time, true)] {
+            let mut part = part;
+
+            while !part.is_empty() {
+                let idx = part.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+                let (number, designator) = (&part[..idx], part.as_bytes()[idx]);
+                part = &part[idx + 1..];
+
+                let (added_months, added_nanoseconds) = match (is_time, designator) {
+                    (false, b'Y') => (number.parse::<i64>().ok()?.checked_mul(12)?, 0),
+                    (false, b'M') => (number.parse().ok()?, 0),
+                    (false, b'D') => (0, whole(number, NANOS_PER_DAY)?),
+                    (true, b'H') => (0, whole(number, 3_600 * NANOS_PER_SECOND)?),
+                    (true, b'M') => (0, whole(number, 60 * NANOS_PER_SECOND)?),
+                    (true, b'S') => {
+                        let (seconds, fraction) = match number.find('.') {
+                            Some(idx) => (&number[..idx], &number[idx + 1..]),
+                            None => (number, ""),
+                        };
+                        let fraction: String = fraction
+                            .chars()
+                            .chain("000000000".chars())
+                            .take(9)
+                            .collect();
+                        let seconds = if seconds.is_empty() { "0" } else { seconds };
+
+                        (
+                            0,
+                            whole(seconds, NANOS_PER_SECOND)?
+                                .checked_add(fraction.parse::<i128>().ok()?)?,
+                        )
+                    }
+                    _ => return None,
+                };
+
+                months = months.checked_add(added_months)?;
+                nanoseconds = nanoseconds.checked_add(added_nanoseconds)?;
+            }
+        }
+
+        if negative {
+            months = -months;
+            nanoseconds = -nanoseconds;
+        }
+
+        Some(Duration {
+            months,
+            nanoseconds,
+        })
+    }
+
+    /// The duration added to a date, as nanoseconds from the epoch.
+    fn after(&self, (year, month): (i64, u8)) -> i128 {
+        let months = i128::from(year) * 12 + i128::from(month) - 1 + i128::from(self.months);
+        let (year, month) = (months.div_euclid(12), months.rem_euclid(12) + 1);
+
+        // Years beyond i64 only arise from durations far longer than any
+        // reference date, so the order does not depend on the day.
+        let year = i64::try_from(year).unwrap_or(if year < 0 { i64::MIN } else { i64::MAX });
+        days_from_civil(year, month as u8, 1) * NANOS_PER_DAY + self.nanoseconds
+    }
+}
+
+impl PartialOrd for Duration {
+    /// Durations are ordered when adding them to each of four dates, chosen
+    /// for the lengths of their months, orders the results the same way.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.months == other.months {
+            return Some(self.nanoseconds.cmp(&other.nanoseconds));
+        }
+
+        let references = [(1696, 9), (1697, 2), (1903, 3), (1903, 7)];
+        let mut orders = references
+            .iter()
+            .map(|&date| self.after(date).cmp(&other.after(date)));
+        let first = orders.next()?;
+
+        if orders.all(|order| order == first) {
+            Some(first)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.months < 0 || self.nanoseconds < 0 {
+            f.write_str("-")?;
+        }
+
+        let months = self.months.unsigned_abs();
+        let nanoseconds = self.nanoseconds.unsigned_abs();
+        let (days, rest) = (
+            nanoseconds / NANOS_PER_DAY as u128,
+            nanoseconds % NANOS_PER_DAY as u128,
+        );
+        let seconds = rest / NANOS_PER_SECOND as u128;
+        let fraction = rest % NANOS_PER_SECOND as u128;
+
+        f.write_str("P")?;
+        if months >= 12 {
+            write!(f, "{}Y", months / 12)?;
+        }
+        if !months.is_multiple_of(12) {
+            write!(f, "{}M", months % 12)?;
+        }
+        if days > 0 {
+            write!(f, "{}D", days)?;
+        }
+
+        if rest > 0 {
+            f.write_str("T")?;
+            if seconds >= 3_600 {
+                write!(f, "{}H", seconds / 3_600)?;
+            }
+            if seconds % 3_600 >= 60 {
+                write!(f, "{}M", seconds % 3_600 / 60)?;
+            }
+            if !seconds.is_multiple_of(60) || fraction > 0 {
+                write!(f, "{}", seconds % 60)?;
+                if fraction > 0 {
+                    let fraction = format!("{:09}", fraction);
+                    write!(f, ".{}", fraction.trim_end_matches('0'))?;
+                }
+                f.write_str("S")?;
+            }
+        } else if months == 0 && days == 0 {
+            f.write_str("T0S")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A whole number of some unit, in nanoseconds.
+fn whole(number: &str, unit: i128) -> Option<i128> {
+    number.parse::<i128>().ok()?.checked_mul(unit)
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days from 1970-01-01 to a date of the proleptic Gregorian calendar,
+/// where year 0 is 1 BCE.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i128 {
+    let year = i128::from(year) - i128::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i128::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i128::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i128) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = year_of_era + era * 400 + i128::from(month <= 2);
+
+    (year as i64, month, day)
+}
@@ -1,5 +1,177 @@
-pub trait TryFrom<T>: Sized {
-    type Error;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::str::FromStr;
 
-    fn try_from(value: T) -> Result<Self, Self::Error>;
+use crate::diagnostics::Diagnostics;
+use crate::errors::Error;
+use crate::options::ParserOptions;
+use crate::shared::QName;
+use crate::span::Span;
+
+use roxmltree::Node;
+
+pub const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+/// The document a component is being parsed from, and where to report
+/// what lenient parsing skips.
+pub struct Context<'s> {
+    pub document: &'s str,
+    pub source: &'s str,
+    pub options: ParserOptions,
+    pub diagnostics: RefCell<Diagnostics>,
+}
+
+impl<'s> Context<'s> {
+    pub fn new(document: &'s str, source: &'s str, options: ParserOptions) -> Self {
+        Context {
+            document,
+            source,
+            options,
+            diagnostics: RefCell::new(Diagnostics::default()),
+        }
+    }
+
+    pub fn span(&self, node: Node) -> Span {
+        Span::node(self.document, self.source, node)
+    }
+
+    /// Fails with `error` in strict mode. In lenient mode, records it so that
+    /// the caller can skip the construct and go on.
+    pub fn report(&self, error: Error) -> Result<(), Error> {
+        if !self.options.lenient {
+            return Err(error);
+        }
+
+        self.diagnostics.borrow_mut().push(error);
+
+        Ok(())
+    }
+
+    /// Parses a child component, which lenient mode skips if it is invalid.
+    pub fn parse<T: FromNode>(&self, node: Node) -> Result<Option<T>, Error> {
+        let component = self
+            .check_attributes(node)
+            .and_then(|_| T::from_node(node, self));
+
+        match component {
+            Ok(component) => Ok(Some(component)),
+            Err(error) => self.report(error).map(|_| None),
+        }
+    }
+
+    /// Reports namespaced attributes other than `xml:*` when foreign
+    /// attributes are not allowed.
+    pub fn check_attributes(&self, node: Node) -> Result<(), Error> {
+        if self.options.allow_foreign_attributes {
+            return Ok(());
+        }
+
+        for attribute in node.attributes() {
+            match attribute.namespace() {
+                Some(namespace) if namespace != roxmltree::NS_XML_URI => {
+                    self.report(Error::ForeignAttribute {
+                        element: node.tag_name().name().to_owned(),
+                        name: QName::new(Some(namespace), attribute.name()),
+                        span: Span::attribute(self.document, self.source, node, attribute.name()),
+                    })?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The attributes of `node` from namespaces other than the schema
+    /// namespace, which any schema element may carry.
+    pub fn extra_attributes(&self, node: Node) -> HashMap<QName, String> {
+        node.attributes()
+            .iter()
+            .filter_map(|attribute| match attribute.namespace() {
+                Some(namespace) if namespace != XSD_NAMESPACE => Some((
+                    QName::new(Some(namespace), attribute.name()),
+                    attribute.value().to_owned(),
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The markup between the start and end tags of `node`, as written.
+    pub fn inner_markup(&self, node: Node) -> &'s str {
+        Span::inner(self.source, node)
+    }
+
+    /// The child elements of `node` in the schema namespace. Elements from
+    /// other namespaces are skipped, and reported unless they are allowed.
+    pub fn children<'c, 'a: 'c, 'd: 'a>(
+        &'c self,
+        node: Node<'a, 'd>,
+    ) -> impl Iterator<Item = Result<Node<'a, 'd>, Error>> + 'c {
+        node.children()
+            .filter(|n| n.node_type() == roxmltree::NodeType::Element)
+            .filter_map(move |child| {
+                if child.tag_name().namespace() == Some(XSD_NAMESPACE) {
+                    return Some(Ok(child));
+                }
+
+                if self.options.allow_foreign_elements {
+                    return None;
+                }
+
+                self.report(Error::ForeignElement {
+                    name: QName::new(child.tag_name().namespace(), child.tag_name().name()),
+                    span: self.span(child),
+                })
+                .err()
+                .map(Err)
+            })
+    }
+
+    pub fn required_attribute<'a>(
+        &self,
+        node: Node<'a, '_>,
+        name: &'a str,
+    ) -> Result<&'a str, Error> {
+        node.attribute(name).ok_or_else(|| Error::MissingAttribute {
+            element: node.tag_name().name().to_owned(),
+            attribute: name.to_owned(),
+            span: self.span(node),
+        })
+    }
+
+    /// The first child element of `node` after its annotation, for elements
+    /// that need one.
+    pub fn first_child<'a, 'd>(&self, node: Node<'a, 'd>) -> Result<Node<'a, 'd>, Error> {
+        let mut children = self.children(node).filter(|child| match *child {
+            Ok(child) => child.tag_name().name() != "annotation",
+            Err(_) => true,
+        });
+
+        children.next().unwrap_or_else(|| {
+            Err(Error::MissingChild {
+                element: node.tag_name().name().to_owned(),
+                span: self.span(node),
+            })
+        })
+    }
+
+    /// Parses an optional attribute, pointing any error at its value. Lenient
+    /// mode treats an invalid value as absent.
+    pub fn attribute<T>(&self, node: Node, name: &str) -> Result<Option<T>, Error>
+    where
+        T: FromStr<Err = Error>,
+    {
+        match node.attribute(name).map(T::from_str) {
+            Some(Ok(value)) => Ok(Some(value)),
+            Some(Err(error)) => self
+                .report(error.with_span(Span::attribute(self.document, self.source, node, name)))
+                .map(|_| None),
+            None => Ok(None),
+        }
+    }
+}
+
+pub trait FromNode: Sized {
+    fn from_node(node: Node, context: &Context) -> Result<Self, Error>;
 }
@@ -0,0 +1,210 @@
+//! Reference resolution for schema locations, following RFC 3986. Plain
+//! filesystem paths are treated as relative references without a scheme.
+
+use std::path::{Path, PathBuf};
+
+struct Parts<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+fn split(uri: &str) -> Parts<'_> {
+    let (rest, fragment) = match uri.find('#') {
+        Some(idx) => (&uri[..idx], Some(&uri[idx + 1..])),
+        None => (uri, None),
+    };
+
+    let (rest, query) = match rest.find('?') {
+        Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+        None => (rest, None),
+    };
+
+    // Single letters are Windows drive letters rather than schemes.
+    let (scheme, rest) = match rest.find([':', '/']) {
+        Some(idx)
+            if idx > 1
+                && rest.as_bytes()[idx] == b':'
+                && rest.as_bytes()[0].is_ascii_alphabetic()
+                && rest[..idx]
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-' || b == b'.') =>
+        {
+            (Some(&rest[..idx]), &rest[idx + 1..])
+        }
+        _ => (None, rest),
+    };
+
+    let (authority, path) = if let Some(stripped) = rest.strip_prefix("//") {
+        match stripped.find('/') {
+            Some(idx) => (Some(&stripped[..idx]), &stripped[idx..]),
+            None => (Some(stripped), ""),
+        }
+    } else {
+        (None, rest)
+    };
+
+    Parts {
+        scheme,
+        authority,
+        path,
+        query,
+        fragment,
+    }
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    let mut output: Vec<&str> = Vec::new();
+
+    for (idx, segment) in segments.iter().enumerate() {
+        let last = idx + 1 == segments.len();
+
+        match *segment {
+            "." => {}
+            ".." => match output.last() {
+                Some(&"..") | None => {
+                    // Relative paths keep the segments that climb above them.
+                    if !absolute {
+                        output.push("..");
+                    }
+                }
+                Some(_) => {
+                    output.pop();
+                }
+            },
+            segment => {
+                output.push(segment);
+                continue;
+            }
+        }
+
+        if last {
+            output.push("");
+        }
+    }
+
+    if absolute {
+        format!("/{}", output.join("/"))
+    } else {
+        output.join("/")
+    }
+}
+
+fn merge(base: &Parts, path: &str) -> String {
+    if base.authority.is_some() && base.path.is_empty() {
+        return format!("/{}", path);
+    }
+
+    match base.path.rfind('/') {
+        Some(idx) => format!("{}{}", &base.path[..=idx], path),
+        None => path.to_owned(),
+    }
+}
+
+/// Resolves `reference` against `base`.
+pub fn resolve(base: &str, reference: &str) -> String {
+    let r = split(reference);
+    let b = split(base);
+
+    let (scheme, authority, path, query) = if r.scheme.is_some() {
+        (r.scheme, r.authority, remove_dot_segments(r.path), r.query)
+    } else if r.authority.is_some() {
+        (b.scheme, r.authority, remove_dot_segments(r.path), r.query)
+    } else if r.path.is_empty() {
+        (
+            b.scheme,
+            b.authority,
+            b.path.to_owned(),
+            r.query.or(b.query),
+        )
+    } else if r.path.starts_with('/') {
+        (b.scheme, b.authority, remove_dot_segments(r.path), r.query)
+    } else {
+        (
+            b.scheme,
+            b.authority,
+            remove_dot_segments(&merge(&b, r.path)),
+            r.query,
+        )
+    };
+
+    let mut uri = String::new();
+
+    if let Some(scheme) = scheme {
+        uri.push_str(scheme);
+        uri.push(':');
+    }
+
+    if let Some(authority) = authority {
+        uri.push_str("//");
+        uri.push_str(authority);
+    }
+
+    uri.push_str(&path);
+
+    if let Some(query) = query {
+        uri.push('?');
+        uri.push_str(query);
+    }
+
+    if let Some(fragment) = r.fragment {
+        uri.push('#');
+        uri.push_str(fragment);
+    }
+
+    uri
+}
+
+/// Whether `reference` is a relative path, which only has a meaning against
+/// a base URI.
+pub fn is_relative_path(reference: &str) -> bool {
+    let r = split(reference);
+
+    r.scheme.is_none()
+        && r.authority.is_none()
+        && !r.path.starts_with('/')
+        && !Path::new(r.path).is_absolute()
+}
+
+/// Returns the filesystem path of a `file:` URI or a plain path, and `None`
+/// for any other scheme.
+pub fn to_file_path(uri: &str) -> Option<PathBuf> {
+    let parts = split(uri);
+
+    match parts.scheme {
+        None => Some(PathBuf::from(uri)),
+        Some(scheme) if scheme.eq_ignore_ascii_case("file") => {
+            Some(PathBuf::from(percent_decode(parts.path)))
+        }
+        Some(_) => None,
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[idx + 1]), hex(bytes[idx + 2])) {
+                decoded.push(hi << 4 | lo);
+                idx += 3;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
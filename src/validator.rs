@@ -0,0 +1,792 @@
+//! Validates instance documents against a schema set, reporting each
+//! violation with the id of the validation rule it breaks, where it is in
+//! the instance and the declaration or type involved.
+
+use std::collections::{HashMap, HashSet};
+use std::ptr;
+
+use roxmltree::{Document, Node};
+
+use crate::builtins::{self, Derivation};
+use crate::complex_type::{AnyAttribute, Attribute, AttributeGroup, ComplexType, Content};
+use crate::content_model::ContentModel;
+use crate::diagnostics::Diagnostics;
+use crate::element::{DefaultFixed, Element};
+use crate::errors::Error;
+use crate::identity::Identity;
+use crate::identity_constraint::IdentityConstraint;
+use crate::parser::SchemaDocument;
+use crate::particle::{content_model, Namespaces, Term};
+use crate::pattern::Patterns;
+use crate::restriction::Restriction;
+use crate::schema_set::{Global, SchemaSet};
+use crate::shared::{BlockDefault, FormDefault, QName};
+use crate::simple_type::SimpleType;
+use crate::span::Span;
+use crate::stream::{Position, StreamValidator};
+use crate::substitution_group::SubstitutionGroups;
+use crate::traits::XSD_NAMESPACE;
+use crate::value::{SimpleTypeValidator, TypedValue};
+
+pub const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+
+/// How deep type derivations and substitution group heads are followed,
+/// which bounds the work on circular definitions.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// Validates instance documents against the global element declarations of
+/// a schema set.
+pub struct Validator<'s, 'a> {
+    pub(crate) schema_set: &'s SchemaSet<'a>,
+    pub(crate) substitution_groups: SubstitutionGroups<'s, 'a>,
+    /// The content model of each complex type, by address, compiled once
+    /// so that matching children does not allocate per element.
+    content_models: HashMap<*const ComplexType, ContentModel<'a>>,
+    /// The identity constraints of the element declarations, by address,
+    /// with their XPaths compiled. Those that do not compile are left out.
+    identities: HashMap<*const IdentityConstraint, Identity<'a>>,
+    patterns: Patterns,
+}
+
+impl<'s, 'a> Validator<'s, 'a> {
+    pub fn new(schema_set: &'s SchemaSet<'a>) -> Self {
+        let mut validator = Validator {
+            schema_set,
+            substitution_groups: SubstitutionGroups::new(schema_set),
+            content_models: HashMap::new(),
+            identities: HashMap::new(),
+            patterns: Patterns::default(),
+        };
+        let mut seen = HashSet::new();
+
+        for global in schema_set.complex_types() {
+            validator.compile(global.component, global.document, &mut seen);
+        }
+
+        for global in schema_set.elements() {
+            validator.register(global.component, global.document);
+
+            if let Some(ref complex_type) = global.component.complex_type {
+                validator.compile(complex_type, global.document, &mut seen);
+            }
+        }
+
+        let referenced: HashSet<QName> = validator
+            .identities
+            .values()
+            .filter_map(|identity| identity.refer.clone())
+            .collect();
+
+        for identity in validator.identities.values_mut() {
+            identity.referenced = referenced.contains(&identity.name);
+        }
+
+        validator
+    }
+
+    /// Compiles the identity constraints of an element declaration.
+    fn register(&mut self, element: &'a Element, document: &'a SchemaDocument) {
+        for constraint in &element.identity_constraints {
+            if let Ok(identity) = Identity::new(constraint, document) {
+                self.identities.insert(constraint, identity);
+            }
+        }
+    }
+
+    /// Compiles the content model of a complex type and those of the
+    /// anonymous types of the elements it declares.
+    fn compile(
+        &mut self,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+        seen: &mut HashSet<*const ComplexType>,
+    ) {
+        if !seen.insert(complex_type) {
+            return;
+        }
+
+        let particle = match content_model(self.schema_set, complex_type, document) {
+            Some(particle) => particle,
+            None => return,
+        };
+
+        let mut pending = vec![&particle];
+        let mut local = Vec::new();
+        while let Some(particle) = pending.pop() {
+            match particle.term {
+                Term::Element(element) => {
+                    if element.r#ref.is_none() {
+                        self.register(element, particle.document);
+                    }
+
+                    if let Some(ref complex_type) = element.complex_type {
+                        local.push((&**complex_type, particle.document));
+                    }
+                }
+                Term::Sequence(ref particles)
+                | Term::Choice(ref particles)
+                | Term::All(ref particles) => pending.extend(particles),
+                Term::Any(_) => {}
+            }
+        }
+
+        for (complex_type, document) in local {
+            self.compile(complex_type, document, seen);
+        }
+
+        let model = ContentModel::new(particle, &self.substitution_groups);
+        self.content_models.insert(complex_type, model);
+    }
+
+    /// Validates a parsed document. Its source is not at hand, so spans
+    /// point at where each node starts and have no document location.
+    pub fn validate(&self, document: &Document) -> Diagnostics {
+        self.run(document, "", None)
+    }
+
+    /// Parses and validates a document held in a string. `location` names
+    /// the document in spans.
+    pub fn validate_str(
+        &self,
+        contents: &str,
+        location: Option<&str>,
+    ) -> Result<Diagnostics, Error> {
+        let location = location.unwrap_or_default();
+        let document = Document::parse(contents).map_err(|error| Error::ParseXml {
+            span: Span::text_pos(location, contents, error.pos()),
+            error,
+        })?;
+
+        Ok(self.run(&document, location, Some(contents)))
+    }
+
+    pub(crate) fn run(
+        &self,
+        document: &Document,
+        location: &str,
+        source: Option<&str>,
+    ) -> Diagnostics {
+        let mut stream = StreamValidator::new(self);
+
+        walk(&mut stream, document.root_element(), location, source);
+        stream.finish()
+    }
+}
+
+/// Where a node of a parsed document is, worked out only when a problem is
+/// reported there.
+struct NodePosition<'v, 'd> {
+    location: &'v str,
+    source: Option<&'v str>,
+    node: Node<'d, 'd>,
+    attribute: Option<&'d str>,
+}
+
+impl<'v, 'd> Position for NodePosition<'v, 'd> {
+    fn span(&self) -> Span {
+        match (self.source, self.attribute) {
+            (Some(source), Some(name)) => Span::attribute(self.location, source, self.node, name),
+            (Some(source), None) => Span::node(self.location, source, self.node),
+            (None, Some(name)) => Span::attribute_position(self.location, self.node, name),
+            (None, None) => Span::position(self.location, self.node),
+        }
+    }
+}
+
+/// Sends the events of an element and its content to a stream validator.
+fn walk<'v, 'd>(
+    stream: &mut StreamValidator<'v, '_, '_, NodePosition<'v, 'd>>,
+    node: Node<'d, 'd>,
+    location: &'v str,
+    source: Option<&'v str>,
+) {
+    let position = |attribute| NodePosition {
+        location,
+        source,
+        node,
+        attribute,
+    };
+
+    stream.start_element(expanded_name(node), position(None));
+
+    // roxmltree copies the namespaces in scope onto every element, so only
+    // those its parent does not have were declared here.
+    let inherited = node.parent_element().map_or(&[][..], |p| p.namespaces());
+
+    for namespace in node.namespaces() {
+        if !inherited.contains(namespace) {
+            stream.namespace(namespace.name(), namespace.uri());
+        }
+    }
+
+    for attribute in node.attributes() {
+        stream.attribute(
+            QName::new(attribute.namespace(), attribute.name()),
+            attribute.value(),
+            position(Some(attribute.name())),
+        );
+    }
+
+    for child in node.children() {
+        if child.is_element() {
+            walk(stream, child, location, source);
+        } else if let (true, Some(text)) = (child.is_text(), child.text()) {
+            stream.text(
+                text,
+                NodePosition {
+                    location,
+                    source,
+                    node: child,
+                    attribute: None,
+                },
+            );
+        }
+    }
+
+    stream.end_element();
+}
+
+/// A type definition, which may be anonymous.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Type<'a> {
+    /// The ur-type, `anyType`.
+    Any,
+    /// A built-in simple type, by its name in the schema namespace.
+    Builtin(&'static str),
+    Simple(&'a SimpleType, &'a SchemaDocument),
+    Complex(&'a ComplexType, &'a SchemaDocument),
+}
+
+/// Whether the values of a type declare IDs or refer to them. A list of
+/// `IDREF`s refers to each of its items.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum IdKind {
+    Id,
+    Idref,
+}
+
+impl<'a> Type<'a> {
+    fn is(&self, other: &Type) -> bool {
+        match (*self, *other) {
+            (Type::Any, Type::Any) => true,
+            (Type::Builtin(a), Type::Builtin(b)) => a == b,
+            (Type::Simple(a, _), Type::Simple(b, _)) => ptr::eq(a, b),
+            (Type::Complex(a, _), Type::Complex(b, _)) => ptr::eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl<'a> std::fmt::Display for Type<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match *self {
+            Type::Any => Some("anyType"),
+            Type::Builtin(name) => Some(name),
+            Type::Simple(simple_type, _) => simple_type.name.as_deref(),
+            Type::Complex(complex_type, _) => complex_type.name.as_deref(),
+        };
+
+        match name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "an anonymous type"),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Declaration<'a> {
+    pub element: &'a Element,
+    pub document: &'a SchemaDocument,
+}
+
+impl<'a> From<&Global<'a, Element>> for Declaration<'a> {
+    fn from(global: &Global<'a, Element>) -> Self {
+        Declaration {
+            element: global.component,
+            document: global.document,
+        }
+    }
+}
+
+pub(crate) struct AttributeUse<'a> {
+    pub name: QName,
+    /// The `xs:attribute` of the use, a local declaration or a reference.
+    pub attribute: &'a Attribute,
+    pub document: &'a SchemaDocument,
+    /// The declaration of the use: the attribute itself, or the global
+    /// declaration it refers to. `None` for references that do not resolve.
+    pub declaration: Option<(&'a Attribute, &'a SchemaDocument)>,
+}
+
+impl<'a> AttributeUse<'a> {
+    /// The default or fixed value of the use, else that of the declaration
+    /// it refers to, along with the document it is declared in.
+    pub fn default_fixed(&self) -> Option<(&'a DefaultFixed, &'a SchemaDocument)> {
+        let own = self
+            .attribute
+            .default_fixed
+            .as_ref()
+            .map(|d| (d, self.document));
+
+        own.or_else(|| {
+            let (declaration, document) = self.declaration?;
+            declaration.default_fixed.as_ref().map(|d| (d, document))
+        })
+    }
+}
+
+/// The attribute wildcard of a complex type. It allows the namespaces that
+/// all of its own and its attribute groups' wildcards allow, or, for an
+/// extension, those the wildcard of the base allows.
+pub(crate) struct AttributeWildcard<'a> {
+    /// The wildcard whose `processContents` applies.
+    pub any_attribute: &'a AnyAttribute,
+    namespaces: Vec<Namespaces>,
+    base: Option<Box<AttributeWildcard<'a>>>,
+}
+
+impl<'a> AttributeWildcard<'a> {
+    pub fn allows(&self, namespace: Option<&str>) -> bool {
+        self.namespaces.iter().all(|n| n.allows(namespace))
+            || self
+                .base
+                .as_ref()
+                .is_some_and(|base| base.allows(namespace))
+    }
+}
+
+impl<'s, 'a> Validator<'s, 'a> {
+    pub(crate) fn content_model(&self, complex_type: &ComplexType) -> Option<&ContentModel<'a>> {
+        self.content_models
+            .get(&(complex_type as *const ComplexType))
+    }
+
+    pub(crate) fn identity(&self, constraint: &IdentityConstraint) -> Option<&Identity<'a>> {
+        self.identities
+            .get(&(constraint as *const IdentityConstraint))
+    }
+
+    /// The attribute uses of a complex type, those of the type it is derived
+    /// from and of its attribute groups included, and its wildcard. A
+    /// restriction overrides the uses of its base by name.
+    pub(crate) fn attribute_uses(
+        &self,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+        uses: &mut Vec<AttributeUse<'a>>,
+        depth: usize,
+    ) -> Option<AttributeWildcard<'a>> {
+        let content = complex_type
+            .complex_content
+            .as_ref()
+            .map(|c| &c.content)
+            .or_else(|| complex_type.simple_content.as_ref().map(|c| &c.content));
+
+        let (attributes, attribute_groups, any_attribute, base_wildcard) = match content {
+            Some(content) => {
+                let base = match *content {
+                    Content::Extension(ref extension) => Some(&extension.base),
+                    Content::Restriction(ref restriction) => restriction.base.as_ref(),
+                };
+
+                let mut base_wildcard = None;
+
+                if depth < MAX_DEPTH {
+                    let base = base
+                        .and_then(|base| document.resolve(base))
+                        .and_then(|base| self.lookup(&base));
+
+                    if let Some(Type::Complex(base, base_document)) = base {
+                        base_wildcard = self.attribute_uses(base, base_document, uses, depth + 1);
+                    }
+                }
+
+                match *content {
+                    Content::Extension(ref extension) => (
+                        &extension.attributes,
+                        &extension.attribute_groups,
+                        &extension.any_attribute,
+                        base_wildcard,
+                    ),
+                    Content::Restriction(ref restriction) => (
+                        &restriction.attributes,
+                        &restriction.attribute_groups,
+                        &restriction.any_attribute,
+                        None,
+                    ),
+                }
+            }
+            None => (
+                &complex_type.attributes,
+                &complex_type.attribute_groups,
+                &complex_type.any_attribute,
+                None,
+            ),
+        };
+
+        let mut wildcards: Vec<(&'a AnyAttribute, &'a SchemaDocument)> =
+            any_attribute.iter().map(|any| (&**any, document)).collect();
+
+        self.attributes(attributes, document, uses);
+        self.attribute_groups(attribute_groups, document, uses, &mut wildcards, depth);
+
+        let wildcard = wildcards
+            .first()
+            .map(|&(any_attribute, _)| AttributeWildcard {
+                any_attribute,
+                namespaces: wildcards
+                    .iter()
+                    .map(|(any, document)| {
+                        Namespaces::new(any.namespace.as_deref(), &document.target_namespace)
+                    })
+                    .collect(),
+                base: None,
+            });
+
+        match (wildcard, base_wildcard) {
+            (Some(wildcard), base) => Some(AttributeWildcard {
+                base: base.map(Box::new),
+                ..wildcard
+            }),
+            (None, base) => base,
+        }
+    }
+
+    /// Adds attribute declarations and references to `uses`, replacing
+    /// those of the same name.
+    fn attributes(
+        &self,
+        attributes: &'a [Attribute],
+        document: &'a SchemaDocument,
+        uses: &mut Vec<AttributeUse<'a>>,
+    ) {
+        for attribute in attributes {
+            let name = match attribute_name(attribute, document) {
+                Some(name) => name,
+                None => continue,
+            };
+            let declaration = match attribute.r#ref {
+                Some(_) => self
+                    .schema_set
+                    .attribute(&name)
+                    .map(|global| (global.component, global.document)),
+                None => Some((attribute, document)),
+            };
+            let used = AttributeUse {
+                name,
+                attribute,
+                document,
+                declaration,
+            };
+
+            match uses.iter_mut().find(|u| u.name == used.name) {
+                Some(existing) => *existing = used,
+                None => uses.push(used),
+            }
+        }
+    }
+
+    /// Adds the attribute uses and wildcards of the attribute groups that
+    /// `references` refer to.
+    fn attribute_groups(
+        &self,
+        references: &'a [AttributeGroup],
+        document: &'a SchemaDocument,
+        uses: &mut Vec<AttributeUse<'a>>,
+        wildcards: &mut Vec<(&'a AnyAttribute, &'a SchemaDocument)>,
+        depth: usize,
+    ) {
+        if depth >= MAX_DEPTH {
+            return;
+        }
+
+        for reference in references {
+            let group = reference
+                .r#ref
+                .as_ref()
+                .and_then(|r| document.resolve(r))
+                .and_then(|name| self.schema_set.attribute_group(&name));
+
+            let group = match group {
+                Some(group) => group,
+                None => continue,
+            };
+            let (component, group_document) = (group.component, group.document);
+
+            wildcards.extend(
+                component
+                    .any_attribute
+                    .iter()
+                    .map(|any| (&**any, group_document)),
+            );
+            self.attributes(&component.attributes, group_document, uses);
+            self.attribute_groups(
+                &component.attribute_groups,
+                group_document,
+                uses,
+                wildcards,
+                depth + 1,
+            );
+        }
+    }
+
+    /// The type of an attribute use, `None` for references that do not
+    /// resolve.
+    pub(crate) fn attribute_type(&self, used: &AttributeUse<'a>) -> Option<Type<'a>> {
+        let (attribute, document) = used.declaration?;
+
+        if let Some(ref simple_type) = attribute.simple_type {
+            return Some(Type::Simple(simple_type, document));
+        }
+
+        match attribute.r#type {
+            Some(ref ty) => document.resolve(ty).and_then(|ty| self.lookup(&ty)),
+            None => Some(Type::Builtin("anySimpleType")),
+        }
+    }
+
+    /// The type of an element declaration: the one it names or declares,
+    /// else that of the head of its substitution group, else `anyType`.
+    /// Types that cannot be resolved are reported by the constraint checks.
+    pub(crate) fn element_type(&self, declaration: Declaration<'a>, depth: usize) -> Type<'a> {
+        let Declaration { element, document } = declaration;
+
+        if let Some(ref ty) = element.r#type {
+            return document
+                .resolve(ty)
+                .and_then(|ty| self.lookup(&ty))
+                .unwrap_or(Type::Any);
+        }
+
+        if let Some(ref complex_type) = element.complex_type {
+            return Type::Complex(complex_type, document);
+        }
+
+        if let Some(ref simple_type) = element.simple_type {
+            return Type::Simple(simple_type, document);
+        }
+
+        let head = element
+            .substitution_group
+            .first()
+            .and_then(|head| document.resolve(head))
+            .and_then(|head| self.schema_set.element(&head));
+
+        match head {
+            Some(head) if depth < MAX_DEPTH => self.element_type(head.into(), depth + 1),
+            _ => Type::Any,
+        }
+    }
+
+    pub(crate) fn lookup(&self, name: &QName) -> Option<Type<'a>> {
+        lookup(self.schema_set, name)
+    }
+
+    /// The type `ty` is derived from, `None` for `anyType`.
+    fn base(&self, ty: Type<'a>) -> Option<Type<'a>> {
+        match ty {
+            Type::Any => None,
+            Type::Builtin(name) => match builtins::derivation(name)? {
+                Derivation::Restriction("anyType") => Some(Type::Any),
+                Derivation::Restriction(base) => Some(Type::Builtin(base)),
+                Derivation::List(_) => Some(Type::Builtin("anySimpleType")),
+            },
+            Type::Simple(simple_type, document) => match simple_type.restriction {
+                Some(ref restriction) => restriction_base(self.schema_set, restriction, document),
+                None => Some(Type::Builtin("anySimpleType")),
+            },
+            Type::Complex(complex_type, document) => match complex_type.base() {
+                Some(base) => document.resolve(base).and_then(|base| self.lookup(&base)),
+                None => Some(Type::Any),
+            },
+        }
+    }
+
+    /// Whether `derived` is, or is derived from, `base` without a step by
+    /// a method that `blocked` names.
+    pub(crate) fn derives(
+        &self,
+        derived: Type<'a>,
+        base: Type<'a>,
+        blocked: &BlockDefault,
+    ) -> bool {
+        let mut current = Some(derived);
+
+        for _ in 0..MAX_DEPTH {
+            match current {
+                Some(ty) if ty.is(&base) => return true,
+                Some(ty) => {
+                    let extension = match ty {
+                        Type::Complex(complex_type, _) => complex_type.is_extension(),
+                        _ => false,
+                    };
+
+                    if extension && blocked.extension || !extension && blocked.restriction {
+                        return false;
+                    }
+
+                    current = self.base(ty);
+                }
+                None => return false,
+            }
+        }
+
+        false
+    }
+
+    /// Whether a simple type is, or is derived from, `ID` or `IDREF`, or is a
+    /// list of such items. Unions are neither.
+    pub(crate) fn id_kind(&self, ty: Type<'a>) -> Option<IdKind> {
+        let mut current = ty;
+
+        for _ in 0..MAX_DEPTH {
+            current = match current {
+                Type::Builtin("ID") => return Some(IdKind::Id),
+                Type::Builtin("IDREF") => return Some(IdKind::Idref),
+                Type::Builtin(name) => match builtins::derivation(name)? {
+                    Derivation::Restriction(base) | Derivation::List(base) => Type::Builtin(base),
+                },
+                Type::Simple(simple_type, document) => {
+                    if let Some(ref list) = simple_type.list {
+                        match (&list.simple_type, &list.item_type) {
+                            (Some(item), _) => Type::Simple(item, document),
+                            (None, Some(item)) => self.lookup(&document.resolve(item)?)?,
+                            (None, None) => return None,
+                        }
+                    } else {
+                        let restriction = simple_type.restriction.as_ref()?;
+                        restriction_base(self.schema_set, restriction, document)?
+                    }
+                }
+                Type::Any | Type::Complex(..) => return None,
+            };
+        }
+
+        None
+    }
+
+    /// The simple type of the value of a complex type with simple content,
+    /// and the restrictions between the two.
+    pub(crate) fn simple_content(
+        &self,
+        complex_type: &'a ComplexType,
+        document: &'a SchemaDocument,
+        depth: usize,
+    ) -> (Type<'a>, Vec<&'a Restriction>) {
+        let any_simple_type = (Type::Builtin("anySimpleType"), Vec::new());
+        let content = match complex_type.simple_content {
+            Some(ref simple_content) => &simple_content.content,
+            None => return any_simple_type,
+        };
+        let (base, restriction) = match *content {
+            Content::Extension(ref extension) => (Some(&extension.base), None),
+            Content::Restriction(ref restriction) => {
+                (restriction.base.as_ref(), Some(&**restriction))
+            }
+        };
+
+        // A simple type given inline is derived from the content type of the
+        // base, so it stands in for it.
+        if let Some(inline) = restriction.and_then(|r| r.simple_type.as_deref()) {
+            return (
+                Type::Simple(inline, document),
+                restriction.into_iter().collect(),
+            );
+        }
+
+        let base = base
+            .and_then(|base| document.resolve(base))
+            .and_then(|base| self.lookup(&base));
+        let (ty, mut restrictions) = match base {
+            Some(Type::Complex(base, base_document)) if depth < MAX_DEPTH => {
+                self.simple_content(base, base_document, depth + 1)
+            }
+            Some(Type::Complex(..)) | Some(Type::Any) | None => any_simple_type,
+            Some(simple) => (simple, Vec::new()),
+        };
+
+        restrictions.extend(restriction);
+        (ty, restrictions)
+    }
+
+    /// Checks values of a simple type.
+    pub(crate) fn simple_type(&self, ty: Type<'a>) -> SimpleTypeValidator<'_, 'a> {
+        SimpleTypeValidator::of(self.schema_set, ty).with_patterns(&self.patterns)
+    }
+
+    /// Whether a value, and its lexical form, is the fixed value of a
+    /// declaration in `document`. Fixed values that are not valid are
+    /// compared as strings.
+    pub(crate) fn is_fixed(
+        &self,
+        ty: Type<'a>,
+        (typed, value): (&TypedValue, &str),
+        fixed: &str,
+        document: &'a SchemaDocument,
+    ) -> bool {
+        match self
+            .simple_type(ty)
+            .validate_in(fixed, &|name| document.resolve(name))
+        {
+            Ok(fixed) => fixed == *typed,
+            Err(_) => value == fixed,
+        }
+    }
+}
+
+/// The expanded name of an element. `xmlns=""` leaves it in no namespace.
+fn expanded_name(node: Node) -> QName {
+    let namespace = node.tag_name().namespace().filter(|n| !n.is_empty());
+    QName::new(namespace, node.tag_name().name())
+}
+
+/// The expanded name instances use for an attribute declaration.
+fn attribute_name(attribute: &Attribute, document: &SchemaDocument) -> Option<QName> {
+    if let Some(ref r) = attribute.r#ref {
+        return document.resolve(r);
+    }
+
+    let form = attribute
+        .form
+        .as_ref()
+        .unwrap_or_else(|| document.attribute_form_default());
+    let namespace = match form {
+        FormDefault::Qualified => document.target_namespace.as_deref(),
+        FormDefault::Unqualified => None,
+    };
+
+    Some(QName::new(namespace, attribute.name.as_deref()?))
+}
+
+/// The base of a restriction in `document`: its inline simple type, or
+/// the type its `base` names.
+pub(crate) fn restriction_base<'a>(
+    schema_set: &SchemaSet<'a>,
+    restriction: &'a Restriction,
+    document: &'a SchemaDocument,
+) -> Option<Type<'a>> {
+    match restriction.simple_type {
+        Some(ref simple_type) => Some(Type::Simple(simple_type, document)),
+        None => lookup(schema_set, &document.resolve(restriction.base.as_ref()?)?),
+    }
+}
+
+/// A built-in type or a global type definition by name.
+pub(crate) fn lookup<'a>(schema_set: &SchemaSet<'a>, name: &QName) -> Option<Type<'a>> {
+    if name.namespace.as_deref() == Some(XSD_NAMESPACE) {
+        if name.name == "anyType" {
+            return Some(Type::Any);
+        }
+
+        if let Some(builtin) = builtins::simple_type(&name.name) {
+            return Some(Type::Builtin(builtin));
+        }
+    }
+
+    match schema_set.simple_type(name) {
+        Some(simple_type) => Some(Type::Simple(simple_type.component, simple_type.document)),
+        None => schema_set
+            .complex_type(name)
+            .map(|complex_type| Type::Complex(complex_type.component, complex_type.document)),
+    }
+}
@@ -0,0 +1,528 @@
+//! Maps the lexical forms of simple types to their values, and checks the
+//! values against the facets of the types.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::rc::Rc;
+
+use crate::builtins::{self, Derivation, WhiteSpace};
+use crate::decimal::Decimal;
+use crate::parser::SchemaDocument;
+use crate::pattern::{Pattern, Patterns};
+use crate::restriction::{Restriction, Restrictions};
+use crate::schema_set::SchemaSet;
+use crate::shared::QName;
+use crate::simple_type::SimpleType;
+use crate::temporal::{DateTime, Duration};
+use crate::validator::{lookup, restriction_base, Type, MAX_DEPTH};
+
+/// A value in the value space of a simple type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypedValue {
+    /// A value of `string` or of a type derived from it.
+    String(String),
+    Boolean(bool),
+    /// A value of `decimal` or of one of the integer types.
+    Decimal(Decimal),
+    Float(f32),
+    Double(f64),
+    /// A value of `dateTime`, `time`, `date` or a Gregorian type.
+    DateTime(DateTime),
+    Duration(Duration),
+    HexBinary(Vec<u8>),
+    Base64Binary(Vec<u8>),
+    AnyUri(String),
+    QName(QName),
+    Notation(QName),
+    List(Vec<TypedValue>),
+}
+
+impl PartialOrd for TypedValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (TypedValue::Decimal(a), TypedValue::Decimal(b)) => a.partial_cmp(b),
+            (TypedValue::Float(a), TypedValue::Float(b)) => a.partial_cmp(b),
+            (TypedValue::Double(a), TypedValue::Double(b)) => a.partial_cmp(b),
+            (TypedValue::DateTime(a), TypedValue::DateTime(b)) => a.partial_cmp(b),
+            (TypedValue::Duration(a), TypedValue::Duration(b)) => a.partial_cmp(b),
+            (a, b) if a == b => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl Hash for TypedValue {
+    /// Agrees with equality, with both zeros of a float hashed alike.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            TypedValue::String(value) | TypedValue::AnyUri(value) => value.hash(state),
+            TypedValue::Boolean(value) => value.hash(state),
+            TypedValue::Decimal(value) => value.hash(state),
+            TypedValue::Float(value) => (value + 0.0).to_bits().hash(state),
+            TypedValue::Double(value) => (value + 0.0).to_bits().hash(state),
+            TypedValue::DateTime(value) => value.hash(state),
+            TypedValue::Duration(value) => value.hash(state),
+            TypedValue::HexBinary(value) | TypedValue::Base64Binary(value) => value.hash(state),
+            TypedValue::QName(value) | TypedValue::Notation(value) => value.hash(state),
+            TypedValue::List(values) => values.hash(state),
+        }
+    }
+}
+
+impl TypedValue {
+    /// Whether both values are of a kind where some pairs of values are
+    /// not ordered: floats with NaN, dates and times, and durations.
+    fn is_partially_ordered(&self, other: &TypedValue) -> bool {
+        matches!(
+            (self, other),
+            (TypedValue::Float(_), TypedValue::Float(_))
+                | (TypedValue::Double(_), TypedValue::Double(_))
+                | (TypedValue::DateTime(_), TypedValue::DateTime(_))
+                | (TypedValue::Duration(_), TypedValue::Duration(_))
+        )
+    }
+
+    /// The length the length facets measure: characters, octets or list
+    /// items. `None` for values they do not apply to.
+    fn length(&self) -> Option<usize> {
+        match *self {
+            TypedValue::String(ref value) | TypedValue::AnyUri(ref value) => {
+                Some(value.chars().count())
+            }
+            TypedValue::HexBinary(ref octets) | TypedValue::Base64Binary(ref octets) => {
+                Some(octets.len())
+            }
+            TypedValue::List(ref items) => Some(items.len()),
+            _ => None,
+        }
+    }
+}
+
+/// A value that is not valid for a simple type: the validation rule it
+/// breaks and why.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FacetViolation {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for FacetViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.rule, self.message)
+    }
+}
+
+/// A simple type resolved against a schema set, which maps strings to
+/// values of the type and checks them against its facets.
+#[derive(Clone, Copy)]
+pub struct SimpleTypeValidator<'s, 'a> {
+    schema_set: &'s SchemaSet<'a>,
+    ty: Type<'a>,
+    /// Where compiled patterns are kept between values, if anywhere.
+    patterns: Option<&'s Patterns>,
+}
+
+impl<'s, 'a> SimpleTypeValidator<'s, 'a> {
+    /// A simple type declared in `document`, which may be anonymous.
+    pub fn new(
+        schema_set: &'s SchemaSet<'a>,
+        simple_type: &'a SimpleType,
+        document: &'a SchemaDocument,
+    ) -> Self {
+        SimpleTypeValidator::of(schema_set, Type::Simple(simple_type, document))
+    }
+
+    /// A built-in or global simple type by name, `None` for complex types
+    /// and names that are not declared.
+    pub fn named(schema_set: &'s SchemaSet<'a>, name: &QName) -> Option<Self> {
+        match lookup(schema_set, name)? {
+            ty @ Type::Builtin(_) | ty @ Type::Simple(..) => {
+                Some(SimpleTypeValidator::of(schema_set, ty))
+            }
+            Type::Any | Type::Complex(..) => None,
+        }
+    }
+
+    pub(crate) fn of(schema_set: &'s SchemaSet<'a>, ty: Type<'a>) -> Self {
+        SimpleTypeValidator {
+            schema_set,
+            ty,
+            patterns: None,
+        }
+    }
+
+    pub(crate) fn with_patterns(self, patterns: &'s Patterns) -> Self {
+        SimpleTypeValidator {
+            patterns: Some(patterns),
+            ..self
+        }
+    }
+
+    /// Maps `value` to a value of the type and checks its facets. Prefixed
+    /// QNames do not resolve, as no namespaces are in scope.
+    pub fn validate(&self, value: &str) -> Result<TypedValue, FacetViolation> {
+        self.validate_in(value, &|name| QName::resolve(name, &HashMap::new()))
+    }
+
+    /// Like `validate`, resolving QNames with `resolve`.
+    pub fn validate_in(
+        &self,
+        value: &str,
+        resolve: &dyn Fn(&str) -> Option<QName>,
+    ) -> Result<TypedValue, FacetViolation> {
+        self.value_at(self.ty, value, resolve, 0)
+    }
+
+    /// Like `validate_in`, then checks the facets of restrictions of a
+    /// complex type with simple content of this type.
+    pub(crate) fn validate_restricted(
+        &self,
+        value: &str,
+        restrictions: &[&'a Restriction],
+        resolve: &dyn Fn(&str) -> Option<QName>,
+    ) -> Result<TypedValue, FacetViolation> {
+        let typed = self.validate_in(value, resolve)?;
+        let value = self.white_space(self.ty, 0).normalize(value);
+
+        for restriction in restrictions {
+            self.facets(self.ty, None, restriction, &value, &typed)?;
+        }
+
+        Ok(typed)
+    }
+
+    fn value_at(
+        &self,
+        ty: Type<'a>,
+        value: &str,
+        resolve: &dyn Fn(&str) -> Option<QName>,
+        depth: usize,
+    ) -> Result<TypedValue, FacetViolation> {
+        let value = self.white_space(ty, 0).normalize(value);
+
+        if depth >= MAX_DEPTH {
+            return Ok(TypedValue::String(value));
+        }
+
+        let invalid = || FacetViolation {
+            rule: "cvc-datatype-valid.1.2.1",
+            message: format!("{:?} is not a valid value of {}", value, ty),
+        };
+
+        let (simple_type, document) = match ty {
+            Type::Any | Type::Complex(..) => return Ok(TypedValue::String(value)),
+            Type::Builtin(name) => {
+                return match builtins::derivation(name) {
+                    Some(Derivation::List(_)) if value.is_empty() => Err(invalid()),
+                    Some(Derivation::List(item)) => value
+                        .split(' ')
+                        .map(|v| self.value_at(Type::Builtin(item), v, resolve, depth + 1))
+                        .collect::<Result<_, _>>()
+                        .map(TypedValue::List),
+                    _ => atomic(name, &value, resolve).ok_or_else(invalid),
+                };
+            }
+            Type::Simple(simple_type, document) => (simple_type, document),
+        };
+
+        if let Some(ref list) = simple_type.list {
+            let item = match (&list.simple_type, &list.item_type) {
+                (Some(item), _) => Type::Simple(item, document),
+                (None, Some(item)) => match document.resolve(item).and_then(|i| self.lookup(&i)) {
+                    Some(item) => item,
+                    None => return Ok(TypedValue::String(value)),
+                },
+                (None, None) => return Ok(TypedValue::String(value)),
+            };
+
+            return value
+                .split_whitespace()
+                .map(|v| self.value_at(item, v, resolve, depth + 1))
+                .collect::<Result<_, _>>()
+                .map(TypedValue::List);
+        }
+
+        if let Some(ref union) = simple_type.union {
+            let members = union
+                .member_types
+                .iter()
+                .filter_map(|m| document.resolve(m).and_then(|m| self.lookup(&m)))
+                .chain(
+                    union
+                        .simple_types
+                        .iter()
+                        .map(|member| Type::Simple(member, document)),
+                );
+
+            for member in members {
+                if let Ok(typed) = self.value_at(member, &value, resolve, depth + 1) {
+                    return Ok(typed);
+                }
+            }
+
+            return Err(FacetViolation {
+                rule: "cvc-datatype-valid.1.2.3",
+                message: format!("{:?} is not a valid value of any member of {}", value, ty),
+            });
+        }
+
+        let restriction = match simple_type.restriction {
+            Some(ref restriction) => restriction,
+            None => return Ok(TypedValue::String(value)),
+        };
+        let base = restriction_base(self.schema_set, restriction, document);
+        let typed = match base {
+            Some(base) => self.value_at(base, &value, resolve, depth + 1)?,
+            None => TypedValue::String(value.clone()),
+        };
+
+        self.facets(ty, base, restriction, &value, &typed)?;
+        Ok(typed)
+    }
+
+    /// Checks a value, and its normalised lexical form, against the facets
+    /// of a restriction of `ty`. The bounds and enumerations are mapped to
+    /// values of `base`, or compared as strings when it is not known.
+    fn facets(
+        &self,
+        ty: Type<'a>,
+        base: Option<Type<'a>>,
+        restriction: &Restriction,
+        value: &str,
+        typed: &TypedValue,
+    ) -> Result<(), FacetViolation> {
+        let base = base.unwrap_or(ty);
+        let document = match ty {
+            Type::Simple(_, document) => Some(document),
+            _ => None,
+        };
+        let literal = |literal: &str| {
+            let resolve = |name: &str| document.and_then(|d| d.resolve(name));
+            self.value_at(base, literal, &resolve, 1)
+                .unwrap_or_else(|_| TypedValue::String(literal.to_owned()))
+        };
+
+        let enumeration: Vec<&str> = restriction
+            .restrictions
+            .iter()
+            .filter_map(|r| match *r {
+                Restrictions::Enumeration(ref v) => Some(v.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        if !enumeration.is_empty() && !enumeration.iter().any(|e| literal(e) == *typed) {
+            return Err(FacetViolation {
+                rule: "cvc-enumeration-valid",
+                message: format!("{:?} is not one of {:?}", value, enumeration),
+            });
+        }
+
+        // The patterns of one derivation step are alternatives; those that
+        // do not compile are reported by the constraints and ignored here.
+        let patterns: Vec<Rc<Pattern>> = restriction
+            .restrictions
+            .iter()
+            .filter_map(|r| match *r {
+                Restrictions::Pattern(ref v) => self.pattern(v),
+                _ => None,
+            })
+            .collect();
+
+        if !patterns.is_empty() && !patterns.iter().any(|p| p.is_match(value)) {
+            let patterns: Vec<&str> = patterns.iter().map(|p| p.as_str()).collect();
+
+            return Err(FacetViolation {
+                rule: "cvc-pattern-valid",
+                message: format!("{:?} does not match {:?}", value, patterns),
+            });
+        }
+
+        let length = typed.length();
+
+        for facet in &restriction.restrictions {
+            let (name, bound) = facet.facet();
+            let bound = bound.trim();
+            // Values that are only partially ordered fail a bound they are
+            // not ordered against; other failures to compare are let pass.
+            let within = |accept: fn(Ordering) -> bool| {
+                let bound = literal(bound);
+                match typed.partial_cmp(&bound) {
+                    Some(order) => accept(order),
+                    None => !typed.is_partially_ordered(&bound),
+                }
+            };
+            let count = |check: fn(usize, usize) -> bool| {
+                bound.parse().map_or(true, |n: usize| {
+                    length.is_none_or(|length| check(length, n))
+                })
+            };
+
+            let valid = match *facet {
+                Restrictions::Length(_) => count(|length, n| length == n),
+                Restrictions::MinLength(_) => count(|length, n| length >= n),
+                Restrictions::MaxLength(_) => count(|length, n| length <= n),
+                Restrictions::MinInclusive(_) => within(|o| o != Ordering::Less),
+                Restrictions::MaxInclusive(_) => within(|o| o != Ordering::Greater),
+                Restrictions::MinExclusive(_) => within(|o| o == Ordering::Greater),
+                Restrictions::MaxExclusive(_) => within(|o| o == Ordering::Less),
+                Restrictions::TotalDigits(_) => match *typed {
+                    TypedValue::Decimal(ref decimal) => bound
+                        .parse()
+                        .map_or(true, |n: usize| decimal.total_digits() <= n),
+                    _ => true,
+                },
+                Restrictions::FractionDigits(_) => match *typed {
+                    TypedValue::Decimal(ref decimal) => bound
+                        .parse()
+                        .map_or(true, |n: usize| decimal.fraction_digits() <= n),
+                    _ => true,
+                },
+                Restrictions::Enumeration(_)
+                | Restrictions::Pattern(_)
+                | Restrictions::WhiteSpace(_) => true,
+            };
+
+            if !valid {
+                let rule = match *facet {
+                    Restrictions::Length(_) => "cvc-length-valid",
+                    Restrictions::MinLength(_) => "cvc-minLength-valid",
+                    Restrictions::MaxLength(_) => "cvc-maxLength-valid",
+                    Restrictions::MinInclusive(_) => "cvc-minInclusive-valid",
+                    Restrictions::MaxInclusive(_) => "cvc-maxInclusive-valid",
+                    Restrictions::MinExclusive(_) => "cvc-minExclusive-valid",
+                    Restrictions::MaxExclusive(_) => "cvc-maxExclusive-valid",
+                    Restrictions::TotalDigits(_) => "cvc-totalDigits-valid",
+                    _ => "cvc-fractionDigits-valid",
+                };
+
+                return Err(FacetViolation {
+                    rule,
+                    message: format!("{:?} does not meet {} {}", value, name, bound),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lookup(&self, name: &QName) -> Option<Type<'a>> {
+        lookup(self.schema_set, name)
+    }
+
+    fn pattern(&self, source: &str) -> Option<Rc<Pattern>> {
+        match self.patterns {
+            Some(patterns) => patterns.get(source),
+            None => Pattern::new(source).ok().map(Rc::new),
+        }
+    }
+
+    fn white_space(&self, ty: Type<'a>, depth: usize) -> WhiteSpace {
+        let (simple_type, document) = match ty {
+            Type::Builtin(name) => return builtins::white_space(name),
+            Type::Simple(simple_type, document) => (simple_type, document),
+            Type::Any | Type::Complex(..) => return WhiteSpace::Preserve,
+        };
+
+        if simple_type.list.is_some() {
+            return WhiteSpace::Collapse;
+        }
+
+        // Each member of a union normalises the value its own way.
+        let restriction = match simple_type.restriction {
+            Some(ref restriction) if depth < MAX_DEPTH => restriction,
+            _ => return WhiteSpace::Preserve,
+        };
+
+        let facet = restriction.restrictions.iter().find_map(|r| match *r {
+            Restrictions::WhiteSpace(ref v) => WhiteSpace::parse(v),
+            _ => None,
+        });
+
+        facet.unwrap_or_else(|| {
+            restriction_base(self.schema_set, restriction, document)
+                .map_or(WhiteSpace::Preserve, |base| {
+                    self.white_space(base, depth + 1)
+                })
+        })
+    }
+}
+
+/// Maps a normalised value of a built-in atomic type to its value, `None`
+/// when it is not in the lexical space of the type.
+fn atomic(
+    name: &'static str,
+    value: &str,
+    resolve: &dyn Fn(&str) -> Option<QName>,
+) -> Option<TypedValue> {
+    if !builtins::is_valid(name, value) {
+        return None;
+    }
+
+    let typed = match builtins::primitive(name).unwrap_or(name) {
+        "boolean" => TypedValue::Boolean(value == "true" || value == "1"),
+        "decimal" => TypedValue::Decimal(Decimal::parse(value)?),
+        "float" => TypedValue::Float(float(value)? as f32),
+        "double" => TypedValue::Double(float(value)?),
+        "duration" => TypedValue::Duration(Duration::parse(value)?),
+        primitive @ ("dateTime" | "time" | "date" | "gYearMonth" | "gYear" | "gMonthDay"
+        | "gDay" | "gMonth") => TypedValue::DateTime(DateTime::parse(primitive, value)?),
+        "hexBinary" => TypedValue::HexBinary(hex(value)?),
+        "base64Binary" => TypedValue::Base64Binary(base64(value)?),
+        "anyURI" => TypedValue::AnyUri(value.to_owned()),
+        "QName" => TypedValue::QName(resolve(value)?),
+        "NOTATION" => TypedValue::Notation(resolve(value)?),
+        _ => TypedValue::String(value.to_owned()),
+    };
+
+    Some(typed)
+}
+
+fn float(value: &str) -> Option<f64> {
+    match value {
+        "INF" | "+INF" => Some(f64::INFINITY),
+        "-INF" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        value => value.parse().ok(),
+    }
+}
+
+fn hex(value: &str) -> Option<Vec<u8>> {
+    (0..value.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(value.get(idx..idx + 2)?, 16).ok())
+        .collect()
+}
+
+fn base64(value: &str) -> Option<Vec<u8>> {
+    let mut octets = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for b in value.bytes().filter(|&b| b != b' ' && b != b'=') {
+        let sextet = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        buffer = buffer << 6 | u32::from(sextet);
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            octets.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(octets)
+}
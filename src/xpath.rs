@@ -0,0 +1,167 @@
+//! The XPath subset identity constraints are written in: unions of child
+//! paths, which may start with `.//` to reach any descendant, and for
+//! fields may end with an attribute.
+
+use std::collections::HashMap;
+
+use crate::builtins::is_ncname;
+use crate::shared::QName;
+
+#[derive(Clone, Debug, PartialEq)]
+enum NameTest {
+    Any,
+    /// Any name in the namespace, from `prefix:*`.
+    Namespace(Option<String>),
+    Name(QName),
+}
+
+impl NameTest {
+    fn matches(&self, name: &QName) -> bool {
+        match *self {
+            NameTest::Any => true,
+            NameTest::Namespace(ref namespace) => name.namespace == *namespace,
+            NameTest::Name(ref test) => test == name,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Path {
+    descendant: bool,
+    steps: Vec<NameTest>,
+    attribute: Option<NameTest>,
+}
+
+impl Path {
+    /// Whether the element steps match `names`, the names of the elements
+    /// from below the context element down to the one being tested.
+    fn matches(&self, names: &[&QName]) -> bool {
+        let steps = &self.steps;
+
+        if self.descendant {
+            names.len() >= steps.len()
+                && steps
+                    .iter()
+                    .zip(&names[names.len() - steps.len()..])
+                    .all(|(step, name)| step.matches(name))
+        } else {
+            names.len() == steps.len()
+                && steps
+                    .iter()
+                    .zip(names)
+                    .all(|(step, name)| step.matches(name))
+        }
+    }
+}
+
+/// A compiled selector or field.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct XPath {
+    paths: Vec<Path>,
+}
+
+impl XPath {
+    /// Compiles a selector, which selects only elements. Prefixes are
+    /// resolved against `namespaces`, keyed as in `Schema::namespaces`, and
+    /// unprefixed names have no namespace.
+    pub(crate) fn selector(
+        xpath: &str,
+        namespaces: &HashMap<String, String>,
+    ) -> Result<Self, String> {
+        XPath::parse(xpath, namespaces, false)
+    }
+
+    /// Compiles a field, which may select attributes too.
+    pub(crate) fn field(xpath: &str, namespaces: &HashMap<String, String>) -> Result<Self, String> {
+        XPath::parse(xpath, namespaces, true)
+    }
+
+    fn parse(
+        xpath: &str,
+        namespaces: &HashMap<String, String>,
+        attributes: bool,
+    ) -> Result<Self, String> {
+        let xpath: String = xpath.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut paths = Vec::new();
+
+        for path in xpath.split('|') {
+            let (descendant, path) = match path.strip_prefix(".//") {
+                Some(rest) => (true, rest),
+                None => (false, path),
+            };
+            let mut parsed = Path {
+                descendant,
+                steps: Vec::new(),
+                attribute: None,
+            };
+
+            for step in path.split('/') {
+                if parsed.attribute.is_some() {
+                    return Err("An attribute must be the last step".to_owned());
+                }
+
+                let attribute = step
+                    .strip_prefix('@')
+                    .or_else(|| step.strip_prefix("attribute::"));
+
+                match attribute {
+                    Some(_) if !attributes => {
+                        return Err("A selector cannot select attributes".to_owned())
+                    }
+                    Some(test) => parsed.attribute = Some(name_test(test, namespaces)?),
+                    None if step == "." => {}
+                    None => {
+                        let test = step.strip_prefix("child::").unwrap_or(step);
+                        parsed.steps.push(name_test(test, namespaces)?);
+                    }
+                }
+            }
+
+            paths.push(parsed);
+        }
+
+        Ok(XPath { paths })
+    }
+
+    /// Whether the element at the end of `names` is selected, where `names`
+    /// are the names from below the context element down to it.
+    pub(crate) fn selects(&self, names: &[&QName]) -> bool {
+        self.paths
+            .iter()
+            .any(|path| path.attribute.is_none() && path.matches(names))
+    }
+
+    /// Whether an attribute of the element at the end of `names` is
+    /// selected.
+    pub(crate) fn selects_attribute(&self, names: &[&QName], attribute: &QName) -> bool {
+        self.paths.iter().any(|path| match path.attribute {
+            Some(ref test) => test.matches(attribute) && path.matches(names),
+            None => false,
+        })
+    }
+}
+
+fn name_test(test: &str, namespaces: &HashMap<String, String>) -> Result<NameTest, String> {
+    if test == "*" {
+        return Ok(NameTest::Any);
+    }
+
+    let (prefix, local) = match test.find(':') {
+        Some(idx) => (Some(&test[..idx]), &test[idx + 1..]),
+        None => (None, test),
+    };
+    let namespace = match prefix {
+        Some(prefix) if is_ncname(prefix) => match namespaces.get(prefix) {
+            Some(namespace) => Some(namespace.as_str()),
+            None => return Err(format!("The prefix {} is not declared", prefix)),
+        },
+        Some(_) => return Err(format!("{:?} is not a name test", test)),
+        None => None,
+    };
+
+    match local {
+        "*" if prefix.is_some() => Ok(NameTest::Namespace(namespace.map(str::to_owned))),
+        local if is_ncname(local) => Ok(NameTest::Name(QName::new(namespace, local))),
+        _ => Err(format!("{:?} is not a name test", test)),
+    }
+}
@@ -0,0 +1,212 @@
+use xsd::shared::QName;
+use xsd::{constraints, Error, Parser, Validator};
+
+const TEST: &str = "urn:test";
+
+const SCHEMA: &str = r###"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:t="urn:test" targetNamespace="urn:test">
+ <xsd:annotation><xsd:documentation>Attribute components</xsd:documentation></xsd:annotation>
+ <xsd:notation name="png" public="image/png"/>
+ <xsd:attribute name="lang" type="xsd:language"/>
+ <xsd:attribute name="version" type="xsd:int" fixed="1"/>
+ <xsd:attributeGroup name="common">
+  <xsd:attribute ref="t:lang"/>
+  <xsd:attribute name="stamp" type="xsd:date"/>
+  <xsd:anyAttribute namespace="##other" processContents="lax"/>
+ </xsd:attributeGroup>
+ <xsd:complexType name="doc">
+  <xsd:annotation><xsd:documentation>A document.</xsd:documentation></xsd:annotation>
+  <xsd:sequence>
+   <xsd:element name="part" minOccurs="0" maxOccurs="unbounded">
+    <xsd:complexType>
+     <xsd:anyAttribute namespace="##targetNamespace" processContents="strict"/>
+    </xsd:complexType>
+   </xsd:element>
+  </xsd:sequence>
+  <xsd:attributeGroup ref="t:common"/>
+  <xsd:attribute ref="t:version" use="required"/>
+ </xsd:complexType>
+ <xsd:element name="doc" type="t:doc"/>
+</xsd:schema>"###;
+
+fn violations(instance: &str) -> Vec<(&'static str, u32)> {
+    let parser = Parser::parse_str(SCHEMA, Some("doc.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+
+    Validator::new(&schema_set)
+        .validate_str(instance, Some("doc.xml"))
+        .unwrap()
+        .iter()
+        .map(|d| match d.error {
+            Error::Invalid { rule, ref span, .. } => (rule, span.line),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+fn constraint_violations(body: &str) -> Vec<(&'static str, u32)> {
+    let contents = format!(
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+            xmlns:t="urn:test" targetNamespace="urn:test">{}
+</xsd:schema>"#,
+        body
+    );
+    let parser = Parser::parse_str(&contents, Some("constraints.xsd")).unwrap();
+
+    constraints::check(&parser.schema_set())
+        .iter()
+        .map(|d| match d.error {
+            Error::Constraint { rule, ref span, .. } => (rule, span.line),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn global_components() {
+    let parser = Parser::parse_str(SCHEMA, Some("doc.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+
+    assert_eq!(parser.root().annotations.len(), 1);
+    assert!(schema_set
+        .attribute(&QName::new(Some(TEST), "lang"))
+        .is_some());
+    assert!(schema_set
+        .attribute_group(&QName::new(Some(TEST), "common"))
+        .is_some());
+    assert_eq!(
+        schema_set
+            .notation(&QName::new(Some(TEST), "png"))
+            .unwrap()
+            .component
+            .public
+            .as_deref(),
+        Some("image/png")
+    );
+}
+
+#[test]
+fn valid_instance() {
+    let found = violations(
+        r#"<t:doc xmlns:t="urn:test" xmlns:o="urn:other" t:version="1" t:lang="en"
+    stamp="2024-01-01" o:x="anything">
+ <part t:lang="fr"/>
+</t:doc>"#,
+    );
+
+    assert!(found.is_empty(), "{:?}", found);
+}
+
+#[test]
+fn references_and_groups() {
+    let found = violations(
+        r#"<t:doc xmlns:t="urn:test" t:version="2" t:lang="not a language" stamp="x"/>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("cvc-attribute.4", 1),
+            ("cvc-datatype-valid.1.2.1", 1),
+            ("cvc-datatype-valid.1.2.1", 1),
+        ]
+    );
+}
+
+#[test]
+fn required_reference() {
+    let found = violations(r#"<t:doc xmlns:t="urn:test"/>"#);
+
+    assert_eq!(found, vec![("cvc-complex-type.4", 1)]);
+}
+
+#[test]
+fn wildcards() {
+    let found = violations(
+        r#"<t:doc xmlns:t="urn:test" xmlns:o="urn:other" t:version="1" t:extra="1" o:x="1">
+ <part t:lang="fr" t:size="3" other="1" o:x="1"/>
+</t:doc>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("cvc-complex-type.3.2.2", 1),
+            ("cvc-complex-type.3.2.2", 2),
+            ("cvc-complex-type.3.2.2", 2),
+            ("cvc-complex-type.3.2.2", 2),
+        ]
+    );
+}
+
+#[test]
+fn unresolved_references() {
+    let found = constraint_violations(
+        r#"
+ <xsd:complexType name="a">
+  <xsd:attribute ref="t:missing"/>
+  <xsd:attributeGroup ref="t:missing"/>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(found, vec![("src-resolve", 4), ("src-resolve", 5)]);
+}
+
+#[test]
+fn attribute_constraints() {
+    let found = constraint_violations(
+        r#"
+ <xsd:attribute name="a" type="xsd:int"/>
+ <xsd:attribute name="a" type="xsd:int"/>
+ <xsd:complexType name="c"/>
+ <xsd:attribute name="b" type="t:c"/>
+ <xsd:attributeGroup name="g"><xsd:attributeGroup ref="t:h"/></xsd:attributeGroup>
+ <xsd:attributeGroup name="h"><xsd:attributeGroup ref="t:g"/></xsd:attributeGroup>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("sch-props-correct.2", 4),
+            ("a-props-correct.1", 6),
+            ("src-attribute_group.3", 7),
+            ("src-attribute_group.3", 8),
+        ]
+    );
+}
+
+#[test]
+fn single_id() {
+    let found = constraint_violations(
+        r#"
+ <xsd:attribute name="key" type="xsd:ID"/>
+ <xsd:simpleType name="code"><xsd:restriction base="xsd:ID"/></xsd:simpleType>
+ <xsd:attributeGroup name="ids">
+  <xsd:attribute ref="t:key"/>
+  <xsd:attribute name="code" type="t:code"/>
+ </xsd:attributeGroup>
+ <xsd:complexType name="base"><xsd:attribute name="id" type="xsd:ID"/></xsd:complexType>
+ <xsd:complexType name="derived">
+  <xsd:complexContent>
+   <xsd:extension base="t:base">
+    <xsd:attribute name="other">
+     <xsd:simpleType><xsd:restriction base="t:code"/></xsd:simpleType>
+    </xsd:attribute>
+   </xsd:extension>
+  </xsd:complexContent>
+ </xsd:complexType>
+ <xsd:complexType name="replaced">
+  <xsd:complexContent>
+   <xsd:restriction base="t:base">
+    <xsd:attribute name="id" type="xsd:string"/>
+    <xsd:attribute name="code" type="t:code"/>
+   </xsd:restriction>
+  </xsd:complexContent>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![("ct-props-correct.5", 10), ("ag-props-correct.3", 5)]
+    );
+}
@@ -0,0 +1,119 @@
+use xsd::{Catalog, CatalogResolver, ChainResolver, Elements, Error, FileResolver, Parser};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn testdata(path: &str) -> String {
+    format!("{}/tests/testdata/catalog/{}", WORK_DIR, path)
+}
+
+fn resolver() -> CatalogResolver {
+    let mut resolver = CatalogResolver::new();
+    resolver.load(&testdata("catalog.xml")).unwrap();
+    resolver
+}
+
+#[test]
+fn system_entry() {
+    assert_eq!(
+        resolver()
+            .resolve_system("http://www.w3.org/2001/xml.xsd")
+            .unwrap(),
+        Some(testdata("w3c/xml.xsd"))
+    );
+}
+
+#[test]
+fn rewrite_uri_entry() {
+    assert_eq!(
+        resolver()
+            .resolve_uri("http://partner.example.com/schemas/v2/order.xsd")
+            .unwrap(),
+        Some(testdata("partner/v2/order.xsd"))
+    );
+}
+
+#[test]
+fn next_catalog_skips_missing_catalogs() {
+    assert_eq!(
+        resolver()
+            .resolve_uri("http://example.com/next.xsd")
+            .unwrap(),
+        Some(testdata("more/next.xsd"))
+    );
+}
+
+#[test]
+fn delegation_replaces_remaining_catalogs() {
+    let resolver = resolver();
+
+    assert_eq!(
+        resolver.resolve_uri("urn:example:delegated:one").unwrap(),
+        Some(testdata("one.xsd"))
+    );
+    assert_eq!(
+        resolver.resolve_uri("urn:example:delegated:two").unwrap(),
+        None
+    );
+}
+
+#[test]
+fn longest_rewrite_wins() {
+    let catalog = Catalog::parse(
+        "file:///etc/xml/catalog.xml",
+        r#"<catalog xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+            <rewriteSystem systemIdStartString="http://example.com/" rewritePrefix="../short/"/>
+            <rewriteSystem systemIdStartString="http://example.com/long/" rewritePrefix="long/"/>
+            <systemSuffix systemIdSuffix="/common.xsd" uri="common.xsd"/>
+        </catalog>"#,
+    )
+    .unwrap();
+
+    let mut resolver = CatalogResolver::new();
+    resolver.push(catalog);
+
+    assert_eq!(
+        resolver
+            .resolve_system("http://example.com/long/a.xsd")
+            .unwrap(),
+        Some("file:///etc/xml/long/a.xsd".to_owned())
+    );
+    assert_eq!(
+        resolver.resolve_system("http://example.com/b.xsd").unwrap(),
+        Some("file:///etc/short/b.xsd".to_owned())
+    );
+    assert_eq!(
+        resolver
+            .resolve_system("http://example.org/x/common.xsd")
+            .unwrap(),
+        Some("file:///etc/xml/common.xsd".to_owned())
+    );
+}
+
+#[test]
+fn not_a_catalog() {
+    match Catalog::parse("catalog.xml", "<catalog/>") {
+        Err(Error::InvalidCatalog { span }) => assert_eq!(span.document, "catalog.xml"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_with_catalog() {
+    let mut resolver = ChainResolver::new();
+    resolver.push(self::resolver());
+    resolver.push(FileResolver);
+
+    let parser = Parser::parse_with_resolver(&testdata("root.xsd"), &resolver).unwrap();
+
+    let names: Vec<&str> = parser
+        .documents
+        .iter()
+        .flat_map(|d| &d.components)
+        .filter_map(|e| match e {
+            Elements::Element(e) => e.name.as_deref(),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(names, vec!["root", "lang", "order"]);
+}
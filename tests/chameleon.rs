@@ -0,0 +1,94 @@
+use xsd::shared::QName;
+use xsd::{Parser, SubstitutionGroups};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+const MAIN: &str = "http://example.org/main";
+const OTHER: &str = "http://example.org/other";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema";
+
+fn parse(file: &str) -> Parser {
+    Parser::parse(format!("{}/tests/testdata/chameleon/{}", WORK_DIR, file)).unwrap()
+}
+
+#[test]
+fn included_components_take_the_including_namespace() {
+    let parser = parse("main.xsd");
+    let schema_set = parser.schema_set();
+
+    assert!(schema_set
+        .complex_type(&QName::new(Some(MAIN), "Address"))
+        .is_some());
+    assert!(schema_set
+        .complex_type(&QName::new(None, "Address"))
+        .is_none());
+
+    let address = schema_set
+        .element(&QName::new(Some(MAIN), "address"))
+        .unwrap();
+    assert_eq!(
+        address.resolve(address.component.r#type.as_ref().unwrap()),
+        Some(QName::new(Some(MAIN), "Address"))
+    );
+}
+
+#[test]
+fn default_namespace_is_not_rewritten() {
+    let parser = parse("main.xsd");
+    let schema_set = parser.schema_set();
+
+    let note = schema_set.element(&QName::new(Some(MAIN), "note")).unwrap();
+    assert_eq!(
+        note.resolve(note.component.r#type.as_ref().unwrap()),
+        Some(QName::new(Some(XSD), "string"))
+    );
+}
+
+#[test]
+fn substitution_groups_follow_the_chameleon_namespace() {
+    let parser = parse("main.xsd");
+    let schema_set = parser.schema_set();
+    let groups = SubstitutionGroups::new(&schema_set);
+
+    let members: Vec<String> = groups
+        .members(&QName::new(Some(MAIN), "address"))
+        .iter()
+        .map(|e| e.name.to_string())
+        .collect();
+    assert_eq!(members, vec!["{http://example.org/main}homeAddress"]);
+}
+
+#[test]
+fn included_once_per_namespace() {
+    let parser = parse("both.xsd");
+    let schema_set = parser.schema_set();
+
+    assert!(schema_set
+        .complex_type(&QName::new(Some(MAIN), "Address"))
+        .is_some());
+    assert!(schema_set
+        .complex_type(&QName::new(Some(OTHER), "Address"))
+        .is_some());
+    assert_eq!(schema_set.complex_types().len(), 2);
+}
+
+#[test]
+fn components_know_their_document() {
+    let parser = parse("main.xsd");
+    let schema_set = parser.schema_set();
+
+    let order = schema_set
+        .element(&QName::new(Some(MAIN), "order"))
+        .unwrap();
+    assert!(order.document.location.ends_with("chameleon/main.xsd"));
+    assert!(!order.document.is_chameleon());
+
+    let address = schema_set
+        .complex_type(&QName::new(Some(MAIN), "Address"))
+        .unwrap();
+    assert!(address.document.location.ends_with("chameleon/types.xsd"));
+    assert!(address.document.is_chameleon());
+    assert_eq!(address.document.schema.target_namespace, None);
+
+    let included: Vec<Option<usize>> = parser.root().includes.iter().map(|i| i.document).collect();
+    assert_eq!(included, vec![Some(1), Some(2)]);
+}
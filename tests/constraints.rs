@@ -0,0 +1,472 @@
+use xsd::{constraints, Error, Parser};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn violations(body: &str) -> Vec<(&'static str, u32)> {
+    let contents = format!(
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+            xmlns:t="urn:test" targetNamespace="urn:test">{}
+</xsd:schema>"#,
+        body
+    );
+    let parser = Parser::parse_str(&contents, Some("constraints.xsd")).unwrap();
+
+    constraints::check(&parser.schema_set())
+        .iter()
+        .map(|d| match d.error {
+            Error::Constraint { rule, ref span, .. } => (rule, span.line),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn fixtures_pass() {
+    for fixture in &["xsd1.xml", "vehicles.xsd"] {
+        let parser = Parser::parse(format!("{}/tests/testdata/{}", WORK_DIR, fixture)).unwrap();
+
+        let diagnostics = constraints::check(&parser.schema_set());
+
+        assert!(diagnostics.is_empty(), "{}: {:?}", fixture, diagnostics);
+    }
+}
+
+#[test]
+fn duplicate_names() {
+    let found = violations(
+        r#"
+ <xsd:element name="a"/>
+ <xsd:element name="a"/>
+ <xsd:simpleType name="b"><xsd:restriction base="xsd:string"/></xsd:simpleType>
+ <xsd:complexType name="b"/>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![("sch-props-correct.2", 4), ("sch-props-correct.2", 6)]
+    );
+}
+
+#[test]
+fn unresolved_and_circular_types() {
+    let found = violations(
+        r#"
+ <xsd:element name="a" type="t:missing"/>
+ <xsd:simpleType name="b"><xsd:restriction base="t:c"/></xsd:simpleType>
+ <xsd:simpleType name="c"><xsd:restriction base="t:b"/></xsd:simpleType>
+ <xsd:complexType name="d">
+  <xsd:sequence><xsd:element name="e" type="u:e"/></xsd:sequence>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("st-props-correct.2", 4),
+            ("st-props-correct.2", 5),
+            ("src-resolve.4", 7),
+            ("src-resolve", 3),
+        ]
+    );
+}
+
+#[test]
+fn facets() {
+    let found = violations(
+        r#"
+ <xsd:simpleType name="a">
+  <xsd:restriction base="xsd:string">
+   <xsd:minLength value="5"/>
+   <xsd:maxLength value="2"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="b">
+  <xsd:restriction base="xsd:int"><xsd:maxLength value="2"/></xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="c">
+  <xsd:restriction base="xsd:decimal">
+   <xsd:totalDigits value="4"/>
+   <xsd:minInclusive value="10"/>
+   <xsd:maxInclusive value="1.5"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="d">
+  <xsd:restriction base="t:c"><xsd:totalDigits value="6"/></xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="e">
+  <xsd:restriction base="xsd:date">
+   <xsd:minInclusive value="2001-01-01Z"/>
+   <xsd:maxInclusive value="2000-12-31-13:00"/>
+  </xsd:restriction>
+ </xsd:simpleType>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("minLength-less-than-equal-to-maxLength", 4),
+            ("cos-applicable-facets", 10),
+            ("minInclusive-less-than-equal-to-maxInclusive", 13),
+            ("totalDigits-valid-restriction", 20),
+            ("minInclusive-less-than-equal-to-maxInclusive", 23),
+        ]
+    );
+}
+
+#[test]
+fn invalid_patterns() {
+    let found = violations(
+        r#"
+ <xsd:simpleType name="a">
+  <xsd:restriction base="xsd:string">
+   <xsd:pattern value="[a-z]+"/>
+   <xsd:pattern value="(unclosed"/>
+  </xsd:restriction>
+ </xsd:simpleType>"#,
+    );
+
+    assert_eq!(found, vec![("s4s-att-invalid-value", 4)]);
+}
+
+#[test]
+fn identity_constraints() {
+    let found = violations(
+        r#"
+ <xsd:element name="a">
+  <xsd:key name="k">
+   <xsd:selector xpath="t:b/@c"/>
+   <xsd:field xpath="@c"/>
+  </xsd:key>
+  <xsd:keyref name="r" refer="t:k">
+   <xsd:selector xpath="t:d"/>
+   <xsd:field xpath="@e"/>
+   <xsd:field xpath="u:f"/>
+  </xsd:keyref>
+  <xsd:keyref name="s" refer="t:missing">
+   <xsd:selector xpath=".//t:d"/>
+   <xsd:field xpath="@e"/>
+  </xsd:keyref>
+ </xsd:element>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("c-selector-xpath", 4),
+            ("c-fields-xpaths", 8),
+            ("c-props-correct.2", 8),
+            ("src-resolve", 13),
+        ]
+    );
+}
+
+#[test]
+fn derivation() {
+    let found = violations(
+        r##"
+ <xsd:complexType name="sealed" final="#all">
+  <xsd:sequence><xsd:element name="a" type="xsd:string"/></xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="extended">
+  <xsd:complexContent><xsd:extension base="t:sealed"/></xsd:complexContent>
+ </xsd:complexType>
+ <xsd:complexType name="fromSimple">
+  <xsd:complexContent><xsd:extension base="xsd:string"/></xsd:complexContent>
+ </xsd:complexType>
+ <xsd:complexType name="fromComplex">
+  <xsd:simpleContent><xsd:extension base="t:sealed"/></xsd:simpleContent>
+ </xsd:complexType>"##,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("cos-ct-extends.1.1", 7),
+            ("src-ct.1", 10),
+            ("src-ct.2", 13)
+        ]
+    );
+}
+
+#[test]
+fn particle_restriction() {
+    let found = violations(
+        r#"
+ <xsd:complexType name="base">
+  <xsd:sequence>
+   <xsd:element name="a" type="xsd:string"/>
+   <xsd:element name="b" type="xsd:string" minOccurs="0" maxOccurs="3"/>
+   <xsd:any namespace="urn:other" minOccurs="0"/>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="valid">
+  <xsd:complexContent>
+   <xsd:restriction base="t:base">
+    <xsd:sequence>
+     <xsd:element name="a" type="xsd:token"/>
+     <xsd:element name="b" type="xsd:string" maxOccurs="2"/>
+    </xsd:sequence>
+   </xsd:restriction>
+  </xsd:complexContent>
+ </xsd:complexType>
+ <xsd:complexType name="wider">
+  <xsd:complexContent>
+   <xsd:restriction base="t:base">
+    <xsd:sequence>
+     <xsd:element name="a" type="xsd:string"/>
+     <xsd:element name="b" type="xsd:string" maxOccurs="unbounded"/>
+    </xsd:sequence>
+   </xsd:restriction>
+  </xsd:complexContent>
+ </xsd:complexType>
+ <xsd:complexType name="skipsRequired">
+  <xsd:complexContent>
+   <xsd:restriction base="t:base">
+    <xsd:sequence><xsd:element name="b" type="xsd:string"/></xsd:sequence>
+   </xsd:restriction>
+  </xsd:complexContent>
+ </xsd:complexType>
+ <xsd:complexType name="otherType">
+  <xsd:complexContent>
+   <xsd:restriction base="t:base">
+    <xsd:sequence><xsd:element name="a" type="xsd:int"/></xsd:sequence>
+   </xsd:restriction>
+  </xsd:complexContent>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("rcase-Recurse.2", 25),
+            ("rcase-Recurse.2", 33),
+            ("rcase-Recurse.2", 40),
+        ]
+    );
+}
+
+#[test]
+fn element_declarations_consistent() {
+    let found = violations(
+        r#"
+ <xsd:complexType name="base">
+  <xsd:sequence><xsd:element name="a" type="xsd:string"/></xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="derived">
+  <xsd:complexContent>
+   <xsd:extension base="t:base">
+    <xsd:choice>
+     <xsd:element name="b" type="xsd:string"/>
+     <xsd:element name="a" type="xsd:int"/>
+    </xsd:choice>
+   </xsd:extension>
+  </xsd:complexContent>
+ </xsd:complexType>
+ <xsd:element name="head" type="xsd:string"/>
+ <xsd:element name="member" type="xsd:int" substitutionGroup="t:head"/>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![("cos-element-consistent", 11), ("e-props-correct.4", 17)]
+    );
+}
+
+#[test]
+fn rule_in_message() {
+    let contents = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:complexType name="a">
+  <xsd:complexContent><xsd:extension base="xsd:int"/></xsd:complexContent>
+ </xsd:complexType>
+</xsd:schema>"#;
+    let parser = Parser::parse_str(contents, Some("rule.xsd")).unwrap();
+    let diagnostics = constraints::check(&parser.schema_set());
+
+    assert_eq!(
+        diagnostics.iter().next().unwrap().to_string(),
+        "error: rule.xsd:3:23: src-ct.1: The base of complex content, xsd:int, is a simple type"
+    );
+}
+
+fn ambiguities(body: &str) -> Vec<(String, u32, u32)> {
+    let contents = format!(
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+            xmlns:t="urn:test" targetNamespace="urn:test" elementFormDefault="qualified">{}
+</xsd:schema>"#,
+        body
+    );
+    let parser = Parser::parse_str(&contents, Some("upa.xsd")).unwrap();
+
+    constraints::check(&parser.schema_set())
+        .iter()
+        .map(|d| match d.error {
+            Error::AmbiguousContentModel {
+                ref term,
+                ref first,
+                ref span,
+            } => (term.clone(), first.line, span.line),
+            ref other => panic!("unexpected error: {}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn unambiguous_content_models() {
+    let found = ambiguities(
+        r###"
+ <xsd:complexType name="a">
+  <xsd:sequence>
+   <xsd:element name="x" minOccurs="2" maxOccurs="2"/>
+   <xsd:element name="x"/>
+   <xsd:choice maxOccurs="unbounded">
+    <xsd:element name="y"/>
+    <xsd:element name="z"/>
+   </xsd:choice>
+   <xsd:any namespace="##other" minOccurs="0"/>
+  </xsd:sequence>
+ </xsd:complexType>"###,
+    );
+
+    assert!(found.is_empty(), "{:?}", found);
+}
+
+#[test]
+fn optional_element_followed_by_same_name() {
+    let found = ambiguities(
+        r#"
+ <xsd:complexType name="a">
+  <xsd:sequence>
+   <xsd:element name="x" minOccurs="0"/>
+   <xsd:element name="x"/>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="b">
+  <xsd:sequence>
+   <xsd:element name="y" maxOccurs="3"/>
+   <xsd:element name="y"/>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="c">
+  <xsd:choice>
+   <xsd:sequence><xsd:element name="z"/><xsd:element name="p"/></xsd:sequence>
+   <xsd:sequence><xsd:element name="z"/><xsd:element name="q"/></xsd:sequence>
+  </xsd:choice>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("Element {urn:test}x".to_owned(), 5, 6),
+            ("Element {urn:test}y".to_owned(), 11, 12),
+            ("Element {urn:test}z".to_owned(), 17, 18),
+        ]
+    );
+}
+
+#[test]
+fn wildcard_overlap() {
+    let found = ambiguities(
+        r###"
+ <xsd:complexType name="a">
+  <xsd:sequence>
+   <xsd:any namespace="##targetNamespace" minOccurs="0"/>
+   <xsd:element name="x"/>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="b">
+  <xsd:sequence>
+   <xsd:any namespace="urn:one urn:two" minOccurs="0"/>
+   <xsd:any namespace="##other"/>
+  </xsd:sequence>
+ </xsd:complexType>"###,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("Element {urn:test}x".to_owned(), 5, 6),
+            ("An element matched by a wildcard".to_owned(), 11, 12),
+        ]
+    );
+}
+
+#[test]
+fn substitution_group_members() {
+    let found = ambiguities(
+        r#"
+ <xsd:element name="head"/>
+ <xsd:element name="member" substitutionGroup="t:head"/>
+ <xsd:complexType name="a">
+  <xsd:sequence>
+   <xsd:element ref="t:head" minOccurs="0"/>
+   <xsd:element ref="t:member"/>
+  </xsd:sequence>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(found, vec![("Element {urn:test}member".to_owned(), 7, 8)]);
+}
+
+#[test]
+fn inline_restriction_base() {
+    let found = violations(
+        r#"
+ <xsd:simpleType name="a">
+  <xsd:restriction>
+   <xsd:simpleType><xsd:restriction base="xsd:string"/></xsd:simpleType>
+   <xsd:maxLength value="3"/>
+   <xsd:fractionDigits value="1"/>
+  </xsd:restriction>
+ </xsd:simpleType>"#,
+    );
+
+    assert_eq!(found, vec![("cos-applicable-facets", 4)]);
+}
+
+#[test]
+fn model_groups() {
+    let found = violations(
+        r#"
+ <xsd:group name="a"><xsd:sequence><xsd:group ref="t:b"/></xsd:sequence></xsd:group>
+ <xsd:group name="b"><xsd:choice><xsd:group ref="t:a"/></xsd:choice></xsd:group>
+ <xsd:group name="c"><xsd:sequence><xsd:element name="x"/></xsd:sequence></xsd:group>
+ <xsd:group name="c"><xsd:sequence><xsd:group ref="t:missing"/></xsd:sequence></xsd:group>
+ <xsd:complexType name="d">
+  <xsd:sequence>
+   <xsd:element name="e">
+    <xsd:complexType><xsd:group ref="t:missing"/></xsd:complexType>
+   </xsd:element>
+  </xsd:sequence>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("sch-props-correct.2", 6),
+            ("src-resolve", 10),
+            ("mg-props-correct.2", 3),
+            ("mg-props-correct.2", 4),
+            ("src-resolve", 6),
+        ]
+    );
+}
+
+#[test]
+fn circular_derivation() {
+    let found = violations(
+        r#"
+ <xsd:complexType name="a">
+  <xsd:complexContent><xsd:extension base="t:b"/></xsd:complexContent>
+ </xsd:complexType>
+ <xsd:complexType name="b">
+  <xsd:complexContent><xsd:restriction base="t:a"/></xsd:complexContent>
+ </xsd:complexType>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![("ct-props-correct.3", 3), ("ct-props-correct.3", 6)]
+    );
+}
@@ -0,0 +1,207 @@
+use std::time::{Duration, Instant};
+
+use xsd::complex_type::NestedParticle;
+use xsd::shared::QName;
+use xsd::{ContentModel, Parser, SchemaSet};
+
+const SCHEMA: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:t="urn:test" targetNamespace="urn:test" elementFormDefault="qualified">
+ <xsd:complexType name="list">
+  <xsd:sequence>
+   <xsd:element name="head" type="xsd:string" minOccurs="0"/>
+   <xsd:element name="row" type="xsd:string" minOccurs="2" maxOccurs="1000"/>
+   <xsd:choice minOccurs="0">
+    <xsd:element name="total" type="xsd:int"/>
+    <xsd:element name="none" type="xsd:string"/>
+   </xsd:choice>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="person">
+  <xsd:all>
+   <xsd:element name="name" type="xsd:string"/>
+   <xsd:element name="age" type="xsd:int" minOccurs="0"/>
+   <xsd:element name="email" type="xsd:string"/>
+  </xsd:all>
+ </xsd:complexType>
+ <xsd:group name="pair">
+  <xsd:sequence>
+   <xsd:element name="key" type="xsd:string"/>
+   <xsd:element name="value" type="xsd:string"/>
+  </xsd:sequence>
+ </xsd:group>
+ <xsd:complexType name="map">
+  <xsd:sequence>
+   <xsd:group ref="t:pair" minOccurs="1" maxOccurs="unbounded"/>
+   <xsd:element ref="t:shape" minOccurs="0"/>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="mixed">
+  <xsd:sequence>
+   <xsd:choice>
+    <xsd:element name="a" type="xsd:string"/>
+    <xsd:element name="b" type="xsd:string"/>
+   </xsd:choice>
+   <xsd:element name="c" type="xsd:string"/>
+   <xsd:group ref="t:pair"/>
+   <xsd:any namespace="urn:other" processContents="skip"/>
+   <xsd:sequence><xsd:element name="d" type="xsd:string"/></xsd:sequence>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="nested">
+  <xsd:sequence minOccurs="0" maxOccurs="10">
+   <xsd:sequence minOccurs="0" maxOccurs="100">
+    <xsd:element name="x" type="xsd:string" minOccurs="0" maxOccurs="3"/>
+    <xsd:element name="y" type="xsd:string" minOccurs="0"/>
+   </xsd:sequence>
+  </xsd:sequence>
+ </xsd:complexType>
+ <xsd:element name="shape" abstract="true"/>
+ <xsd:element name="circle" substitutionGroup="t:shape"/>
+</xsd:schema>"#;
+
+fn model<'a>(schema_set: &SchemaSet<'a>, name: &str) -> ContentModel<'a> {
+    let global = schema_set
+        .complex_type(&QName::new(Some("urn:test"), name))
+        .unwrap();
+
+    ContentModel::of(schema_set, global.component, global.document).unwrap()
+}
+
+fn names(names: &str) -> Vec<QName> {
+    names
+        .split_whitespace()
+        .map(|name| QName::new(Some("urn:test"), name))
+        .collect()
+}
+
+#[test]
+fn sequences_and_counters() {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let list = model(&schema_set, "list");
+
+    assert!(list.is_valid(&names("row row")));
+    assert!(list.is_valid(&names("head row row row total")));
+    assert!(!list.is_valid(&names("head row")));
+    assert!(!list.is_valid(&names("row row total none")));
+    assert!(!list.is_valid(&names("row head row")));
+
+    let rows = vec![QName::new(Some("urn:test"), "row"); 1000];
+    assert!(list.is_valid(&rows));
+
+    let rows = vec![QName::new(Some("urn:test"), "row"); 1001];
+    assert!(!list.is_valid(&rows));
+}
+
+#[test]
+fn expected_and_complete() {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let list = model(&schema_set, "list");
+    let mut matcher = list.matcher();
+
+    let expected = |matcher: &xsd::content_model::Matcher| -> Vec<String> {
+        matcher
+            .expected()
+            .iter()
+            .map(|p| p.element_name().unwrap().name)
+            .collect()
+    };
+
+    assert_eq!(expected(&matcher), vec!["head", "row"]);
+    assert!(!matcher.is_complete());
+
+    assert!(matcher.next(&names("row")[0]).is_some());
+    assert_eq!(expected(&matcher), vec!["row"]);
+    assert!(matcher.next(&names("total")[0]).is_none());
+
+    assert!(matcher.next(&names("row")[0]).is_some());
+    assert!(matcher.is_complete());
+    assert_eq!(expected(&matcher), vec!["row", "total", "none"]);
+}
+
+#[test]
+fn all_groups() {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let person = model(&schema_set, "person");
+
+    assert!(person.is_valid(&names("name email")));
+    assert!(person.is_valid(&names("email age name")));
+    assert!(!person.is_valid(&names("name")));
+    assert!(!person.is_valid(&names("name name email")));
+    assert!(!person.is_valid(&names("age email name age")));
+}
+
+#[test]
+fn group_references_and_substitution() {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let map = model(&schema_set, "map");
+
+    assert!(map.is_valid(&names("key value")));
+    assert!(map.is_valid(&names("key value key value circle")));
+    assert!(!map.is_valid(&names("key value key")));
+    assert!(!map.is_valid(&names("value key")));
+    assert!(!map.is_valid(&names("")));
+
+    let mut matcher = map.matcher();
+    for name in &names("key value") {
+        matcher.next(name).unwrap();
+    }
+    let circle = matcher.next(&names("circle")[0]).unwrap();
+    assert_eq!(circle.element_name().unwrap().name, "shape");
+}
+
+#[test]
+fn particles_in_document_order() {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let mixed = schema_set
+        .complex_type(&QName::new(Some("urn:test"), "mixed"))
+        .unwrap();
+
+    let kinds: Vec<&str> = mixed
+        .component
+        .sequence
+        .as_ref()
+        .unwrap()
+        .particles
+        .iter()
+        .map(|particle| match particle {
+            NestedParticle::Element(_) => "element",
+            NestedParticle::Any(_) => "any",
+            NestedParticle::Choice(_) => "choice",
+            NestedParticle::Sequence(_) => "sequence",
+            NestedParticle::Group(_) => "group",
+        })
+        .collect();
+    assert_eq!(kinds, vec!["choice", "element", "group", "any", "sequence"]);
+
+    let mixed = model(&schema_set, "mixed");
+    let other = QName::new(Some("urn:other"), "note");
+    let mut instance = names("b c key value");
+    instance.push(other);
+    instance.extend(names("d"));
+
+    assert!(mixed.is_valid(&instance));
+    assert!(!mixed.is_valid(&names("c b key value d")));
+}
+
+#[test]
+fn nested_counters_match_in_linear_time() {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let nested = model(&schema_set, "nested");
+    let children = names(&"y ".repeat(1000));
+
+    let started = Instant::now();
+    assert!(nested.is_valid(&children));
+    assert!(nested.is_valid(&names(&"x x x y ".repeat(250))));
+    assert!(!nested.is_valid(&names(&"y ".repeat(1001))));
+    assert!(
+        started.elapsed() < Duration::from_secs(2),
+        "{:?}",
+        started.elapsed()
+    );
+}
@@ -0,0 +1,63 @@
+use xsd::shared::QName;
+use xsd::{Parser, SubstitutionGroups};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn parse() -> Parser {
+    Parser::parse(format!("{}/tests/testdata/cycle/a.xsd", WORK_DIR)).unwrap()
+}
+
+#[test]
+fn mutual_imports_load_each_document_once() {
+    let parser = parse();
+
+    let locations: Vec<&str> = parser
+        .documents
+        .iter()
+        .map(|d| d.location.rsplit('/').next().unwrap())
+        .collect();
+    assert_eq!(locations, vec!["a.xsd", "b.xsd"]);
+
+    // Both imports of b.xsd and b.xsd's import of a.xsd point at the
+    // documents that were already loaded.
+    let imports: Vec<Option<usize>> = parser
+        .documents
+        .iter()
+        .flat_map(|d| &d.imports)
+        .map(|i| i.document)
+        .collect();
+    assert_eq!(imports, vec![Some(1), Some(1), Some(0)]);
+
+    assert_eq!(parser.root().includes[0].document, Some(0));
+}
+
+#[test]
+fn components_keep_their_namespace() {
+    let parser = parse();
+    let schema_set = parser.schema_set();
+
+    let names: Vec<String> = schema_set
+        .elements()
+        .iter()
+        .map(|e| e.name.to_string())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            "{http://example.org/a}a",
+            "{http://example.org/b}b",
+            "{http://example.org/b}c",
+        ]
+    );
+
+    let groups = SubstitutionGroups::new(&schema_set);
+    let members: Vec<String> = groups
+        .members(&QName::new(Some("http://example.org/b"), "b"))
+        .iter()
+        .map(|e| e.name.to_string())
+        .collect();
+    assert_eq!(
+        members,
+        vec!["{http://example.org/a}a", "{http://example.org/b}c"]
+    );
+}
@@ -0,0 +1,59 @@
+use std::cmp::Ordering;
+
+use xsd::Decimal;
+
+fn decimal(value: &str) -> Decimal {
+    Decimal::parse(value).unwrap()
+}
+
+#[test]
+fn canonical_forms() {
+    assert_eq!(decimal("+007.50").to_string(), "7.5");
+    assert_eq!(decimal("-.05").to_string(), "-0.05");
+    assert_eq!(decimal("-0.000").to_string(), "0");
+    assert_eq!(decimal("1200").to_string(), "1200");
+    assert_eq!(decimal("1200.").to_string(), "1200");
+    assert_eq!(Decimal::from(-42).to_string(), "-42");
+    assert_eq!(
+        decimal("123456789012345678901234567890.123456789012345678901").to_string(),
+        "123456789012345678901234567890.123456789012345678901"
+    );
+
+    for invalid in &["", ".", "1e3", "1.2.3", "--1", " 1", "INF"] {
+        assert!(Decimal::parse(invalid).is_none(), "{:?}", invalid);
+    }
+}
+
+#[test]
+fn exact_order() {
+    assert_eq!(decimal("1.0"), decimal("1"));
+    assert_eq!(decimal("-0"), decimal("0"));
+    assert!(decimal("0.1") < decimal("0.10000000000000000001"));
+    assert!(decimal("-10") < decimal("-9.99"));
+    assert!(decimal("-0.5") < decimal("0"));
+    assert_eq!(
+        decimal("18446744073709551616").cmp(&decimal("18446744073709551615.9")),
+        Ordering::Greater
+    );
+}
+
+#[test]
+fn digits() {
+    assert_eq!(
+        (
+            decimal("0.05").total_digits(),
+            decimal("0.05").fraction_digits()
+        ),
+        (2, 2)
+    );
+    assert_eq!(
+        (
+            decimal("120.50").total_digits(),
+            decimal("120.50").fraction_digits()
+        ),
+        (4, 1)
+    );
+    assert_eq!(decimal("0").total_digits(), 1);
+    assert!(decimal("3.000").is_integer());
+    assert!(!decimal("3.001").is_integer());
+}
@@ -0,0 +1,141 @@
+use xsd::{Elements, Error, Parser};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn element_names(parser: &Parser) -> Vec<&str> {
+    parser
+        .documents
+        .iter()
+        .flat_map(|d| &d.components)
+        .filter_map(|e| match e {
+            Elements::Element(e) => e.name.as_deref(),
+            _ => None,
+        })
+        .collect()
+}
+
+fn utf16(s: &str, bom: bool, to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    if bom {
+        bytes.extend(&to_bytes(0xFEFF));
+    }
+
+    for unit in s.encode_utf16() {
+        bytes.extend(&to_bytes(unit));
+    }
+
+    bytes
+}
+
+const SCHEMA: &str = r#"<?xml version="1.0" encoding="UTF-16"?>
+<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:element name="größe" type="xsd:string"/>
+</xsd:schema>"#;
+
+#[test]
+fn parse_str() {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+
+    assert_eq!(element_names(&parser), vec!["größe"]);
+    assert_eq!(parser.root().location, "");
+}
+
+#[test]
+fn parse_str_with_base_uri() {
+    let base = format!("{}/tests/testdata/nested/root.xsd", WORK_DIR);
+    let contents = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+        <xsd:include schemaLocation="c/e.xsd"/>
+    </xsd:schema>"#;
+
+    let parser = Parser::parse_str(contents, Some(&base)).unwrap();
+
+    assert_eq!(element_names(&parser), vec!["e"]);
+}
+
+#[test]
+fn parse_reader() {
+    let parser = Parser::parse_reader(SCHEMA.as_bytes(), None).unwrap();
+
+    assert_eq!(element_names(&parser), vec!["größe"]);
+}
+
+#[test]
+fn utf16_with_byte_order_mark() {
+    let le = utf16(SCHEMA, true, u16::to_le_bytes);
+    let be = utf16(SCHEMA, true, u16::to_be_bytes);
+
+    assert_eq!(
+        element_names(&Parser::parse_bytes(&le, None).unwrap()),
+        vec!["größe"]
+    );
+    assert_eq!(
+        element_names(&Parser::parse_bytes(&be, None).unwrap()),
+        vec!["größe"]
+    );
+}
+
+#[test]
+fn utf16_without_byte_order_mark() {
+    let le = utf16(SCHEMA, false, u16::to_le_bytes);
+
+    assert_eq!(
+        element_names(&Parser::parse_bytes(&le, None).unwrap()),
+        vec!["größe"]
+    );
+}
+
+#[test]
+fn declared_latin1() {
+    let mut contents = b"<?xml version='1.0' encoding='ISO-8859-1'?>
+<xsd:schema xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">
+ <xsd:element name=\"gr"
+        .to_vec();
+    contents.extend(&[0xF6, 0xDF]);
+    contents.extend(b"e\" type=\"xsd:string\"/>\n</xsd:schema>");
+
+    let parser = Parser::parse_bytes(&contents, None).unwrap();
+
+    assert_eq!(element_names(&parser), vec!["größe"]);
+}
+
+#[test]
+fn declared_windows_1252() {
+    let mut contents = b"<?xml version=\"1.0\" encoding=\"windows-1252\"?>
+<xsd:schema xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">
+ <xsd:element name=\"price"
+        .to_vec();
+    contents.push(0x80);
+    contents.extend(b"\" type=\"xsd:string\"/>\n</xsd:schema>");
+
+    let parser = Parser::parse_bytes(&contents, None).unwrap();
+
+    assert_eq!(element_names(&parser), vec!["price€"]);
+}
+
+#[test]
+fn unsupported_encoding() {
+    let contents = br#"<?xml version="1.0" encoding="EBCDIC-CP-US"?><schema/>"#;
+
+    match Parser::parse_bytes(contents, None) {
+        Err(Error::UnsupportedEncoding { encoding, .. }) => assert_eq!(encoding, "EBCDIC-CP-US"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_utf8() {
+    let contents = b"<xsd:schema xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">\xF6</xsd:schema>";
+
+    assert!(matches!(
+        Parser::parse_bytes(contents, None),
+        Err(Error::Utf8 { .. })
+    ));
+}
+
+#[test]
+fn stale_utf16_declaration() {
+    let parser = Parser::parse_bytes(SCHEMA.as_bytes(), None).unwrap();
+
+    assert_eq!(element_names(&parser), vec!["größe"]);
+}
@@ -0,0 +1,110 @@
+use roxmltree::Document;
+use xsd::hints::hints;
+use xsd::{Error, MemoryResolver, SchemaHints};
+
+const ORDER: &str = r###"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    targetNamespace="urn:order" elementFormDefault="qualified">
+ <xsd:element name="order">
+  <xsd:complexType>
+   <xsd:sequence>
+    <xsd:element name="item" type="xsd:string"/>
+    <xsd:any namespace="##local" processContents="strict" minOccurs="0"/>
+   </xsd:sequence>
+  </xsd:complexType>
+ </xsd:element>
+</xsd:schema>"###;
+
+const NOTE: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:element name="note" type="xsd:string"/>
+</xsd:schema>"#;
+
+const INSTANCE: &str = r#"<order xmlns="urn:order"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+    xsi:schemaLocation="urn:order order.xsd">
+ <item>pen</item>
+ <note xmlns="" xsi:noNamespaceSchemaLocation="note.xsd">fragile</note>
+</order>"#;
+
+fn resolver() -> MemoryResolver {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("schemas/order.xsd", ORDER);
+    resolver.insert("schemas/note.xsd", NOTE);
+    resolver
+}
+
+fn found(hints: SchemaHints, instance: &str) -> Vec<(String, u32)> {
+    hints
+        .validate_str(instance, Some("schemas/order.xml"), &resolver())
+        .unwrap()
+        .iter()
+        .map(|d| match d.error {
+            Error::Invalid { rule, ref span, .. } => (rule.to_owned(), span.line),
+            Error::SchemaNotFound {
+                ref location,
+                span: Some(ref span),
+            } => (location.clone(), span.line),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn follows_hints_on_nested_elements() {
+    assert_eq!(found(SchemaHints::new(), INSTANCE), vec![]);
+}
+
+#[test]
+fn forbidden_and_overridden_hints() {
+    assert_eq!(
+        found(SchemaHints::new().forbid(true), INSTANCE),
+        vec![("cvc-elt.1.a".to_owned(), 1)]
+    );
+
+    let overridden = SchemaHints::new()
+        .forbid(true)
+        .override_location(Some("urn:order"), "schemas/order.xsd");
+    assert_eq!(
+        found(overridden, INSTANCE),
+        vec![("cvc-complex-type.2.4.c".to_owned(), 5)]
+    );
+}
+
+#[test]
+fn missing_schema() {
+    let instance = INSTANCE.replace("order.xsd", "missing.xsd");
+
+    assert_eq!(
+        found(SchemaHints::new(), &instance),
+        vec![
+            ("schemas/missing.xsd".to_owned(), 3),
+            ("cvc-elt.1.a".to_owned(), 1),
+        ]
+    );
+}
+
+#[test]
+fn no_namespace_location_is_one_uri() {
+    let instance = r#"<note xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+    xsi:noNamespaceSchemaLocation="  my notes.xsd
+"/>"#;
+    let document = Document::parse(instance).unwrap();
+
+    let locations: Vec<_> = hints(&document, "note.xml", Some(instance))
+        .into_iter()
+        .map(|hint| (hint.namespace, hint.location))
+        .collect();
+
+    assert_eq!(locations, vec![(None, "my notes.xsd".to_owned())]);
+}
+
+#[test]
+fn nothing_loaded() {
+    let instance = INSTANCE.replace(".xsd", "-missing.xsd");
+    let document = Document::parse(&instance).unwrap();
+
+    let diagnostics = SchemaHints::new()
+        .load(&document, "schemas/order.xml", None, &resolver())
+        .unwrap_err();
+
+    assert_eq!(diagnostics.len(), 2);
+}
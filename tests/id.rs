@@ -0,0 +1,116 @@
+use xsd::{Error, Parser, Validator};
+
+const SCHEMA: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:t="urn:test" targetNamespace="urn:test">
+ <xsd:element name="people">
+  <xsd:complexType>
+   <xsd:sequence>
+    <xsd:element name="person" maxOccurs="unbounded">
+     <xsd:complexType>
+      <xsd:attribute name="id" type="xsd:ID"/>
+      <xsd:attribute name="code" type="t:code"/>
+      <xsd:attribute name="friends" type="xsd:IDREFS"/>
+     </xsd:complexType>
+    </xsd:element>
+    <xsd:element name="badge" type="t:code" minOccurs="0" maxOccurs="unbounded"/>
+    <xsd:element name="owner" type="xsd:IDREF" minOccurs="0" maxOccurs="unbounded"/>
+   </xsd:sequence>
+  </xsd:complexType>
+ </xsd:element>
+ <xsd:simpleType name="code">
+  <xsd:restriction base="xsd:ID"><xsd:pattern value="c\d+"/></xsd:restriction>
+ </xsd:simpleType>
+</xsd:schema>"#;
+
+fn violations(instance: &str) -> Vec<(&'static str, u32)> {
+    let parser = Parser::parse_str(SCHEMA, Some("people.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+
+    Validator::new(&schema_set)
+        .validate_str(instance, Some("people.xml"))
+        .unwrap()
+        .iter()
+        .map(|d| match d.error {
+            Error::Invalid { rule, ref span, .. } => (rule, span.line),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn references_resolve_anywhere_in_the_document() {
+    let found = violations(
+        r#"<t:people xmlns:t="urn:test">
+ <person id="a" friends=" b  c1 "/>
+ <person id="b" friends="c2"/>
+ <person code="c1"/>
+ <badge>c2</badge>
+ <owner>a</owner>
+</t:people>"#,
+    );
+
+    assert!(found.is_empty(), "{:?}", found);
+}
+
+#[test]
+fn duplicate_and_dangling_ids() {
+    let found = violations(
+        r#"<t:people xmlns:t="urn:test">
+ <person id="a" code="c1"/>
+ <person id="a" friends="a nobody"/>
+ <badge>c1</badge>
+ <owner>missing</owner>
+</t:people>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("cvc-id.2", 3),
+            ("cvc-id.2", 4),
+            ("cvc-id.1", 3),
+            ("cvc-id.1", 5),
+        ]
+    );
+}
+
+#[test]
+fn wildcard_ids() {
+    let schema = r###"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:t="urn:test" targetNamespace="urn:test">
+ <xsd:attribute name="key" type="xsd:ID"/>
+ <xsd:attribute name="alias" type="xsd:ID"/>
+ <xsd:element name="item">
+  <xsd:complexType>
+   <xsd:attribute name="id" type="xsd:ID"/>
+   <xsd:anyAttribute namespace="##targetNamespace" processContents="strict"/>
+  </xsd:complexType>
+ </xsd:element>
+</xsd:schema>"###;
+    let parser = Parser::parse_str(schema, Some("item.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+
+    let found: Vec<_> = Validator::new(&schema_set)
+        .validate_str(
+            r#"<t:item xmlns:t="urn:test" t:key="a" t:alias="b"/>"#,
+            Some("item.xml"),
+        )
+        .unwrap()
+        .iter()
+        .chain(
+            Validator::new(&schema_set)
+                .validate_str(
+                    r#"<t:item xmlns:t="urn:test" id="a" t:key="b"/>"#,
+                    Some("item.xml"),
+                )
+                .unwrap()
+                .iter(),
+        )
+        .map(|d| match d.error {
+            Error::Invalid { rule, .. } => rule,
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect();
+
+    assert_eq!(found, vec!["cvc-complex-type.5.1", "cvc-complex-type.5.2"]);
+}
@@ -0,0 +1,102 @@
+use xsd::{Error, Parser, Validator};
+
+const SCHEMA: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:l="urn:library" targetNamespace="urn:library" elementFormDefault="qualified">
+ <xsd:element name="library">
+  <xsd:complexType>
+   <xsd:sequence>
+    <xsd:element name="book" maxOccurs="unbounded">
+     <xsd:complexType>
+      <xsd:sequence>
+       <xsd:element name="title" type="xsd:string"/>
+       <xsd:element name="shelf" type="xsd:integer" minOccurs="0"/>
+      </xsd:sequence>
+      <xsd:attribute name="id" type="xsd:integer"/>
+     </xsd:complexType>
+    </xsd:element>
+    <xsd:element name="loan" minOccurs="0" maxOccurs="unbounded">
+     <xsd:complexType>
+      <xsd:attribute name="book" type="xsd:integer"/>
+     </xsd:complexType>
+    </xsd:element>
+   </xsd:sequence>
+  </xsd:complexType>
+  <xsd:key name="bookId">
+   <xsd:selector xpath="l:book"/>
+   <xsd:field xpath="@id"/>
+  </xsd:key>
+  <xsd:unique name="placement">
+   <xsd:selector xpath=".//l:book"/>
+   <xsd:field xpath="l:title"/>
+   <xsd:field xpath="l:shelf"/>
+  </xsd:unique>
+  <xsd:keyref name="loanBook" refer="l:bookId">
+   <xsd:selector xpath="l:loan"/>
+   <xsd:field xpath="@book"/>
+  </xsd:keyref>
+ </xsd:element>
+</xsd:schema>"#;
+
+fn violations(instance: &str) -> Vec<(&'static str, u32, String)> {
+    let parser = Parser::parse_str(SCHEMA, Some("library.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+
+    Validator::new(&schema_set)
+        .validate_str(instance, Some("library.xml"))
+        .unwrap()
+        .iter()
+        .map(|d| match d.error {
+            Error::Invalid {
+                rule,
+                ref span,
+                ref message,
+                ..
+            } => (rule, span.line, message.clone()),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn values_compared_in_value_space() {
+    let found = violations(
+        r#"<library xmlns="urn:library">
+ <book id="1"><title>Emma</title><shelf>3</shelf></book>
+ <book id="2"><title>Emma</title></book>
+ <book id="3"><title>Emma</title></book>
+ <loan book="01"/>
+ <loan book=" 3 "/>
+</library>"#,
+    );
+
+    assert!(found.is_empty(), "{:?}", found);
+}
+
+#[test]
+fn duplicates_missing_fields_and_dangling_references() {
+    let found = violations(
+        r#"<library xmlns="urn:library">
+ <book id="1"><title>Emma</title><shelf>3</shelf></book>
+ <book id="01"><title>Emma</title><shelf>03</shelf></book>
+ <book><title>Persuasion</title></book>
+ <loan book="1"/>
+ <loan book="4"/>
+</library>"#,
+    );
+
+    let rules: Vec<_> = found.iter().map(|(rule, line, _)| (*rule, *line)).collect();
+    assert_eq!(
+        rules,
+        vec![
+            ("cvc-identity-constraint.4.2.2", 3),
+            ("cvc-identity-constraint.4.1", 3),
+            ("cvc-identity-constraint.4.2.1", 4),
+            ("cvc-identity-constraint.4.3", 6),
+        ]
+    );
+    assert!(
+        found[0].2.contains("first found at line 2"),
+        "{}",
+        found[0].2
+    );
+}
@@ -0,0 +1,177 @@
+use xsd::{Elements, Error, MemoryResolver, Parser, ParserOptions, Severity};
+
+const VENDOR: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+            xmlns:v="urn:vendor">
+ <xsd:include schemaLocation="missing.xsd"/>
+ <xsd:notation name="jpeg" public="image/jpeg"/>
+ <xsd:element name="a" type="xsd:string" block="sometimes"/>
+ <xsd:simpleType name="b">
+  <xsd:restriction base="xsd:string">
+   <xsd:maxLength/>
+   <xsd:minLength value="1"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:complexType name="c">
+  <xsd:simpleContent/>
+ </xsd:complexType>
+ <v:generator name="tool"/>
+ <xsd:element name="d" type="xsd:int" v:note="kept"/>
+</xsd:schema>"#;
+
+fn resolver() -> MemoryResolver {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("vendor.xsd", VENDOR);
+    resolver
+}
+
+fn component_names(parser: &Parser) -> Vec<&str> {
+    parser
+        .root()
+        .components
+        .iter()
+        .filter_map(|c| match c {
+            Elements::Element(e) => e.name.as_deref(),
+            Elements::SimpleType(s) => s.name.as_deref(),
+            Elements::ComplexType(c) => c.name.as_deref(),
+            Elements::Group(g) => g.name.as_deref(),
+            Elements::Attribute(a) => a.name.as_deref(),
+            Elements::AttributeGroup(g) => g.name.as_deref(),
+            Elements::Notation(n) => Some(&n.name),
+        })
+        .collect()
+}
+
+#[test]
+fn strict_stops_at_first_problem() {
+    // The structure check comes first, and does not load other documents.
+    match Parser::parse_with_resolver("vendor.xsd", &resolver()) {
+        Err(Error::InvalidBlockDefault { value, .. }) => assert_eq!(value, "sometimes"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match ParserOptions::new()
+        .check_structure(false)
+        .parse_with_resolver("vendor.xsd", &resolver())
+    {
+        Err(Error::SchemaNotFound { location, .. }) => assert_eq!(location, "missing.xsd"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn lenient_keeps_going() {
+    let parser = ParserOptions::new()
+        .lenient(true)
+        .parse_with_resolver("vendor.xsd", &resolver())
+        .unwrap();
+
+    assert_eq!(component_names(&parser), vec!["jpeg", "a", "b", "c", "d"]);
+
+    let a = match &parser.root().components[1] {
+        Elements::Element(a) => a,
+        other => panic!("unexpected component: {:?}", other),
+    };
+    assert_eq!(a.block, None);
+
+    let b = match &parser.root().components[2] {
+        Elements::SimpleType(b) => b,
+        other => panic!("unexpected component: {:?}", other),
+    };
+    assert_eq!(b.restriction.as_ref().unwrap().restrictions.len(), 1);
+
+    let c = match &parser.root().components[3] {
+        Elements::ComplexType(c) => c,
+        other => panic!("unexpected component: {:?}", other),
+    };
+    assert_eq!(c.simple_content, None);
+
+    assert_eq!(parser.root().includes[0].document, None);
+
+    let found: Vec<(Severity, u32, String)> = parser
+        .diagnostics
+        .iter()
+        .map(|d| (d.severity, d.span().unwrap().line, d.error.to_string()))
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            (
+                Severity::Error,
+                3,
+                "vendor.xsd:3:2: Schema not found: missing.xsd".to_owned()
+            ),
+            (
+                Severity::Error,
+                5,
+                "vendor.xsd:5:42: Invalid block default: sometimes".to_owned()
+            ),
+            (
+                Severity::Error,
+                8,
+                "vendor.xsd:8:4: Missing attribute: value on maxLength".to_owned()
+            ),
+            (
+                Severity::Error,
+                13,
+                "vendor.xsd:13:3: Missing child element in simpleContent".to_owned()
+            ),
+            (
+                Severity::Warning,
+                15,
+                "vendor.xsd:15:2: Foreign element: {urn:vendor}generator".to_owned()
+            ),
+        ]
+    );
+    assert_eq!(parser.diagnostics.errors().count(), 4);
+    assert_eq!(parser.diagnostics.warnings().count(), 1);
+}
+
+#[test]
+fn foreign_elements() {
+    let contents = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:v="urn:vendor">
+ <v:element name="not-a-schema-element"/>
+ <xsd:element name="a"/>
+</xsd:schema>"#;
+
+    match Parser::parse_str(contents, None) {
+        Err(Error::ForeignElement { name, span }) => {
+            assert_eq!(name.to_string(), "{urn:vendor}element");
+            assert_eq!(span.line, 2);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let parser = ParserOptions::new()
+        .allow_foreign_elements(true)
+        .parse_str(contents, None)
+        .unwrap();
+
+    assert_eq!(component_names(&parser), vec!["a"]);
+    assert!(parser.diagnostics.is_empty());
+}
+
+#[test]
+fn foreign_attributes() {
+    let contents = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:v="urn:vendor">
+ <xsd:element name="a" v:note="kept" xml:lang="en"/>
+</xsd:schema>"#;
+
+    assert!(Parser::parse_str(contents, None).is_ok());
+
+    match ParserOptions::new()
+        .allow_foreign_attributes(false)
+        .parse_str(contents, None)
+    {
+        Err(Error::ForeignAttribute {
+            element,
+            name,
+            span,
+        }) => {
+            assert_eq!(element, "element");
+            assert_eq!(name.to_string(), "{urn:vendor}note");
+            assert_eq!(&contents[span.start..span.end], r#"v:note="kept""#);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
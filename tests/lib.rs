@@ -1,7 +1,17 @@
 use std::collections::HashMap;
 use std::default::Default;
 
-const WORK_DIR: &'static str = env!("CARGO_MANIFEST_DIR");
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn span(document: &str, line: u32, column: u32, start: usize, end: usize) -> xsd::Span {
+    xsd::Span {
+        document: document.to_owned(),
+        line,
+        column,
+        start,
+        end,
+    }
+}
 
 #[test]
 fn parse_xsd1() {
@@ -16,83 +26,104 @@ fn parse_xsd1() {
     );
     namespaces.insert("mns".to_owned(), "http://example.org/order.xsd".to_owned());
 
-    let expected = vec![
-        xsd::Elements::Schema(xsd::schema::Schema {
+    assert_eq!(parser.documents.len(), 1);
+    assert_eq!(
+        parser.root().schema,
+        xsd::schema::Schema {
             element_form_default: Some(xsd::shared::FormDefault::Qualified),
-            namespaces: namespaces,
+            namespaces,
             target_namespace: Some("http://example.org/order.xsd".to_owned()),
+            span: span(&path, 1, 1, 0, 946),
             ..Default::default()
-        }),
+        }
+    );
+
+    let expected = vec![
         xsd::Elements::Element(xsd::element::Element {
             name: Some("Order".to_owned()),
             r#type: Some("mns:OrderType".to_owned()),
+            span: span(&path, 5, 2, 211, 259),
             ..Default::default()
         }),
         xsd::Elements::ComplexType(Box::new(xsd::complex_type::ComplexType {
             name: Some("OrderType".to_owned()),
             sequence: Some(xsd::complex_type::Sequence {
-                elements: vec![
-                    xsd::element::Element {
+                particles: vec![
+                    xsd::complex_type::NestedParticle::Element(Box::new(xsd::element::Element {
                         name: Some("ShippingAddress".to_owned()),
                         r#type: Some("mns:Address".to_owned()),
                         max_occurrences: Some(xsd::shared::Occurrence::Limit(2)),
+                        span: span(&path, 8, 4, 316, 386),
                         ..Default::default()
-                    },
-                    xsd::element::Element {
+                    })),
+                    xsd::complex_type::NestedParticle::Element(Box::new(xsd::element::Element {
                         name: Some("BillingAddress".to_owned()),
                         r#type: Some("mns:Address".to_owned()),
+                        span: span(&path, 9, 4, 390, 445),
                         ..Default::default()
-                    },
+                    })),
                 ],
+                span: span(&path, 7, 3, 298, 463),
                 ..Default::default()
             }),
-            attribute: Some(xsd::complex_type::Attribute {
+            attributes: vec![xsd::complex_type::Attribute {
                 name: Some("Date".to_owned()),
                 r#type: Some("xsd:date".to_owned()),
+                span: span(&path, 11, 3, 466, 510),
                 ..Default::default()
-            }),
+            }],
+            span: span(&path, 6, 2, 261, 530),
             ..Default::default()
         })),
         xsd::Elements::ComplexType(Box::new(xsd::complex_type::ComplexType {
             name: Some("Address".to_owned()),
             sequence: Some(xsd::complex_type::Sequence {
-                elements: vec![
-                    xsd::element::Element {
+                particles: vec![
+                    xsd::complex_type::NestedParticle::Element(Box::new(xsd::element::Element {
                         name: Some("name".to_owned()),
                         r#type: Some("xsd:string".to_owned()),
+                        span: span(&path, 16, 4, 586, 630),
                         ..Default::default()
-                    },
-                    xsd::element::Element {
+                    })),
+                    xsd::complex_type::NestedParticle::Element(Box::new(xsd::element::Element {
                         name: Some("street".to_owned()),
                         r#type: Some("xsd:string".to_owned()),
+                        span: span(&path, 17, 4, 634, 680),
                         ..Default::default()
-                    },
-                    xsd::element::Element {
+                    })),
+                    xsd::complex_type::NestedParticle::Element(Box::new(xsd::element::Element {
                         name: Some("city".to_owned()),
                         r#type: Some("xsd:string".to_owned()),
+                        span: span(&path, 18, 4, 684, 728),
                         ..Default::default()
-                    },
-                    xsd::element::Element {
+                    })),
+                    xsd::complex_type::NestedParticle::Element(Box::new(xsd::element::Element {
                         name: Some("county".to_owned()),
                         r#type: Some("xsd:string".to_owned()),
+                        span: span(&path, 19, 4, 732, 778),
                         ..Default::default()
-                    },
-                    xsd::element::Element {
+                    })),
+                    xsd::complex_type::NestedParticle::Element(Box::new(xsd::element::Element {
                         name: Some("postcode".to_owned()),
                         r#type: Some("xsd:string".to_owned()),
+                        span: span(&path, 20, 4, 782, 830),
                         ..Default::default()
-                    },
+                    })),
                 ],
+                span: span(&path, 15, 3, 568, 848),
                 ..Default::default()
             }),
-            attribute: Some(xsd::complex_type::Attribute {
+            attributes: vec![xsd::complex_type::Attribute {
                 name: Some("country".to_owned()),
                 r#type: Some("xsd:NMTOKEN".to_owned()),
+                default_fixed: Some(xsd::element::DefaultFixed::Fixed("GB".to_owned())),
+                span: span(&path, 22, 3, 851, 912),
                 ..Default::default()
-            }),
+            }],
+            span: span(&path, 14, 2, 533, 932),
             ..Default::default()
         })),
     ];
 
-    assert_eq!(parser.elements, expected);
+    assert_eq!(parser.root().components, expected);
 }
@@ -0,0 +1,134 @@
+use xsd::{Elements, Error, Parser};
+
+fn parse(body: &str) -> Result<Parser, Error> {
+    let contents = format!(
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">{}</xsd:schema>"#,
+        body
+    );
+
+    Parser::parse_str(&contents, Some("malformed.xsd"))
+}
+
+fn assert_missing_attribute(body: &str, expected_element: &str, expected_attribute: &str) {
+    match parse(body) {
+        Err(Error::MissingAttribute {
+            element,
+            attribute,
+            span,
+        }) => {
+            assert_eq!(element, expected_element);
+            assert_eq!(attribute, expected_attribute);
+            assert_eq!(span.document, "malformed.xsd");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+fn assert_missing_child(body: &str, expected_element: &str) {
+    match parse(body) {
+        Err(Error::MissingChild { element, .. }) => assert_eq!(element, expected_element),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+fn assert_invalid_occurs(body: &str, expected_value: &str) {
+    match parse(body) {
+        Err(Error::InvalidOccurs {
+            value,
+            span: Some(_),
+        }) => assert_eq!(value, expected_value),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn restriction_without_base() {
+    assert_missing_attribute(
+        r#"<xsd:simpleType name="a"><xsd:restriction/></xsd:simpleType>"#,
+        "restriction",
+        "base",
+    );
+}
+
+#[test]
+fn facet_without_value() {
+    assert_missing_attribute(
+        r#"<xsd:simpleType name="a">
+            <xsd:restriction base="xsd:string"><xsd:maxLength/></xsd:restriction>
+        </xsd:simpleType>"#,
+        "maxLength",
+        "value",
+    );
+}
+
+#[test]
+fn extension_without_base() {
+    assert_missing_attribute(
+        r#"<xsd:complexType name="a">
+            <xsd:complexContent><xsd:extension/></xsd:complexContent>
+        </xsd:complexType>"#,
+        "extension",
+        "base",
+    );
+}
+
+#[test]
+fn empty_simple_content() {
+    assert_missing_child(
+        r#"<xsd:complexType name="a"><xsd:simpleContent/></xsd:complexType>"#,
+        "simpleContent",
+    );
+}
+
+#[test]
+fn empty_complex_content() {
+    assert_missing_child(
+        r#"<xsd:complexType name="a"><xsd:complexContent> </xsd:complexContent></xsd:complexType>"#,
+        "complexContent",
+    );
+}
+
+#[test]
+fn invalid_occurs() {
+    assert_invalid_occurs(
+        r#"<xsd:complexType name="a"><xsd:sequence minOccurs="-1"/></xsd:complexType>"#,
+        "-1",
+    );
+    assert_invalid_occurs(
+        r#"<xsd:complexType name="a"><xsd:choice maxOccurs="many"/></xsd:complexType>"#,
+        "many",
+    );
+    assert_invalid_occurs(
+        r#"<xsd:complexType name="a">
+            <xsd:sequence><xsd:any maxOccurs="1.5"/></xsd:sequence>
+        </xsd:complexType>"#,
+        "1.5",
+    );
+    assert_invalid_occurs(
+        r#"<xsd:complexType name="a">
+            <xsd:sequence><xsd:element name="b" minOccurs=""/></xsd:sequence>
+        </xsd:complexType>"#,
+        "",
+    );
+}
+
+#[test]
+fn empty_documentation() {
+    let parser = parse(
+        r#"<xsd:simpleType name="a">
+            <xsd:annotation><xsd:documentation/><xsd:appinfo></xsd:appinfo></xsd:annotation>
+            <xsd:restriction base="xsd:string"/>
+        </xsd:simpleType>"#,
+    )
+    .unwrap();
+
+    match &parser.root().components[0] {
+        Elements::SimpleType(simple_type) => {
+            let annotation = simple_type.annotation.as_ref().unwrap();
+
+            assert_eq!(annotation.documentation, vec![String::new()]);
+            assert_eq!(annotation.app_info, vec![String::new()]);
+        }
+        other => panic!("unexpected component: {:?}", other),
+    }
+}
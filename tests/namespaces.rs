@@ -0,0 +1,74 @@
+use xsd::shared::QName;
+use xsd::{Elements, Error, Parser, ParserOptions};
+
+const SCHEMA: &str = r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema"
+           xmlns:v="urn:vendor"
+           xml:lang="en"
+           v:revision="7">
+ <xs:simpleType name="code" v:table="codes">
+  <xs:annotation>
+   <xs:appinfo><v:mapping column="CODE"/></xs:appinfo>
+   <xs:documentation>A code.</xs:documentation>
+  </xs:annotation>
+  <xs:restriction base="xs:string"/>
+ </xs:simpleType>
+ <v:element name="ignored"/>
+</xs:schema>"#;
+
+#[test]
+fn foreign_element_with_schema_local_name() {
+    let parser = ParserOptions::new()
+        .allow_foreign_elements(true)
+        .parse_str(SCHEMA, None)
+        .unwrap();
+
+    assert_eq!(parser.root().components.len(), 1);
+    assert!(matches!(
+        parser.root().components[0],
+        Elements::SimpleType(_)
+    ));
+}
+
+#[test]
+fn extra_attributes() {
+    let parser = ParserOptions::new()
+        .allow_foreign_elements(true)
+        .parse_str(SCHEMA, None)
+        .unwrap();
+
+    let schema = &parser.root().schema;
+
+    assert_eq!(schema.xml_lang.as_deref(), Some("en"));
+    assert_eq!(
+        schema
+            .extra_attributes
+            .get(&QName::new(Some("urn:vendor"), "revision")),
+        Some(&"7".to_owned())
+    );
+
+    match &parser.root().components[0] {
+        Elements::SimpleType(simple_type) => {
+            assert_eq!(simple_type.extra_attributes.len(), 1);
+            assert_eq!(
+                simple_type.extra_attributes[&QName::new(Some("urn:vendor"), "table")],
+                "codes"
+            );
+
+            let annotation = simple_type.annotation.as_ref().unwrap();
+
+            assert_eq!(annotation.app_info, vec![r#"<v:mapping column="CODE"/>"#]);
+            assert_eq!(annotation.documentation, vec!["A code."]);
+        }
+        other => panic!("unexpected component: {:?}", other),
+    }
+}
+
+#[test]
+fn root_outside_schema_namespace() {
+    let contents = r#"<schema xmlns="urn:not-xsd"><element name="a"/></schema>"#;
+
+    match Parser::parse_str(contents, None) {
+        Err(Error::UnhandledTag { tag, .. }) => assert_eq!(tag, "schema"),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,74 @@
+use xsd::{Elements, MemoryResolver, Parser};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+fn resolved_includes(parser: &Parser) -> Vec<&str> {
+    parser
+        .documents
+        .iter()
+        .flat_map(|d| &d.includes)
+        .filter_map(|i| i.resolved_location.as_deref())
+        .collect()
+}
+
+#[test]
+fn includes_resolve_against_their_own_document() {
+    let root = format!("{}/tests/testdata/nested/root.xsd", WORK_DIR);
+    let parser = Parser::parse(&root).unwrap();
+
+    // e.xsd is included twice but only loaded once.
+    let names: Vec<&str> = parser
+        .documents
+        .iter()
+        .flat_map(|d| &d.components)
+        .filter_map(|e| match e {
+            Elements::Element(e) => e.name.as_deref(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, vec!["root", "b", "d", "e", "f"]);
+
+    let testdata = format!("{}/tests/testdata/nested", WORK_DIR);
+    assert_eq!(
+        resolved_includes(&parser),
+        vec![
+            format!("{}/a/b.xsd", testdata),
+            format!("{}/base/f.xsd", testdata),
+            format!("{}/c/d.xsd", testdata),
+            format!("{}/c/e.xsd", testdata),
+            format!("{}/c/e.xsd", testdata),
+        ]
+    );
+}
+
+#[test]
+fn includes_resolve_against_uris() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert(
+        "http://example.com/schemas/root.xsd",
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+            <xsd:include schemaLocation="types/a.xsd"/>
+        </xsd:schema>"#,
+    );
+    resolver.insert(
+        "http://example.com/schemas/types/a.xsd",
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+            <xsd:include schemaLocation="../common/./b.xsd?v=1"/>
+        </xsd:schema>"#,
+    );
+    resolver.insert(
+        "http://example.com/schemas/common/b.xsd?v=1",
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"/>"#,
+    );
+
+    let parser =
+        Parser::parse_with_resolver("http://example.com/schemas/root.xsd", &resolver).unwrap();
+
+    assert_eq!(
+        resolved_includes(&parser),
+        vec![
+            "http://example.com/schemas/types/a.xsd",
+            "http://example.com/schemas/common/b.xsd?v=1",
+        ]
+    );
+}
@@ -0,0 +1,80 @@
+use xsd::Pattern;
+
+fn matches(pattern: &str, value: &str) -> bool {
+    Pattern::new(pattern).unwrap().is_match(value)
+}
+
+#[test]
+fn implicit_anchoring() {
+    assert!(matches("[0-9]{3}", "123"));
+    assert!(!matches("[0-9]{3}", "1234"));
+    assert!(!matches("a|b", "ab"));
+    assert!(matches("a|b|", ""));
+    assert!(matches("^x$", "^x$"));
+    assert!(!matches("^x$", "x"));
+}
+
+#[test]
+fn quantifiers_and_groups() {
+    assert!(matches("(ab){2,3}c?", "ababab"));
+    assert!(!matches("(ab){2,3}c?", "ab"));
+    assert!(matches("a{2,}", "aaaa"));
+    assert!(matches(r"\d+(\.\d{1,2})?", "12.50"));
+    assert!(!matches(r"\d+(\.\d{1,2})?", "12.505"));
+    assert!(matches(".*", "any thing"));
+    assert!(!matches(".", "\n"));
+}
+
+#[test]
+fn character_classes() {
+    assert!(matches("[a-z-[aeiou]]+", "rhythm"));
+    assert!(!matches("[a-z-[aeiou]]+", "vowel"));
+    assert!(matches("[^a-z-[0-9]]", "A"));
+    assert!(!matches("[^a-z-[0-9]]", "5"));
+    assert!(matches("[-a]*", "a-a"));
+    assert!(matches(r"[\-\[\]^]+", "-[]^"));
+    assert!(matches(r"\i\c*", "_x.1:b"));
+    assert!(!matches(r"\i\c*", "1x"));
+    assert!(matches(r"\s\S\w\W", " x9!"));
+}
+
+#[test]
+fn categories_and_blocks() {
+    assert!(matches(r"\p{Lu}\p{Ll}+", "Été"));
+    assert!(matches(r"\P{L}", "1"));
+    assert!(matches(r"\p{IsBasicLatin}+", "abc"));
+    assert!(!matches(r"\p{IsBasicLatin}+", "é"));
+    assert!(matches(r"\p{IsGreek}", "λ"));
+    assert!(matches(r"\p{IsGreekandCoptic}", "λ"));
+    assert!(matches(r"\P{IsHighSurrogates}", "a"));
+}
+
+#[test]
+fn invalid_patterns() {
+    for pattern in &[
+        "(a",
+        "a)",
+        "[a",
+        "[]",
+        "*a",
+        "a**",
+        "a{3,1}",
+        "a{,2}",
+        r"\q",
+        r"\p{Greek}",
+        r"\p{IsNowhere}",
+        "[a-c-e]",
+        "[z-a]",
+        "(?:a)",
+        r"\1",
+        "a]",
+    ] {
+        assert!(
+            Pattern::new(pattern).is_err(),
+            "{} should not compile",
+            pattern
+        );
+    }
+
+    assert_eq!(Pattern::new("ab)").unwrap_err().position, 2);
+}
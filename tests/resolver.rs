@@ -0,0 +1,205 @@
+use std::cell::RefCell;
+
+use xsd::resolver::ResolvedSchema;
+use xsd::{
+    ChainResolver, Elements, Error, FileResolver, MemoryResolver, Parser, ParserOptions,
+    SchemaResolver,
+};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+const ROOT: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:include schemaLocation="common.xsd"/>
+ <xsd:element name="root" type="xsd:string"/>
+</xsd:schema>"#;
+
+const COMMON: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:element name="common" type="xsd:string"/>
+</xsd:schema>"#;
+
+fn element_names(parser: &Parser) -> Vec<&str> {
+    parser
+        .documents
+        .iter()
+        .flat_map(|d| &d.components)
+        .filter_map(|e| match e {
+            Elements::Element(e) => e.name.as_deref(),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn memory_resolver() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("schemas/root.xsd", ROOT);
+    resolver.insert("schemas/common.xsd", COMMON);
+
+    let parser = Parser::parse_with_resolver("schemas/root.xsd", &resolver).unwrap();
+
+    assert_eq!(element_names(&parser), vec!["root", "common"]);
+}
+
+#[test]
+fn memory_resolver_unknown_location() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("root.xsd", ROOT);
+
+    match Parser::parse_with_resolver("root.xsd", &resolver) {
+        Err(Error::SchemaNotFound { location, span }) => {
+            assert_eq!(location, "common.xsd");
+
+            let span = span.unwrap();
+            assert_eq!(
+                (span.document.as_str(), span.line, span.column),
+                ("root.xsd", 2, 2)
+            );
+            assert_eq!(
+                &ROOT[span.start..span.end],
+                r#"<xsd:include schemaLocation="common.xsd"/>"#
+            );
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn chain_resolver_falls_through() {
+    let mut memory = MemoryResolver::new();
+    memory.insert("/nowhere/vehicles_ext.xsd", "<not-loaded/>");
+    memory.insert("common.xsd", "<unrelated/>");
+
+    let mut resolver = ChainResolver::new();
+    resolver.push(FileResolver);
+    resolver.push(memory);
+
+    let root = format!("{}/tests/testdata/vehicles.xsd", WORK_DIR);
+    let resolved = resolver.resolve(None, &root, None).unwrap().unwrap();
+    assert_eq!(resolved.location, root);
+
+    let missing = resolver.resolve(None, "missing.xsd", None).unwrap();
+    assert_eq!(missing, None);
+
+    let fallback = resolver
+        .resolve(Some("/nowhere/root.xsd"), "vehicles_ext.xsd", None)
+        .unwrap();
+    assert_eq!(
+        fallback,
+        Some(ResolvedSchema {
+            location: "/nowhere/vehicles_ext.xsd".to_owned(),
+            contents: b"<not-loaded/>".to_vec(),
+        })
+    );
+
+    let relative = resolver
+        .resolve(Some("/nowhere/root.xsd"), "common.xsd", None)
+        .unwrap();
+    assert_eq!(relative, None);
+}
+
+#[test]
+fn file_resolver_joins_onto_base() {
+    let base = format!("{}/tests/testdata/vehicles.xsd", WORK_DIR);
+
+    let resolved = FileResolver
+        .resolve(Some(&base), "vehicles_ext.xsd", None)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        resolved.location,
+        format!("{}/tests/testdata/vehicles_ext.xsd", WORK_DIR)
+    );
+}
+
+/// Records every location it is asked for.
+struct Recording<'a> {
+    inner: &'a MemoryResolver,
+    requested: RefCell<Vec<String>>,
+}
+
+impl SchemaResolver for Recording<'_> {
+    fn resolve(
+        &self,
+        base: Option<&str>,
+        schema_location: &str,
+        namespace: Option<&str>,
+    ) -> Result<Option<ResolvedSchema>, Error> {
+        self.requested.borrow_mut().push(schema_location.to_owned());
+        self.inner.resolve(base, schema_location, namespace)
+    }
+}
+
+#[test]
+fn redefine_is_rejected() {
+    let root = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:redefine schemaLocation="common.xsd"/>
+ <xsd:element name="root" type="xsd:string"/>
+</xsd:schema>"#;
+
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("root.xsd", root);
+    resolver.insert("common.xsd", COMMON);
+
+    match Parser::parse_with_resolver("root.xsd", &resolver) {
+        Err(Error::UnsupportedRedefine { location, span }) => {
+            assert_eq!(location, "common.xsd");
+            assert_eq!((span.line, span.column), (2, 2));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let recording = Recording {
+        inner: &resolver,
+        requested: RefCell::new(Vec::new()),
+    };
+    let parser = ParserOptions::new()
+        .lenient(true)
+        .parse_with_resolver("root.xsd", &recording)
+        .unwrap();
+
+    // The redefined document is never fetched, so none of its components
+    // are in the model.
+    assert_eq!(recording.requested.into_inner(), vec!["root.xsd"]);
+    assert_eq!(element_names(&parser), vec!["root"]);
+    assert_eq!(parser.diagnostics.len(), 1);
+}
+
+#[test]
+fn parse_str_with_resolver() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("schemas/common.xsd", COMMON);
+
+    let parser =
+        Parser::parse_str_with_resolver(ROOT, Some("schemas/root.xsd"), &resolver).unwrap();
+    assert_eq!(element_names(&parser), vec!["root", "common"]);
+
+    let parser = ParserOptions::new()
+        .lenient(true)
+        .parse_str_with_resolver(ROOT, Some("other/root.xsd"), &resolver)
+        .unwrap();
+    assert_eq!(element_names(&parser), vec!["root"]);
+    assert_eq!(parser.diagnostics.len(), 1);
+}
+
+#[test]
+fn parse_str_without_base() {
+    // The location exists relative to the working directory, which a
+    // document without a base URI must not be resolved against.
+    let contents = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:include schemaLocation="tests/testdata/vehicles_ext.xsd"/>
+</xsd:schema>"#;
+
+    match Parser::parse_str(contents, None) {
+        Err(Error::SchemaNotFound { location, span }) => {
+            assert_eq!(location, "tests/testdata/vehicles_ext.xsd");
+            assert_eq!(span.map(|s| s.line), Some(2));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("common.xsd", COMMON);
+
+    assert!(Parser::parse_str_with_resolver(ROOT, None, &resolver).is_err());
+}
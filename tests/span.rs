@@ -0,0 +1,77 @@
+use xsd::{Error, MemoryResolver, Parser, ParserOptions};
+
+const ROOT: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:include schemaLocation="common.xsd"/>
+</xsd:schema>"#;
+
+const COMMON: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:element name="common" type="xsd:string"/>
+ <xsd:override schemaLocation="base.xsd"/>
+</xsd:schema>"#;
+
+#[test]
+fn unhandled_tag_in_included_document() {
+    let mut resolver = MemoryResolver::new();
+    resolver.insert("root.xsd", ROOT);
+    resolver.insert("common.xsd", COMMON);
+
+    // The schema-for-schemas check would reject the XSD 1.1 override first.
+    let error = ParserOptions::new()
+        .check_structure(false)
+        .parse_with_resolver("root.xsd", &resolver)
+        .unwrap_err();
+
+    match error {
+        Error::UnhandledTag {
+            ref tag, ref span, ..
+        } => {
+            assert_eq!(tag, "override");
+            assert_eq!(
+                (span.document.as_str(), span.line, span.column),
+                ("common.xsd", 3, 2)
+            );
+            assert_eq!(
+                &COMMON[span.start..span.end],
+                r#"<xsd:override schemaLocation="base.xsd"/>"#
+            );
+        }
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+
+    assert_eq!(
+        error.to_string(),
+        "common.xsd:3:2: Unhandled tag: override in schema"
+    );
+}
+
+#[test]
+fn invalid_attribute_value() {
+    let contents = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:element name="a" block="everything"/>
+</xsd:schema>"#;
+
+    match Parser::parse_str(contents, Some("a.xsd")) {
+        Err(Error::InvalidBlockDefault {
+            value,
+            span: Some(span),
+        }) => {
+            assert_eq!(value, "everything");
+            assert_eq!((span.line, span.column), (2, 24));
+            assert_eq!(&contents[span.start..span.end], r#"block="everything""#);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn malformed_xml() {
+    let contents = "<xsd:schema xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\">\n <xsd:element>\n</xsd:schema>";
+
+    match Parser::parse_str(contents, Some("broken.xsd")) {
+        Err(Error::ParseXml { span, .. }) => {
+            assert_eq!(span.document, "broken.xsd");
+            assert_eq!(span.line, 3);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::{self, Read};
+
+use xsd::shared::QName;
+use xsd::{Error, Parser, Span, StreamValidator, Validator};
+
+const SCHEMA: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:t="urn:test" targetNamespace="urn:test" elementFormDefault="qualified">
+ <xsd:element name="feed">
+  <xsd:complexType>
+   <xsd:sequence>
+    <xsd:element name="item" maxOccurs="unbounded">
+     <xsd:complexType>
+      <xsd:simpleContent>
+       <xsd:extension base="xsd:int">
+        <xsd:attribute name="id" type="xsd:ID" use="required"/>
+       </xsd:extension>
+      </xsd:simpleContent>
+     </xsd:complexType>
+    </xsd:element>
+    <xsd:element name="end" type="xsd:string"/>
+   </xsd:sequence>
+  </xsd:complexType>
+ </xsd:element>
+</xsd:schema>"#;
+
+/// A feed of `count` items, generated as it is read.
+struct Feed {
+    count: usize,
+    next: usize,
+    bad: usize,
+    buffer: Vec<u8>,
+}
+
+impl Read for Feed {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buffer.is_empty() {
+            self.buffer = if self.next == 0 {
+                b"<feed xmlns=\"urn:test\">\n".to_vec()
+            } else if self.next <= self.count {
+                let value = if self.next == self.bad { "x" } else { "1" };
+                format!("<item id=\"i{}\">{}</item>\n", self.next, value).into_bytes()
+            } else if self.next == self.count + 1 {
+                b"<end/>\n</feed>\n".to_vec()
+            } else {
+                return Ok(0);
+            };
+            self.next += 1;
+        }
+
+        let len = buf.len().min(self.buffer.len());
+        buf[..len].copy_from_slice(&self.buffer[..len]);
+        self.buffer.drain(..len);
+        Ok(len)
+    }
+}
+
+fn rules(diagnostics: &xsd::Diagnostics) -> Vec<(&'static str, u32)> {
+    diagnostics
+        .iter()
+        .map(|d| match d.error {
+            Error::Invalid { rule, ref span, .. } => (rule, span.line),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn large_generated_feed() {
+    let parser = Parser::parse_str(SCHEMA, Some("feed.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+    let feed = Feed {
+        count: 50_000,
+        next: 0,
+        bad: 40_000,
+        buffer: Vec::new(),
+    };
+
+    let diagnostics = Validator::new(&schema_set)
+        .validate_reader(feed, Some("feed.xml"))
+        .unwrap();
+
+    assert_eq!(
+        rules(&diagnostics),
+        vec![("cvc-datatype-valid.1.2.1", 40_001)]
+    );
+    assert!(diagnostics
+        .iter()
+        .next()
+        .unwrap()
+        .to_string()
+        .starts_with("error: feed.xml:40001:"));
+}
+
+#[test]
+fn reader_matches_string() {
+    let parser = Parser::parse_str(SCHEMA, Some("feed.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+    let validator = Validator::new(&schema_set);
+    let instance = r#"<feed xmlns="urn:test">
+ <item>1</item>
+ <item id="b"><![CDATA[ 2 ]]></item>
+ text
+</feed>"#;
+
+    let from_reader = validator
+        .validate_reader(instance.as_bytes(), None)
+        .unwrap();
+    let from_str = validator.validate_str(instance, None).unwrap();
+
+    assert_eq!(
+        rules(&from_reader),
+        vec![
+            ("cvc-complex-type.4", 2),
+            ("cvc-complex-type.2.3", 3),
+            ("cvc-complex-type.2.4.b", 1),
+        ]
+    );
+    assert_eq!(rules(&from_reader), rules(&from_str));
+}
+
+#[test]
+fn malformed_stream() {
+    let parser = Parser::parse_str(SCHEMA, Some("feed.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+
+    let error = Validator::new(&schema_set)
+        .validate_reader(&b"<feed xmlns=\"urn:test\">\n<item>"[..], Some("feed.xml"))
+        .unwrap_err();
+
+    match error {
+        Error::ReadXml { ref span, .. } => assert_eq!(span.document, "feed.xml"),
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn push_events() {
+    let parser = Parser::parse_str(SCHEMA, Some("feed.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+    let validator = Validator::new(&schema_set);
+    let at = |line| Span {
+        line,
+        ..Span::default()
+    };
+    let name = |name| QName::new(Some("urn:test"), name);
+
+    let mut stream = StreamValidator::new(&validator);
+    stream.start_element(name("feed"), at(1));
+    stream.start_element(name("item"), at(2));
+    stream.attribute(QName::new(None, "id"), "a", at(2));
+    stream.text("7", at(2));
+    stream.end_element();
+    stream.start_element(name("other"), at(3));
+    stream.end_element();
+    stream.end_element();
+
+    assert_eq!(rules(&stream.finish()), vec![("cvc-complex-type.2.4.a", 3)]);
+}
+
+#[test]
+fn unprefixed_names_in_no_namespace() {
+    let schema = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:complexType name="Base">
+  <xsd:sequence><xsd:element name="kind" type="kind" minOccurs="0"/></xsd:sequence>
+ </xsd:complexType>
+ <xsd:complexType name="Derived">
+  <xsd:complexContent>
+   <xsd:extension base="Base">
+    <xsd:sequence><xsd:element name="extra" type="xsd:string"/></xsd:sequence>
+   </xsd:extension>
+  </xsd:complexContent>
+ </xsd:complexType>
+ <xsd:simpleType name="kind">
+  <xsd:restriction base="xsd:QName"><xsd:enumeration value="Base"/></xsd:restriction>
+ </xsd:simpleType>
+ <xsd:element name="root">
+  <xsd:complexType>
+   <xsd:sequence><xsd:element name="item" type="Base" maxOccurs="unbounded"/></xsd:sequence>
+  </xsd:complexType>
+ </xsd:element>
+</xsd:schema>"#;
+    let parser = Parser::parse_str(schema, Some("root.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+    let validator = Validator::new(&schema_set);
+    let instance = r#"<root xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+ <item xsi:type="Derived"><kind>Base</kind><extra>x</extra></item>
+ <item xmlns:p="urn:p"><kind>p:Base</kind></item>
+ <item xsi:type="Derived"><kind>Base</kind></item>
+ <item xmlns:p="urn:p" xsi:type="p:Derived"/>
+ <item xmlns=""><kind xmlns="">Base</kind></item>
+</root>"#;
+
+    let from_reader = validator
+        .validate_reader(instance.as_bytes(), None)
+        .unwrap();
+    let from_str = validator.validate_str(instance, None).unwrap();
+
+    assert_eq!(
+        rules(&from_reader),
+        vec![
+            ("cvc-enumeration-valid", 3),
+            ("cvc-complex-type.2.4.b", 4),
+            ("cvc-elt.4.2", 5),
+        ]
+    );
+    assert_eq!(rules(&from_reader), rules(&from_str));
+}
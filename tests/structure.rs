@@ -0,0 +1,172 @@
+use xsd::{Error, Parser, ParserOptions};
+
+fn parse(body: &str) -> Result<Parser, Error> {
+    let contents = format!(
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">{}</xsd:schema>"#,
+        body
+    );
+
+    Parser::parse_str(&contents, Some("structure.xsd"))
+}
+
+#[test]
+fn simple_content_after_sequence() {
+    let body = r#"<xsd:complexType name="a">
+        <xsd:sequence/>
+        <xsd:simpleContent><xsd:extension base="xsd:string"/></xsd:simpleContent>
+    </xsd:complexType>"#;
+
+    match parse(body) {
+        Err(Error::UnexpectedElement {
+            parent,
+            element,
+            span,
+        }) => {
+            assert_eq!(parent, "complexType");
+            assert_eq!(element, "simpleContent");
+            assert_eq!(span.line, 3);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn repeated_sequence() {
+    let body = r#"<xsd:complexType name="a">
+        <xsd:sequence><xsd:element name="b"/></xsd:sequence>
+        <xsd:sequence><xsd:element name="c"/></xsd:sequence>
+    </xsd:complexType>"#;
+
+    match parse(body) {
+        Err(Error::UnexpectedElement { element, span, .. }) => {
+            assert_eq!(element, "sequence");
+            assert_eq!(span.line, 3);
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn type_and_inline_type() {
+    let body = r#"<xsd:element name="a" type="xsd:string">
+        <xsd:simpleType><xsd:restriction base="xsd:string"/></xsd:simpleType>
+    </xsd:element>"#;
+
+    match parse(body) {
+        Err(Error::ConflictingContent {
+            element,
+            first,
+            second,
+            ..
+        }) => {
+            assert_eq!(element, "element");
+            assert_eq!(first, "type");
+            assert_eq!(second, "simpleType");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn attributes() {
+    match parse(r#"<xsd:element name="a" minOccurs="0"/>"#) {
+        Err(Error::UnexpectedAttribute {
+            element, attribute, ..
+        }) => {
+            assert_eq!(element, "element");
+            assert_eq!(attribute, "minOccurs");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match parse(r#"<xsd:element name="a" nillable="yes"/>"#) {
+        Err(Error::InvalidAttributeValue {
+            attribute,
+            value,
+            span: Some(_),
+            ..
+        }) => {
+            assert_eq!(attribute, "nillable");
+            assert_eq!(value, "yes");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match parse(r#"<xsd:complexType><xsd:sequence/></xsd:complexType>"#) {
+        Err(Error::MissingAttribute {
+            element, attribute, ..
+        }) => {
+            assert_eq!(element, "complexType");
+            assert_eq!(attribute, "name");
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn lenient_reports_every_violation() {
+    let contents = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+ <xsd:element name="a" type="xsd:string" default="x" fixed="y"/>
+ <xsd:complexType name="b">
+  <xsd:sequence>
+   <xsd:element ref="a" type="xsd:int"/>
+   <xsd:element/>
+  </xsd:sequence>
+  <xsd:sequence/>
+ </xsd:complexType>
+ <xsd:simpleType name="c">
+  <xsd:list itemType="xsd:int"><xsd:simpleType/></xsd:list>
+ </xsd:simpleType>
+ <xsd:attribute name="d" default="1" colour="red"/>
+</xsd:schema>"#;
+
+    let parser = ParserOptions::new()
+        .lenient(true)
+        .parse_str(contents, Some("structure.xsd"))
+        .unwrap();
+
+    let found: Vec<String> = parser
+        .diagnostics
+        .errors()
+        .map(|d| d.error.to_string())
+        .collect();
+
+    assert_eq!(
+        found,
+        vec![
+            "structure.xsd:2:2: element cannot have both default and fixed",
+            "structure.xsd:5:4: element cannot have both ref and type",
+            "structure.xsd:6:4: Missing attribute: name on element",
+            "structure.xsd:8:3: Unexpected element: sequence in complexType",
+            "structure.xsd:11:3: list cannot have both itemType and simpleType",
+            "structure.xsd:11:32: Missing child element in simpleType",
+            "structure.xsd:13:38: Unexpected attribute: colour on attribute",
+        ]
+    );
+}
+
+#[test]
+fn unchecked() {
+    let body = r#"<xsd:complexType name="a">
+        <xsd:sequence/>
+        <xsd:sequence/>
+    </xsd:complexType>"#;
+    let contents = format!(
+        r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema">{}</xsd:schema>"#,
+        body
+    );
+
+    assert!(ParserOptions::new()
+        .check_structure(false)
+        .parse_str(&contents, None)
+        .is_ok());
+}
+
+#[test]
+fn fixtures_pass() {
+    for fixture in &["tests/testdata/xsd1.xml", "tests/testdata/vehicles.xsd"] {
+        let parser = ParserOptions::new().lenient(true).parse(fixture).unwrap();
+
+        assert!(parser.diagnostics.is_empty(), "{}", fixture);
+    }
+}
@@ -0,0 +1,124 @@
+use xsd::complex_type::NestedParticle;
+use xsd::schema_set::Global;
+use xsd::shared::QName;
+use xsd::{Parser, SubstitutionGroups};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+const VEHICLES: &str = "http://example.org/vehicles";
+
+fn names(elements: Vec<&Global<xsd::element::Element>>) -> Vec<String> {
+    let mut names: Vec<String> = elements.iter().map(|e| e.name.to_string()).collect();
+    names.sort();
+    names
+}
+
+fn parse() -> Parser {
+    Parser::parse(format!("{}/tests/testdata/vehicles.xsd", WORK_DIR)).unwrap()
+}
+
+#[test]
+fn transitive_members() {
+    let parser = parse();
+    let schema_set = parser.schema_set();
+    let groups = SubstitutionGroups::new(&schema_set);
+
+    assert_eq!(
+        names(groups.members(&QName::new(Some(VEHICLES), "vehicle"))),
+        vec![
+            "{http://example.org/vehicles/ext}truck",
+            "{http://example.org/vehicles}amphibian",
+            "{http://example.org/vehicles}bike",
+            "{http://example.org/vehicles}car",
+            "{http://example.org/vehicles}kit",
+            "{http://example.org/vehicles}kitCar",
+            "{http://example.org/vehicles}sportsCar",
+        ]
+    );
+}
+
+#[test]
+fn allowed_skips_abstract_elements() {
+    let parser = parse();
+    let schema_set = parser.schema_set();
+    let groups = SubstitutionGroups::new(&schema_set);
+
+    assert_eq!(
+        names(groups.allowed(&QName::new(Some(VEHICLES), "vehicle"))),
+        vec![
+            "{http://example.org/vehicles/ext}truck",
+            "{http://example.org/vehicles}amphibian",
+            "{http://example.org/vehicles}bike",
+            "{http://example.org/vehicles}car",
+            "{http://example.org/vehicles}kitCar",
+            "{http://example.org/vehicles}sportsCar",
+        ]
+    );
+}
+
+#[test]
+fn multiple_heads() {
+    let parser = parse();
+    let schema_set = parser.schema_set();
+    let groups = SubstitutionGroups::new(&schema_set);
+
+    assert_eq!(
+        names(groups.allowed(&QName::new(Some(VEHICLES), "boat"))),
+        vec![
+            "{http://example.org/vehicles}amphibian",
+            "{http://example.org/vehicles}boat",
+        ]
+    );
+}
+
+#[test]
+fn blocked_substitution() {
+    let parser = parse();
+    let schema_set = parser.schema_set();
+    let groups = SubstitutionGroups::new(&schema_set);
+    let fleet = QName::new(Some(VEHICLES), "fleet");
+
+    assert_eq!(names(groups.members(&fleet)).len(), 1);
+    assert_eq!(
+        names(groups.allowed(&fleet)),
+        vec!["{http://example.org/vehicles}fleet"]
+    );
+}
+
+#[test]
+fn blocked_substitution_in_list() {
+    let parser = parse();
+    let schema_set = parser.schema_set();
+    let groups = SubstitutionGroups::new(&schema_set);
+
+    assert_eq!(
+        names(groups.allowed(&QName::new(Some(VEHICLES), "depot"))),
+        vec!["{http://example.org/vehicles}depot"]
+    );
+    assert_eq!(
+        names(groups.allowed(&QName::new(Some(VEHICLES), "ferry"))),
+        vec![
+            "{http://example.org/vehicles}ferry",
+            "{http://example.org/vehicles}hovercraft",
+        ]
+    );
+}
+
+#[test]
+fn allowed_at_particle() {
+    let parser = parse();
+    let schema_set = parser.schema_set();
+    let groups = SubstitutionGroups::new(&schema_set);
+
+    let garage = schema_set
+        .complex_type(&QName::new(Some(VEHICLES), "Garage"))
+        .unwrap();
+    let particle = match garage.component.sequence.as_ref().unwrap().particles[0] {
+        NestedParticle::Element(ref element) => element,
+        ref other => panic!("unexpected particle: {:?}", other),
+    };
+
+    assert_eq!(
+        names(groups.allowed_at(particle, garage)),
+        names(groups.allowed(&QName::new(Some(VEHICLES), "vehicle")))
+    );
+}
@@ -0,0 +1,101 @@
+use std::cmp::Ordering;
+
+use xsd::{DateTime, Duration};
+
+fn date_time(primitive: &str, value: &str) -> DateTime {
+    DateTime::parse(primitive, value).unwrap()
+}
+
+fn compare(primitive: &str, a: &str, b: &str) -> Option<Ordering> {
+    date_time(primitive, a).partial_cmp(&date_time(primitive, b))
+}
+
+fn duration(value: &str) -> Duration {
+    Duration::parse(value).unwrap()
+}
+
+#[test]
+fn parse_fields() {
+    let value = date_time("dateTime", "-0044-03-15T12:30:05.250+01:30");
+    assert_eq!(
+        (value.year, value.month, value.day),
+        (Some(-44), Some(3), Some(15))
+    );
+    assert_eq!(
+        (value.hour, value.minute, value.second),
+        (Some(12), Some(30), Some(5))
+    );
+    assert_eq!((value.nanosecond, value.timezone), (250_000_000, Some(90)));
+    assert_eq!(value.to_string(), "-0044-03-15T12:30:05.25+01:30");
+
+    let midnight = date_time("dateTime", "1999-12-31T24:00:00Z");
+    assert_eq!(midnight.to_string(), "2000-01-01T00:00:00Z");
+
+    assert_eq!(date_time("gMonthDay", "--02-29").to_string(), "--02-29");
+    assert_eq!(date_time("gDay", "---07-05:00").to_string(), "---07-05:00");
+    assert_eq!(date_time("time", "08:00:00").to_string(), "08:00:00");
+
+    assert!(DateTime::parse("date", "2001-02-29").is_none());
+    assert!(DateTime::parse("time", "12:00").is_none());
+    assert!(DateTime::parse("string", "2001-02-28").is_none());
+}
+
+#[test]
+fn timezones() {
+    assert_eq!(
+        compare(
+            "dateTime",
+            "2000-01-01T12:00:00Z",
+            "2000-01-01T13:00:00+01:00"
+        ),
+        Some(Ordering::Equal)
+    );
+    assert_eq!(
+        compare("date", "2000-01-01Z", "2000-01-02"),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        compare("dateTime", "2000-01-01T12:00:00Z", "2000-01-01T12:00:00"),
+        None
+    );
+    assert_eq!(
+        compare("dateTime", "2000-01-01T12:00:00", "2000-01-02T03:00:00Z"),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        compare("time", "23:00:00-02:00", "00:30:00Z"),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(compare("gYear", "1999", "2000"), Some(Ordering::Less));
+    assert!(date_time("date", "2000-01-01")
+        .partial_cmp(&date_time("gYear", "2000"))
+        .is_none());
+}
+
+#[test]
+fn durations() {
+    assert_eq!(duration("P1Y"), duration("P12M"));
+    assert_eq!(duration("PT36H"), duration("P1DT12H"));
+    assert_eq!(duration("-PT1.5S").nanoseconds, -1_500_000_000);
+    assert_eq!(duration("P1Y2M3DT4H5M6.7S").to_string(), "P1Y2M3DT4H5M6.7S");
+    assert_eq!(duration("PT0S").to_string(), "PT0S");
+
+    assert_eq!(duration("P1M").partial_cmp(&duration("P30D")), None);
+    assert_eq!(
+        duration("P1M").partial_cmp(&duration("P27D")),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        duration("P1Y").partial_cmp(&duration("P364D")),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(duration("P1Y").partial_cmp(&duration("P365D")), None);
+    assert_eq!(
+        duration("-P1D").partial_cmp(&duration("PT1S")),
+        Some(Ordering::Less)
+    );
+
+    assert!(Duration::parse("P").is_none());
+    assert!(Duration::parse("P1S").is_none());
+    assert!(Duration::parse("P99999999999999999999Y").is_none());
+}
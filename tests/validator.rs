@@ -0,0 +1,294 @@
+use xsd::{Error, Parser, Validator};
+
+const WORK_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+const SCHEMA: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:t="urn:test" targetNamespace="urn:test" elementFormDefault="qualified">
+ <xsd:element name="order">
+  <xsd:complexType>
+   <xsd:sequence>
+    <xsd:element name="customer" type="xsd:string"/>
+    <xsd:element name="item" type="t:item" maxOccurs="3"/>
+    <xsd:element name="note" type="xsd:string" minOccurs="0" nillable="true"/>
+   </xsd:sequence>
+   <xsd:attribute name="id" type="xsd:positiveInteger" use="required"/>
+   <xsd:attribute name="currency" type="xsd:string" fixed="GBP"/>
+  </xsd:complexType>
+ </xsd:element>
+ <xsd:complexType name="item">
+  <xsd:simpleContent>
+   <xsd:extension base="t:quantity">
+    <xsd:attribute name="sku" type="t:sku" use="required"/>
+   </xsd:extension>
+  </xsd:simpleContent>
+ </xsd:complexType>
+ <xsd:element name="few">
+  <xsd:complexType>
+   <xsd:simpleContent>
+    <xsd:restriction base="t:item">
+     <xsd:simpleType>
+      <xsd:restriction base="t:quantity"><xsd:maxInclusive value="9"/></xsd:restriction>
+     </xsd:simpleType>
+    </xsd:restriction>
+   </xsd:simpleContent>
+  </xsd:complexType>
+ </xsd:element>
+ <xsd:simpleType name="quantity">
+  <xsd:restriction base="xsd:int">
+   <xsd:minInclusive value="1"/>
+   <xsd:maxExclusive value="100"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="sku">
+  <xsd:restriction base="xsd:token">
+   <xsd:enumeration value="apple"/>
+   <xsd:enumeration value="pear"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="sizes">
+  <xsd:list itemType="xsd:int"/>
+ </xsd:simpleType>
+ <xsd:simpleType name="size">
+  <xsd:union memberTypes="xsd:int">
+   <xsd:simpleType>
+    <xsd:restriction base="xsd:string"><xsd:enumeration value="large"/></xsd:restriction>
+   </xsd:simpleType>
+  </xsd:union>
+ </xsd:simpleType>
+ <xsd:element name="sizes" type="t:sizes"/>
+ <xsd:element name="size" type="t:size"/>
+ <xsd:element name="shape" abstract="true"/>
+ <xsd:element name="circle" type="xsd:decimal" substitutionGroup="t:shape"/>
+ <xsd:element name="shapes">
+  <xsd:complexType>
+   <xsd:sequence>
+    <xsd:element ref="t:shape" maxOccurs="unbounded"/>
+    <xsd:any namespace="urn:other" processContents="skip" minOccurs="0"/>
+   </xsd:sequence>
+  </xsd:complexType>
+ </xsd:element>
+ <xsd:complexType name="vehicle" block="extension">
+  <xsd:attribute name="wheels" type="xsd:int"/>
+ </xsd:complexType>
+ <xsd:complexType name="car">
+  <xsd:complexContent>
+   <xsd:extension base="t:vehicle"><xsd:attribute name="doors" type="xsd:int"/></xsd:extension>
+  </xsd:complexContent>
+ </xsd:complexType>
+ <xsd:element name="vehicle" type="t:vehicle"/>
+ <xsd:element name="amount" type="xsd:decimal" block="restriction"/>
+</xsd:schema>"#;
+
+fn violations(instance: &str) -> Vec<(&'static str, u32)> {
+    let parser = Parser::parse_str(SCHEMA, Some("order.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+
+    Validator::new(&schema_set)
+        .validate_str(instance, Some("order.xml"))
+        .unwrap()
+        .iter()
+        .map(|d| match d.error {
+            Error::Invalid { rule, ref span, .. } => (rule, span.line),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn valid_instance() {
+    let found = violations(
+        r#"<order xmlns="urn:test" id="7" currency="GBP">
+ <customer>Ann</customer>
+ <item sku=" apple ">3</item>
+ <item sku="pear">99</item>
+ <note xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:nil="true"/>
+</order>"#,
+    );
+
+    assert!(found.is_empty(), "{:?}", found);
+}
+
+#[test]
+fn content_and_attributes() {
+    let found = violations(
+        r#"<order xmlns="urn:test" currency="EUR" extra="1">
+ <item sku="apple">3</item>
+ <item sku="plum">100</item>
+ <item sku="pear">x<b/></item>
+</order>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("cvc-attribute.4", 1),
+            ("cvc-complex-type.3.2.2", 1),
+            ("cvc-complex-type.4", 1),
+            ("cvc-complex-type.2.4.a", 2),
+        ]
+    );
+}
+
+#[test]
+fn simple_values() {
+    let found = violations(
+        r#"<order xmlns="urn:test" id="0">
+ <customer>Ann</customer>
+ <item sku="plum">100</item>
+ <item sku="pear">x</item>
+ <item sku="pear">0</item>
+</order>"#,
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            ("cvc-datatype-valid.1.2.1", 1),
+            ("cvc-enumeration-valid", 3),
+            ("cvc-maxExclusive-valid", 3),
+            ("cvc-datatype-valid.1.2.1", 4),
+            ("cvc-minInclusive-valid", 5),
+        ]
+    );
+}
+
+#[test]
+fn incomplete_content() {
+    let found = violations(
+        r#"<order xmlns="urn:test" id="1">
+ <customer>Ann</customer>
+</order>"#,
+    );
+
+    assert_eq!(found, vec![("cvc-complex-type.2.4.b", 1)]);
+}
+
+#[test]
+fn lists_and_unions() {
+    assert!(violations(r#"<sizes xmlns="urn:test"> 1 2  3 </sizes>"#).is_empty());
+    assert!(violations(r#"<size xmlns="urn:test">large</size>"#).is_empty());
+    assert!(violations(r#"<size xmlns="urn:test">12</size>"#).is_empty());
+
+    assert_eq!(
+        violations(r#"<sizes xmlns="urn:test">1 two</sizes>"#),
+        vec![("cvc-datatype-valid.1.2.1", 1)]
+    );
+    assert_eq!(
+        violations(r#"<size xmlns="urn:test">small</size>"#),
+        vec![("cvc-datatype-valid.1.2.3", 1)]
+    );
+}
+
+#[test]
+fn substitution_groups_and_wildcards() {
+    let found = violations(
+        r#"<shapes xmlns="urn:test">
+ <circle>1.5</circle>
+ <circle>wide</circle>
+ <o:anything xmlns:o="urn:other"><o:x/></o:anything>
+</shapes>"#,
+    );
+
+    assert_eq!(found, vec![("cvc-datatype-valid.1.2.1", 3)]);
+
+    let found = violations(
+        r#"<shapes xmlns="urn:test">
+ <shape/>
+</shapes>"#,
+    );
+
+    assert_eq!(found, vec![("cvc-elt.2", 2)]);
+}
+
+#[test]
+fn undeclared_root_and_xsi_type() {
+    assert_eq!(
+        violations(r#"<unknown xmlns="urn:test"/>"#),
+        vec![("cvc-elt.1.a", 1)]
+    );
+
+    let found = violations(
+        r#"<size xmlns="urn:test" xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="xsd:boolean">12</size>"#,
+    );
+
+    assert_eq!(found, vec![("cvc-elt.4.3", 2)]);
+}
+
+#[test]
+fn blocked_xsi_type() {
+    let found = violations(
+        r#"<circle xmlns="urn:test" xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="xsd:int">12</circle>"#,
+    );
+
+    assert!(found.is_empty(), "{:?}", found);
+
+    let found = violations(
+        r#"<vehicle xmlns="urn:test"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="car" doors="4"/>"#,
+    );
+
+    // The declared type is used instead, which has no `doors`.
+    assert_eq!(
+        found,
+        vec![("cvc-elt.4.3", 2), ("cvc-complex-type.3.2.2", 2)]
+    );
+
+    let found = violations(
+        r#"<amount xmlns="urn:test" xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="xsd:int">12</amount>"#,
+    );
+
+    assert_eq!(found, vec![("cvc-elt.4.3", 2)]);
+}
+
+#[test]
+fn message_names_rule_and_location() {
+    let parser = Parser::parse_str(SCHEMA, Some("order.xsd")).unwrap();
+    let schema_set = parser.schema_set();
+    let diagnostics = Validator::new(&schema_set)
+        .validate_str(
+            r#"<sizes xmlns="urn:test">1 two</sizes>"#,
+            Some("order.xml"),
+        )
+        .unwrap();
+
+    assert_eq!(
+        diagnostics.iter().next().unwrap().to_string(),
+        "error: order.xml:1:1: cvc-datatype-valid.1.2.1: \"two\" is not a valid value of int, \
+         in element {urn:test}sizes"
+    );
+}
+
+#[test]
+fn parsed_document() {
+    let parser = Parser::parse(format!("{}/tests/testdata/vehicles.xsd", WORK_DIR)).unwrap();
+    let schema_set = parser.schema_set();
+    let document =
+        roxmltree::Document::parse(r#"<vehicle xmlns="http://example.org/vehicles"/>"#).unwrap();
+
+    let found: Vec<_> = Validator::new(&schema_set)
+        .validate(&document)
+        .iter()
+        .map(|d| match d.error {
+            Error::Invalid { rule, ref span, .. } => (rule, span.line, span.column),
+            ref other => panic!("unexpected error: {:?}", other),
+        })
+        .collect();
+
+    assert_eq!(found, vec![("cvc-elt.2", 1, 1)]);
+}
+
+#[test]
+fn inline_simple_content() {
+    assert!(violations(r#"<few xmlns="urn:test" sku="pear">9</few>"#).is_empty());
+    assert_eq!(
+        violations(r#"<few xmlns="urn:test" sku="pear">12</few>"#),
+        vec![("cvc-maxInclusive-valid", 1)]
+    );
+    assert_eq!(
+        violations(r#"<few xmlns="urn:test" sku="pear">0</few>"#),
+        vec![("cvc-minInclusive-valid", 1)]
+    );
+}
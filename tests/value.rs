@@ -0,0 +1,211 @@
+use xsd::shared::QName;
+use xsd::{Decimal, Parser, SimpleTypeValidator, TypedValue};
+
+const SCHEMA: &str = r#"<xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema"
+    xmlns:t="urn:test" targetNamespace="urn:test">
+ <xsd:simpleType name="percent">
+  <xsd:restriction base="xsd:decimal">
+   <xsd:minInclusive value="0"/>
+   <xsd:maxInclusive value="100.0"/>
+   <xsd:fractionDigits value="2"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="amount">
+  <xsd:restriction base="xsd:decimal">
+   <xsd:maxInclusive value="9999999999999999.99"/>
+   <xsd:totalDigits value="18"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="level">
+  <xsd:restriction base="xsd:int">
+   <xsd:enumeration value="1"/>
+   <xsd:enumeration value="+2"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="code">
+  <xsd:restriction base="xsd:hexBinary">
+   <xsd:length value="2"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="percentList">
+  <xsd:list itemType="t:percent"/>
+ </xsd:simpleType>
+ <xsd:simpleType name="percents">
+  <xsd:restriction base="t:percentList">
+   <xsd:maxLength value="3"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="sku">
+  <xsd:restriction base="xsd:token">
+   <xsd:pattern value="\d{3}-[A-Z]{2}"/>
+   <xsd:pattern value="none"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="shortSku">
+  <xsd:restriction base="t:sku">
+   <xsd:pattern value="\d{3}.*"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="term">
+  <xsd:restriction base="xsd:date">
+   <xsd:minInclusive value="2020-01-01Z"/>
+   <xsd:maxExclusive value="2021-01-01Z"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="shift">
+  <xsd:restriction base="xsd:dayTimeDuration">
+   <xsd:maxInclusive value="PT12H"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="answer">
+  <xsd:union memberTypes="xsd:boolean xsd:double"/>
+ </xsd:simpleType>
+ <xsd:simpleType name="digit">
+  <xsd:restriction>
+   <xsd:simpleType>
+    <xsd:restriction base="xsd:int"><xsd:minInclusive value="0"/></xsd:restriction>
+   </xsd:simpleType>
+   <xsd:maxExclusive value="10"/>
+   <xsd:enumeration value="07"/>
+   <xsd:enumeration value="-1"/>
+   <xsd:enumeration value="10"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+ <xsd:simpleType name="kind">
+  <xsd:restriction base="xsd:QName">
+   <xsd:enumeration value="t:circle"/>
+  </xsd:restriction>
+ </xsd:simpleType>
+</xsd:schema>"#;
+
+fn check(name: &str, value: &str) -> Result<TypedValue, &'static str> {
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let namespace = if name.starts_with(char::is_uppercase) || name == "boolean" {
+        "http://www.w3.org/2001/XMLSchema"
+    } else {
+        "urn:test"
+    };
+
+    SimpleTypeValidator::named(&schema_set, &QName::new(Some(namespace), name))
+        .unwrap()
+        .validate(value)
+        .map_err(|violation| violation.rule)
+}
+
+fn decimal(value: &str) -> TypedValue {
+    TypedValue::Decimal(Decimal::parse(value).unwrap())
+}
+
+#[test]
+fn values_and_facets() {
+    assert_eq!(check("percent", " 12.50 "), Ok(decimal("12.5")));
+    assert_eq!(check("percent", "100.5"), Err("cvc-maxInclusive-valid"));
+    assert_eq!(check("percent", "1.125"), Err("cvc-fractionDigits-valid"));
+    assert_eq!(check("percent", "-0"), Ok(decimal("0")));
+    assert_eq!(check("percent", "ten"), Err("cvc-datatype-valid.1.2.1"));
+
+    assert_eq!(check("level", "02"), Ok(decimal("2")));
+    assert_eq!(check("level", "3"), Err("cvc-enumeration-valid"));
+
+    assert_eq!(
+        check("code", "0aFF"),
+        Ok(TypedValue::HexBinary(vec![0x0a, 0xff]))
+    );
+    assert_eq!(check("code", "0a"), Err("cvc-length-valid"));
+}
+
+#[test]
+fn inline_base() {
+    // Enumerations are mapped to values of the inline base, and its facets
+    // apply along with those of the restriction.
+    assert_eq!(check("digit", " 7 "), Ok(decimal("7")));
+    assert_eq!(check("digit", "-1"), Err("cvc-minInclusive-valid"));
+    assert_eq!(check("digit", "10"), Err("cvc-maxExclusive-valid"));
+    assert_eq!(check("digit", "x"), Err("cvc-datatype-valid.1.2.1"));
+}
+
+#[test]
+fn exact_decimals() {
+    assert_eq!(
+        check("amount", "9999999999999999.99"),
+        Ok(decimal("9999999999999999.99"))
+    );
+    assert_eq!(
+        check("amount", "9999999999999999.991"),
+        Err("cvc-maxInclusive-valid")
+    );
+    assert_eq!(
+        check("amount", "-999999999999999999.9"),
+        Err("cvc-totalDigits-valid")
+    );
+    assert!(check("amount", "00012345678901234567.00").is_err());
+    assert!(check("amount", "0001234567890123.4500").is_ok());
+}
+
+#[test]
+fn dates_and_durations() {
+    assert!(check("term", "2020-06-30+02:00").is_ok());
+    assert_eq!(check("term", "2019-12-31Z"), Err("cvc-minInclusive-valid"));
+    assert_eq!(check("term", "2021-01-01Z"), Err("cvc-maxExclusive-valid"));
+    assert!(check("term", "2020-12-31").is_ok());
+    // Without a timezone, a value this close to a bound is not ordered
+    // against it.
+    assert_eq!(check("term", "2020-01-01"), Err("cvc-minInclusive-valid"));
+
+    assert!(check("shift", "PT8H30M").is_ok());
+    assert!(check("shift", "PT720M").is_ok());
+    assert_eq!(check("shift", "P1D"), Err("cvc-maxInclusive-valid"));
+    assert_eq!(check("shift", "P1M"), Err("cvc-datatype-valid.1.2.1"));
+}
+
+#[test]
+fn patterns() {
+    assert!(check("sku", " 123-AB ").is_ok());
+    assert!(check("sku", "none").is_ok());
+    assert_eq!(check("sku", "123-ab"), Err("cvc-pattern-valid"));
+
+    // Patterns of different derivation steps must all match.
+    assert!(check("shortSku", "123-AB").is_ok());
+    assert_eq!(check("shortSku", "none"), Err("cvc-pattern-valid"));
+}
+
+#[test]
+fn lists_unions_and_qnames() {
+    assert_eq!(
+        check("percents", "1 2.5"),
+        Ok(TypedValue::List(vec![decimal("1"), decimal("2.5")]))
+    );
+    assert_eq!(check("percents", "1 2 3 4"), Err("cvc-maxLength-valid"));
+    assert_eq!(check("percents", "1 200"), Err("cvc-maxInclusive-valid"));
+
+    assert_eq!(check("answer", "1"), Ok(TypedValue::Boolean(true)));
+    assert_eq!(check("answer", "1e3"), Ok(TypedValue::Double(1000.0)));
+    assert_eq!(check("answer", "maybe"), Err("cvc-datatype-valid.1.2.3"));
+
+    let parser = Parser::parse_str(SCHEMA, None).unwrap();
+    let schema_set = parser.schema_set();
+    let kind =
+        SimpleTypeValidator::named(&schema_set, &QName::new(Some("urn:test"), "kind")).unwrap();
+    let resolve = |value: &str| {
+        value
+            .strip_prefix("s:")
+            .map(|name| QName::new(Some("urn:test"), name))
+    };
+
+    assert_eq!(
+        kind.validate_in("s:circle", &resolve),
+        Ok(TypedValue::QName(QName::new(Some("urn:test"), "circle")))
+    );
+    assert_eq!(
+        kind.validate_in("s:square", &resolve).unwrap_err().rule,
+        "cvc-enumeration-valid"
+    );
+    assert!(kind.validate("s:circle").is_err());
+}
+
+#[test]
+fn builtins() {
+    assert_eq!(check("boolean", "false"), Ok(TypedValue::Boolean(false)));
+    assert!(check("boolean", "no").is_err());
+}